@@ -1,6 +1,6 @@
-use std::io::Write;
+use std::{io::Write, time::Duration};
 use tempfile::NamedTempFile;
-use rusty_chain::framework::ChainLink;
+use rusty_chain::{batch::BatcherInitializer, dead_letter::DeadLetterQueue, framework::ChainLink};
 
 use crate::etl::{etl_process::{EtlProcess, EtlProcessInitializer}, read_file::ReadFromFileInitializer, parse::ParseStringToCustomerInitializer, database::{InsertCustomerIntoDatabaseInitializer, DatabaseRepository}};
 
@@ -9,38 +9,20 @@ mod etl {
 
     // example filename: "read_file.rs"
     pub mod read_file {
-        use std::{io::{BufReader, SeekFrom, Seek, BufRead}, fs::File};
+        use std::{io::{BufReader, BufRead}, fs::File};
         use rusty_chain::chain_link;
 
-        chain_link!(ReadFromFile => (buffer: Option<BufReader<File>>), input: String => String, {
-            if let Some(file_path) = input.received {
-                // store the file buffer in the initializer
-                let mut file = File::open(file_path.read().await.clone()).expect("The file should open.");
-                file.seek(SeekFrom::Start(0)).expect("The file should return to the front.");
-                let mut read_buffer = BufReader::new(file);
-                read_buffer.seek(SeekFrom::Start(0)).expect("The read buffer should return to the front.");
-                let _ = input.initializer.write().await.buffer.replace(read_buffer);
-            }
-
-            let mut locked_initializer = input.initializer.write().await;
-            if let Some(buffer) = locked_initializer.buffer.as_mut() {
-                // read the next line from the file
-                let mut output: String = String::default();
-                let read_bytes_count = buffer
-                    .read_line(&mut output)
-                    .expect("The buffer should return a line.");
-                if read_bytes_count == 0 {
-                    // returning None informs the process that the next file path should be supplied
-                    None
-                }
-                else {
-                    // return the file line
-                    Some(output)
-                }
-            }
-            else {
-                // return None if the file path hasn't been provided yet
-                None
+        // every line of the file is produced from the one file path
+        chain_link!(ReadFromFile, input: String => String, flat, {
+            match input.received {
+                Some(file_path) => {
+                    let file = File::open(file_path.read().await.as_str()).expect("The file should open.");
+                    BufReader::new(file)
+                        .lines()
+                        .map(|line| line.expect("The buffer should return a line."))
+                        .collect::<Vec<String>>()
+                },
+                None => vec![]
             }
         });
     }
@@ -49,7 +31,7 @@ mod etl {
     pub mod models {
 
         // this is in a separate module so that it can be conveniently shared between dependent modules
-        #[derive(Debug)]
+        #[derive(Debug, Clone)]
         pub struct Customer {
             pub customer_name: String,
             pub age: u8
@@ -89,12 +71,13 @@ mod etl {
             }))
         }
 
-        chain_link!(ParseStringToCustomer, input: String => Customer, {
+        // parsing is CPU-bound, so it runs on a blocking thread
+        chain_link!(ParseStringToCustomer, input: String => Customer, blocking, {
             match input.received {
                 Some(received) => {
 
                     // parse the file line using nom
-                    let (_, parsed_customer) = parse_customer(received.read().await.as_str()).unwrap();
+                    let (_, parsed_customer) = parse_customer(received.as_str()).unwrap();
 
                     // return the parsed Customer instance
                     Some(parsed_customer)
@@ -113,18 +96,21 @@ mod etl {
         pub struct DatabaseRepository { }
 
         impl DatabaseRepository {
-            pub fn insert_customer(&self, customer: &Customer) {
-                // pretends that it inserts into database
-                println!("DatabaseRepository: inserted customer {} with age {}", customer.customer_name, customer.age);
+            pub fn insert_customers(&self, customers: &[Customer]) {
+                // pretends that it inserts into database in a single statement
+                for customer in customers {
+                    println!("DatabaseRepository: inserted customer {} with age {}", customer.customer_name, customer.age);
+                }
             }
         }
 
-        chain_link!(InsertCustomerIntoDatabase => (repository: DatabaseRepository), input: Customer => bool, {
+        chain_link!(InsertCustomerIntoDatabase => (repository: DatabaseRepository), input: Vec<Customer> => usize, {
             match input.received {
                 Some(received) => {
-                    // when the Customer is supplied, perform the insert into the database
-                    input.initializer.read().await.repository.insert_customer(&*received.read().await);
-                    Some(true)
+                    // when a batch of Customers is supplied, perform a bulk insert into the database
+                    let customers = received.read().await;
+                    input.initializer.read().await.repository.insert_customers(&customers);
+                    Some(customers.len())
                 },
                 None => None
             }
@@ -133,14 +119,16 @@ mod etl {
 
     // example filename: "etl_process.rs"
     pub mod etl_process {
-        use rusty_chain::chain;
-        use super::{read_file::{ReadFromFile, ReadFromFileInitializer}, parse::{ParseStringToCustomer, ParseStringToCustomerInitializer}, database::{InsertCustomerIntoDatabase, InsertCustomerIntoDatabaseInitializer}};
+        use rusty_chain::{batch::Batcher, chain};
+        use super::{models::Customer, read_file::ReadFromFile, parse::ParseStringToCustomer, database::InsertCustomerIntoDatabase};
+
+        type BatchCustomers = Batcher<Customer>;
 
         // this single line creates the EtlProcess chain
         chain!(EtlProcess,
-            String => bool,
+            String => usize,
             [
-                ReadFromFile => ParseStringToCustomer => InsertCustomerIntoDatabase
+                ReadFromFile => ParseStringToCustomer => BatchCustomers => InsertCustomerIntoDatabase
             ]: (all join)
         );
     }
@@ -158,20 +146,27 @@ async fn main() {
     writeln!(second_file, "Adam Allison,31").unwrap();
     writeln!(second_file, "Brady Brickly,32").unwrap();
     writeln!(second_file, "Charlie Chucks,43").unwrap();
+    writeln!(second_file, "not a customer").unwrap();
 
     // setup chain
     let etl_process = EtlProcess::new_raw(
         EtlProcessInitializer::new(
-            ReadFromFileInitializer {
-                buffer: None
-            },
+            ReadFromFileInitializer { },
             ParseStringToCustomerInitializer { },
+            BatcherInitializer {
+                size: 2,
+                timeout: Duration::from_millis(100)
+            },
             InsertCustomerIntoDatabaseInitializer { 
                 repository: DatabaseRepository { }
             }
         )
     ).await;
 
+    // a line that fails to parse is kept instead of stopping the process
+    let dead_letter_queue = DeadLetterQueue::new();
+    etl_process.set_dead_letter_queue(dead_letter_queue.clone());
+
     // pass in files
     fn get_path_as_string(path: &std::path::Path) -> String {
         (*path).as_os_str().to_str().unwrap().to_string()
@@ -179,18 +174,24 @@ async fn main() {
     etl_process.push_raw(get_path_as_string(first_file.path())).await;
     etl_process.push_raw(get_path_as_string(second_file.path())).await;
 
-    // run ETL process until completed
-    let mut is_successful = true;
-    while is_successful {
-        etl_process.process().await;
-        match etl_process.try_pop().await {
-            Some(popped_is_successful) => {
-                is_successful = *popped_is_successful.read().await;
-            },
-            None => {
-                is_successful = false;
-            }
+    // run ETL process until completed, including the last batch that is smaller than the batch size
+    loop {
+        let outcome = etl_process.process().await;
+        while let Some(inserted_count) = etl_process.try_pop().await {
+            println!("EtlProcess: inserted a batch of {} customers", *inserted_count.read().await);
         }
+        if outcome.is_idle() {
+            break;
+        }
+        if outcome.is_busy {
+            // the last batch is waiting for its timeout
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    for dead_letter in dead_letter_queue.take_all() {
+        let line = dead_letter.input::<String>().expect("The dead letter should hold a file line.");
+        println!("EtlProcess: {} failed on {:?} after {} attempt(s)", dead_letter.path, *line.read().await, dead_letter.attempt_count);
     }
 
     first_file.close().expect("The first file should close.");
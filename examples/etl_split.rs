@@ -9,35 +9,20 @@ mod etl {
 
     // example filename: "read_file.rs"
     pub mod read_file {
-        use std::{io::{BufReader, SeekFrom, Seek, BufRead}, fs::File};
+        use std::{io::{BufReader, BufRead}, fs::File};
         use rusty_chain::chain_link;
 
-        chain_link!(ReadFromFile => (buffer: Option<BufReader<File>>), input: String => String, {
-            if let Some(file_path) = input.received {
-                // store the file buffer in the initializer
-                let mut file = File::open(file_path.read().await.clone()).expect("The file should open.");
-                file.seek(SeekFrom::Start(0)).expect("The file should return to the front.");
-                let mut read_buffer = BufReader::new(file);
-                read_buffer.seek(SeekFrom::Start(0)).expect("The read buffer should return to the front.");
-                let _ = input.initializer.write().await.buffer.replace(read_buffer);
-            }
-
-            let mut locked_initializer = input.initializer.write().await;
-            if let Some(buffer) = locked_initializer.buffer.as_mut() {
-                // read the next line from the file
-                let mut output: String = String::default();
-                let read_bytes_count = buffer
-                    .read_line(&mut output)
-                    .expect("The buffer should return a line.");
-                if read_bytes_count == 0 {
-                    None
-                }
-                else {
-                    Some(output)
-                }
-            }
-            else {
-                None
+        // every line of the file is produced from the one file path
+        chain_link!(ReadFromFile, input: String => String, flat, {
+            match input.received {
+                Some(file_path) => {
+                    let file = File::open(file_path.read().await.as_str()).expect("The file should open.");
+                    BufReader::new(file)
+                        .lines()
+                        .map(|line| line.expect("The buffer should return a line."))
+                        .collect::<Vec<String>>()
+                },
+                None => vec![]
             }
         });
     }
@@ -86,12 +71,13 @@ mod etl {
             }))
         }
 
-        chain_link!(ParseStringToCustomer, input: String => Customer, {
+        // parsing is CPU-bound, so it runs on a blocking thread
+        chain_link!(ParseStringToCustomer, input: String => Customer, blocking, {
             match input.received {
                 Some(received) => {
 
                     // parse the file line using nom
-                    let (_, parsed_customer) = parse_customer(received.read().await.as_str()).unwrap();
+                    let (_, parsed_customer) = parse_customer(received.as_str()).unwrap();
 
                     // return the parsed Customer instance
                     Some(parsed_customer)
@@ -141,7 +127,7 @@ mod etl {
     pub mod separate_database {
         use rusty_chain::chain;
 
-        use super::{models::Customer, database::{InsertCustomerIntoDatabase, InsertCustomerIntoDatabaseInitializer}};
+        use super::{models::Customer, database::InsertCustomerIntoDatabase};
 
         // this split_merge uses a "join" so that it can know if the process has completed
         // the other options (omitted or "unique") do not provide that type of information since they run immediately without waiting for confirmation that something was processed
@@ -157,7 +143,7 @@ mod etl {
     // example filename: "etl_process.rs"
     pub mod etl_process {
         use rusty_chain::chain;
-        use super::{read_file::{ReadFromFile, ReadFromFileInitializer}, parse::{ParseStringToCustomer, ParseStringToCustomerInitializer}, separate_database::{SeparateDatabaseSplitMerge, SeparateDatabaseSplitMergeInitializer}};
+        use super::{read_file::ReadFromFile, parse::ParseStringToCustomer, separate_database::SeparateDatabaseSplitMerge};
 
         chain!(EtlProcess,
             String => bool,
@@ -184,9 +170,7 @@ async fn main() {
     // setup chain
     let etl_process = EtlProcess::new_raw(
         EtlProcessInitializer::new(
-            ReadFromFileInitializer {
-                buffer: None
-            },
+            ReadFromFileInitializer { },
             ParseStringToCustomerInitializer { },
             SeparateDatabaseSplitMergeInitializer::new(
                 InsertCustomerIntoDatabaseInitializer {
@@ -1,13 +1,13 @@
 use std::collections::HashMap;
 
-use madlib::{SpeechPart, MadlibConstructor, MadlibConstructionInitializer, CollectConstructedMadlibPartsInitializer, MadlibPart};
-use rusty_chain::framework::ChainLink;
+use madlib::{SpeechPart, MadlibConstructor, MadlibConstructionInitializer, MadlibPart, ConstructedMadlibPart};
+use rusty_chain::{framework::ChainLink, window::{WindowInitializer, WindowKind}};
 
 
 mod madlib {
     use std::collections::HashMap;
     use rand::seq::SliceRandom;
-    use rusty_chain::{chain_link, chain};
+    use rusty_chain::{chain_link, chain, window::Window};
 
     #[derive(Clone)]
     pub enum MadlibPart {
@@ -23,33 +23,22 @@ mod madlib {
         Adjective
     }
 
+    #[derive(Clone)]
     pub enum ConstructedMadlibPart {
         Word(String),
         End
     }
 
-    // TODO make the possible_words_per_speech_part purely part of the construction and pass in a vector of madlib parts
-
+    // every part of a madlib is constructed at once, each becoming a separate output
     chain_link!(MadlibConstruction => (
-            possible_words_per_speech_part: HashMap<SpeechPart, Vec<String>>,
-            index: usize,
-            madlib_parts: Option<Vec<MadlibPart>>
+            possible_words_per_speech_part: HashMap<SpeechPart, Vec<String>>
         ),
-        input: Vec<MadlibPart> => ConstructedMadlibPart, {
+        input: Vec<MadlibPart> => ConstructedMadlibPart, flat, {
 
-        if let Some(madlib_parts) = input.received {
-            input.initializer.write().await.madlib_parts.replace(madlib_parts.read().await.clone());
-        }
-
-        let mut locked_initializer = input.initializer.write().await;
-        if let Some(madlib_parts) = locked_initializer.madlib_parts.as_ref() {
-            if locked_initializer.index == madlib_parts.len() {
-                None
-            }
-            else
-            {
-                let index = locked_initializer.index;
-                let word = match &madlib_parts[index] {
+        match input.received {
+            Some(madlib_parts) => {
+                let locked_initializer = input.initializer.read().await;
+                madlib_parts.read().await.iter().map(|madlib_part| match madlib_part {
                     MadlibPart::Static(text) => {
                         ConstructedMadlibPart::Word(text.clone())
                     },
@@ -60,35 +49,15 @@ mod madlib {
                     MadlibPart::End => {
                         ConstructedMadlibPart::End
                     }
-                };
-                locked_initializer.index += 1;
-                Some(word)
-            }
-        }
-        else {
-            None
-        }
-    });
-
-    chain_link!(CollectConstructedMadlibParts => ( buffer: Vec<String> ), input: ConstructedMadlibPart => String, {
-        match input.received {
-            Some(constructed_madlib_part) => {
-                match &*constructed_madlib_part.read().await {
-                    ConstructedMadlibPart::End => {
-                        let output = Some(input.initializer.read().await.buffer.join(" "));
-                        input.initializer.write().await.buffer.clear();
-                        output
-                    },
-                    ConstructedMadlibPart::Word(word) => {
-                        input.initializer.write().await.buffer.push(word.clone());
-                        None
-                    }
-                }
+                }).collect::<Vec<ConstructedMadlibPart>>()
             },
-            None => None
+            None => vec![]
         }
     });
 
+    // the words of a madlib are collected until its end
+    type CollectConstructedMadlibParts = Window<ConstructedMadlibPart, String, fn(&[ConstructedMadlibPart]) -> String>;
+
     chain!(MadlibConstructor,
         Vec<MadlibPart> => String,
         [
@@ -120,12 +89,20 @@ async fn main() {
     let madlib_constructor = MadlibConstructor::new_raw(
         madlib::MadlibConstructorInitializer::new(
             MadlibConstructionInitializer {
-                possible_words_per_speech_part,
-                index: 0,
-                madlib_parts: None
+                possible_words_per_speech_part
             },
-            CollectConstructedMadlibPartsInitializer {
-                buffer: vec![]
+            WindowInitializer {
+                kind: WindowKind::Delimited(|part| matches!(part, ConstructedMadlibPart::End)),
+                reducer: |parts| {
+                    parts
+                        .iter()
+                        .filter_map(|part| match part {
+                            ConstructedMadlibPart::Word(word) => Some(word.as_str()),
+                            ConstructedMadlibPart::End => None
+                        })
+                        .collect::<Vec<&str>>()
+                        .join(" ")
+                }
             }
         )
     ).await;
@@ -144,7 +121,7 @@ async fn main() {
     // iterate until the process has completed
     let is_processed = madlib_constructor.process().await;
 
-    assert!(is_processed);
+    assert!(is_processed.is_produced());
 
     let output = madlib_constructor
         .try_pop()
@@ -6,24 +6,28 @@ mod mapper_example {
 
     use std::time::Duration;
 
-    use rusty_chain::chain_link;
+    use rusty_chain::{chain_link, clock::Clock};
 
     pub struct ChildRecord {
         parent_id: i32,
         image_bytes: Vec<u8>
     }
 
-    pub struct DatabaseConnection {}
+    pub struct DatabaseConnection {
+        clock: Clock
+    }
 
     impl DatabaseConnection {
-        pub fn new(_connection_string: String) -> Self {
-            DatabaseConnection { }
+        pub fn new(_connection_string: String, clock: Clock) -> Self {
+            DatabaseConnection {
+                clock
+            }
         }
     }
 
     impl DatabaseConnection {
         pub async fn get_parent_by_parent_id(&self, parent_id: i32) -> ParentRecord {
-            tokio::time::sleep(Duration::from_millis(500)).await;
+            self.clock.sleep(Duration::from_millis(500)).await;
             ParentRecord {
                 parent_id,
                 name: String::from("Some name")
@@ -31,7 +35,7 @@ mod mapper_example {
         }
         pub async fn get_child_records_by_parent_id(&self, parent_id: i32) -> Vec<ChildRecord> {
             // return two child records
-            tokio::time::sleep(Duration::from_millis(500)).await;
+            self.clock.sleep(Duration::from_millis(500)).await;
             vec![
                 ChildRecord {
                     parent_id,
@@ -68,11 +72,12 @@ mod mapper_example {
         }
     }
 
-    chain_link!(GetParentById => (connection_string: String), input: GetParentByIdInput => ParentModel, {
+    // each lookup mostly waits on the database, so several are run at once while keeping the models in the order they were requested
+    chain_link!(GetParentById => (connection_string: String), input: GetParentByIdInput => ParentModel, concurrency = 4, ordered, {
         match input.received {
             Some(parent_id_container) => {
                 // the connection string was part of the initializer, so we can create our database connection on demand
-                let database_connection = DatabaseConnection::new(input.initializer.read().await.connection_string.clone());
+                let database_connection = DatabaseConnection::new(input.initializer.read().await.connection_string.clone(), input.clock.clone());
                 let parent_id = parent_id_container.read().await.parent_id;
                 let parent_record = database_connection.get_parent_by_parent_id(parent_id).await;
                 let child_records = database_connection.get_child_records_by_parent_id(parent_id).await;
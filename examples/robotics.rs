@@ -1,17 +1,13 @@
 use std::time::Duration;
 use robotics::{automated_robot::{AutomatedRobot, AutomatedRobotInitializer}, sensory_split::SensorySplitInitializer, camera_sensor::CameraSensorInitializer, sensor_processor::SensorProcessorInitializer, robot_interface::RobotInterfaceInitializer, dependency::{Robot, Controller, Camera}, controller_sensor::ControllerSensorInitializer};
-use rusty_chain::framework::ChainLink;
+use rusty_chain::{conflate::ConflateInitializer, expire::{ExpireInitializer, Expiry}, framework::ChainLink, port::ToPortInitializer};
 
 mod robotics {
 
     pub mod model {
         use rand::Rng;
         
-        pub enum SensorData {
-            Camera(Direction),
-            Controller(KeyPress)
-        }
-
+        #[derive(Clone)]
         pub enum Direction {
             Left,
             Straight,
@@ -28,6 +24,7 @@ mod robotics {
             }
         }
 
+        #[derive(Clone)]
         pub enum KeyPress {
             Stop,
             Go
@@ -51,6 +48,7 @@ mod robotics {
 
     pub mod dependency {
         use std::{time::Duration, sync::Arc};
+        use rusty_chain::clock::Clock;
         use tokio::sync::Mutex;
         use super::model::{Direction, KeyPress, Facing};
         
@@ -61,10 +59,10 @@ mod robotics {
             pub fn new() -> Self {
                 Camera { }
             }
-            pub async fn read_instruction_under_robot(&self) -> Direction {
+            pub async fn read_instruction_under_robot(&self, clock: &Clock) -> Direction {
 
                 // pretend that reading from the camera is somewhat slow
-                tokio::time::sleep(Duration::from_millis(1000)).await;
+                clock.sleep(Duration::from_millis(1000)).await;
 
                 // actual camera read would occur here
                 Direction::choose(&mut rand::thread_rng())
@@ -204,15 +202,15 @@ mod robotics {
 
     pub mod camera_sensor {
         use rusty_chain::chain_link;
-        use crate::robotics::model::SensorData;
+        use crate::robotics::model::Direction;
         use super::dependency::Camera;
 
-        chain_link!(CameraSensor => (camera: Camera), input: () => SensorData, {
+        chain_link!(CameraSensor => (camera: Camera), input: () => Direction, {
             match input.received {
                 Some(_) => {
                     println!("{}: CameraSensor", chrono::Utc::now().timestamp());
-                    let direction = input.initializer.read().await.camera.read_instruction_under_robot().await;
-                    Some(SensorData::Camera(direction))
+                    let direction = input.initializer.read().await.camera.read_instruction_under_robot(&input.clock).await;
+                    Some(direction)
                 },
                 None => None
             }
@@ -221,18 +219,13 @@ mod robotics {
 
     pub mod controller_sensor {
         use rusty_chain::chain_link;
-        use super::{model::SensorData, dependency::Controller};
+        use super::{model::KeyPress, dependency::Controller};
 
-        chain_link!(ControllerSensor => (controller: Controller), input: () => SensorData, {
+        chain_link!(ControllerSensor => (controller: Controller), input: () => KeyPress, {
             match input.received {
                 Some(_) => {
                     println!("{}: ControllerSensor", chrono::Utc::now().timestamp());
-                    if let Some(key_press) = input.initializer.write().await.controller.read_last_keypress().await {
-                        Some(SensorData::Controller(key_press))
-                    }
-                    else {
-                        None
-                    }
+                    input.initializer.write().await.controller.read_last_keypress().await
                 },
                 None => None
             }
@@ -242,14 +235,15 @@ mod robotics {
     pub mod sensor_processor {
         use rusty_chain::chain_link;
         use crate::robotics::model::RobotAction;
-        use super::model::{SensorData, Direction, KeyPress};
+        use super::model::{Direction, KeyPress};
 
-        chain_link!(SensorProcessor, input: SensorData => RobotAction, {
+        // the camera and the controller each push into their own port
+        chain_link!(SensorProcessor, input: [camera: Direction, controller: KeyPress] => RobotAction, {
             match input.received {
                 Some(sensor_data) => {
                     println!("{}: SensorProcessor", chrono::Utc::now().timestamp());
                     match &*sensor_data.read().await {
-                        SensorData::Camera(direction) => {
+                        SensorProcessorPortInput::Camera(direction) => {
                             match direction {
                                 Direction::Left => {
                                     Some(RobotAction::MoveLeft)
@@ -262,7 +256,7 @@ mod robotics {
                                 }
                             }
                         },
-                        SensorData::Controller(key_press) => {
+                        SensorProcessorPortInput::Controller(key_press) => {
                             match key_press {
                                 KeyPress::Go => {
                                     Some(RobotAction::Startup)
@@ -313,29 +307,37 @@ mod robotics {
     }
 
     pub mod sensory_split {
-        use rusty_chain::chain;
+        use rusty_chain::{chain, conflate::Conflate, port::ToPort};
+
+        use super::{controller_sensor::ControllerSensor, camera_sensor::CameraSensor, model::Direction, sensor_processor::{SensorProcessorCamera, SensorProcessorController, SensorProcessorPortInput}};
 
-        use super::{model::SensorData, controller_sensor::{ControllerSensor, ControllerSensorInitializer}, camera_sensor::{CameraSensor, CameraSensorInitializer}};
+        // only the most recent direction matters, so a stale one is replaced rather than queued
+        type LatestDirection = Conflate<Direction>;
+        type CameraToSensorProcessor = ToPort<SensorProcessorCamera>;
+        type ControllerToSensorProcessor = ToPort<SensorProcessorController>;
 
         // the split_merge is not joined, so this runs each sensor in separate threads
         // each parallel chainlink will also not run again until a previous instance has completed due to the "unique" keyword used below
         chain!(SensorySplit,
-            () => SensorData,
+            () => SensorProcessorPortInput,
             [
-                CameraSensor,
-                ControllerSensor
+                CameraSensor => LatestDirection => CameraToSensorProcessor,
+                ControllerSensor => ControllerToSensorProcessor
             ]: (all unique)
         );
     }
 
     pub mod automated_robot {
-        use rusty_chain::chain;
-        use super::{sensory_split::{SensorySplit, SensorySplitInitializer}, sensor_processor::{SensorProcessor, SensorProcessorInitializer}, robot_interface::{RobotInterface, RobotInterfaceInitializer}};
+        use rusty_chain::{chain, expire::Expire};
+        use super::{sensory_split::SensorySplit, sensor_processor::SensorProcessor, robot_interface::RobotInterface};
+
+        // the robot never acts on an action that has waited too long to still be right
+        type FreshRobotInterface = Expire<RobotInterface>;
 
         chain!(AutomatedRobot,
             () => bool,
             [
-                SensorySplit => SensorProcessor => RobotInterface
+                SensorySplit => SensorProcessor => FreshRobotInterface
             ]: (all join)
         );
     }
@@ -358,14 +360,17 @@ fn main() {
                     CameraSensorInitializer {
                         camera: Camera::new()
                     },
+                    ConflateInitializer,
+                    ToPortInitializer,
                     ControllerSensorInitializer {
                         controller: Controller::new()
-                    }
+                    },
+                    ToPortInitializer
                 ),
                 SensorProcessorInitializer { },
-                RobotInterfaceInitializer {
+                ExpireInitializer::new(Duration::from_secs(2), Expiry::Drop, RobotInterfaceInitializer {
                     robot: Robot::new()
-                }
+                })
             )
         ).await;
 
@@ -1,12 +1,12 @@
-use rusty_chain::framework::ChainLink;
-use work_order::{work_assignment_manager::WorkAssignmentManagerInitializer, model::{WorkSystemCache, OrderEvent, Order, WorkType, Customer, WorkerAvailability, Worker}, work_processor::{WorkProcessor, WorkProcessorInitializer}, unit_of_work_manager::UnitOfWorkManagerInitializer};
+use rusty_chain::{framework::ChainLink, join::{Fairness, JoinKind, KeyedJoinInitializer}};
+use work_order::{model::{OrderEvent, Order, WorkType, Customer, WorkerAvailability, Worker}, work_processor::{WorkProcessor, WorkProcessorInitializer}, unit_of_work_manager::UnitOfWorkManagerInitializer};
 
 mod work_order {
 
     // the shared types between other modules
     pub mod model {
         use std::fmt::Display;
-        use dashmap::DashMap;
+        use rusty_chain::join::JoinInput;
 
         #[derive(PartialEq, Eq, Hash, Clone, Debug)]
         pub enum WorkType {
@@ -42,179 +42,40 @@ mod work_order {
             pub worker: Worker
         }
 
-        pub enum OrderEvent {
-            AddOrder(Order),
-            AddWorkerAvailability(WorkerAvailability)
-        }
-
-        pub struct AssignedOrder {
-            pub work_type: WorkType,
-            pub customer: Customer,
-            pub worker: Worker
-        }
-
-        pub enum EventOutcome {
-            OrderAssignedToWorker(AssignedOrder),
-            NothingAssigned
-        }
-
-        // This contains all of the data pertaining to the work system.
-        // In a true implementation, this struct would interact with a database.
-        pub struct WorkSystemCache {
-            pub customers_per_work_type: DashMap<WorkType, tokio::sync::Mutex<Vec<Customer>>>,
-            pub workers_per_work_type: DashMap<WorkType, tokio::sync::Mutex<Vec<Worker>>>
-        }
-
-        impl WorkSystemCache {
-            pub fn new() -> Self {
-                WorkSystemCache {
-                    customers_per_work_type: DashMap::new(),
-                    workers_per_work_type: DashMap::new()
-                }
-            }
-        }
+        // an order is the left input and a worker's availability is the right input
+        pub type OrderEvent = JoinInput<Order, WorkerAvailability>;
     }
 
     pub mod work_assignment_manager {
-        use rusty_chain::chain_link;
-        use crate::work_order::model::{EventOutcome, AssignedOrder};
-        use super::model::{WorkSystemCache, OrderEvent};
-
-        // reacts to an order event, potentially assigning a customer's work to a worker
-        chain_link!(WorkAssignmentManager => (work_system_cache: WorkSystemCache), input: OrderEvent => EventOutcome, {
-            match input.received {
-                Some(order_event) => {
-                    match &*order_event.read().await {
-                        OrderEvent::AddOrder(order) => {
-
-                            // ensure that the work system contains the work type
-                            {
-                                let locked_work_system_cache = &input.initializer.write().await.work_system_cache;
-                                if !locked_work_system_cache.customers_per_work_type.contains_key(&order.work_type) {
-                                    locked_work_system_cache.customers_per_work_type.insert(order.work_type.clone(), tokio::sync::Mutex::new(vec![]));
-                                }
-                                if !locked_work_system_cache.workers_per_work_type.contains_key(&order.work_type) {
-                                    locked_work_system_cache.workers_per_work_type.insert(order.work_type.clone(), tokio::sync::Mutex::new(vec![]));
-                                }
-                            }
-
-                            // check for an available worker
-
-                            if let Some(popped_worker) = &input.initializer
-                                .read()
-                                .await
-                                .work_system_cache
-                                .workers_per_work_type
-                                .get(&order.work_type)
-                                .expect(&format!("The work type {} should exist in the workers_per_work_type.", order.work_type))
-                                .lock()
-                                .await
-                                .pop() {
-                                
-                                    // there is a worker available for this customer's work order
-                                    Some(EventOutcome::OrderAssignedToWorker(AssignedOrder {
-                                        work_type: order.work_type.clone(),
-                                        worker: popped_worker.clone(),
-                                        customer: order.customer.clone()
-                                    }))
-                            }
-                            else {
-
-                                // cache the customer's work for when a worker is available
-                                input.initializer
-                                    .read()
-                                    .await
-                                    .work_system_cache
-                                    .customers_per_work_type
-                                    .get(&order.work_type)
-                                    .unwrap()
-                                    .lock()
-                                    .await
-                                    .push(order.customer.clone());
-
-                                Some(EventOutcome::NothingAssigned)
-                            }
-
-                        },
-                        OrderEvent::AddWorkerAvailability(worker_availability) => {
-
-                            // ensure that the work system contains the work type
-                            {
-                                let locked_work_system_cache = &input.initializer.write().await.work_system_cache;
-                                if !locked_work_system_cache.customers_per_work_type.contains_key(&worker_availability.work_type) {
-                                    locked_work_system_cache.customers_per_work_type.insert(worker_availability.work_type.clone(), tokio::sync::Mutex::new(vec![]));
-                                }
-                                if !locked_work_system_cache.workers_per_work_type.contains_key(&worker_availability.work_type) {
-                                    locked_work_system_cache.workers_per_work_type.insert(worker_availability.work_type.clone(), tokio::sync::Mutex::new(vec![]));
-                                }
-                            }
-
-                            // check for an applicable order
+        use rusty_chain::join::KeyedJoin;
+        use super::model::{Order, WorkerAvailability, WorkType};
 
-                            if let Some(popped_customer) = &input.initializer
-                                .read()
-                                .await
-                                .work_system_cache
-                                .customers_per_work_type
-                                .get(&worker_availability.work_type)
-                                .expect(&format!("The work type {} should exist in the workers_per_work_type.", worker_availability.work_type))
-                                .lock()
-                                .await
-                                .pop() {
-                                
-                                    // there is a customer's work order ready for this worker
-                                    Some(EventOutcome::OrderAssignedToWorker(AssignedOrder {
-                                        work_type: worker_availability.work_type.clone(),
-                                        worker: worker_availability.worker.clone(),
-                                        customer: popped_customer.clone()
-                                    }))
-                            }
-                            else {
-
-                                // cache the worker until a customer's work order is processed
-                                input.initializer
-                                    .read()
-                                    .await
-                                    .work_system_cache
-                                    .workers_per_work_type
-                                    .get(&worker_availability.work_type)
-                                    .unwrap()
-                                    .lock()
-                                    .await
-                                    .push(worker_availability.worker.clone());
-
-                                Some(EventOutcome::NothingAssigned)
-                            }
-                        }
-                    }
-                },
-                None => None
-            }
-        });
+        // pairs each customer's order with a worker who is available for the same type of work
+        pub type WorkAssignmentManager = KeyedJoin<Order, WorkerAvailability, WorkType>;
     }
 
     pub mod unit_of_work_manager {
         use rusty_chain::chain_link;
-        use super::model::{EventOutcome, WorkType};
+        use super::model::{Order, WorkerAvailability, WorkType};
 
-        // processes the assigned order, if applicable
-        chain_link!(UnitOfWorkManager, input: EventOutcome => bool, {
+        // processes the assigned order
+        chain_link!(UnitOfWorkManager, input: (Order, Option<WorkerAvailability>) => bool, {
             match input.received {
-                Some(event_outcome) => {
-                    match &*event_outcome.read().await {
-                        EventOutcome::OrderAssignedToWorker(assigned_order) => {
-                            match assigned_order.work_type {
+                Some(assigned_order) => {
+                    match &*assigned_order.read().await {
+                        (order, Some(worker_availability)) => {
+                            match order.work_type {
                                 WorkType::InvestigateAccount => {
-                                    println!("The worker {} investigated the account of {}.", assigned_order.worker.name, assigned_order.customer.name);
+                                    println!("The worker {} investigated the account of {}.", worker_availability.worker.name, order.customer.name);
                                     Some(true)
                                 },
                                 WorkType::CallCustomer => {
-                                    println!("The worker {} called the customer {}.", assigned_order.worker.name, assigned_order.customer.name);
+                                    println!("The worker {} called the customer {}.", worker_availability.worker.name, order.customer.name);
                                     Some(true)
                                 }
                             }
                         },
-                        EventOutcome::NothingAssigned => None
+                        (_, None) => None
                     }
                 },
                 None => None
@@ -224,7 +85,7 @@ mod work_order {
 
     pub mod work_processor {
         use rusty_chain::chain;
-        use super::{unit_of_work_manager::{UnitOfWorkManager, UnitOfWorkManagerInitializer}, work_assignment_manager::{WorkAssignmentManager, WorkAssignmentManagerInitializer}, model::OrderEvent};
+        use super::{unit_of_work_manager::UnitOfWorkManager, work_assignment_manager::WorkAssignmentManager, model::OrderEvent};
 
         // the chain of processing from an order event to a processed work order
         chain!(WorkProcessor,
@@ -243,14 +104,18 @@ async fn main() {
 
     let work_processor = WorkProcessor::new_raw(
         WorkProcessorInitializer::new(
-            WorkAssignmentManagerInitializer {
-                work_system_cache: WorkSystemCache::new()
+            KeyedJoinInitializer {
+                left_key: |order| order.work_type.clone(),
+                right_key: |worker_availability| worker_availability.work_type.clone(),
+                kind: JoinKind::Inner,
+                fairness: Fairness::Oldest,
+                ttl: None
             },
             UnitOfWorkManagerInitializer { }
         )
     ).await;
 
-    work_processor.push_raw(OrderEvent::AddOrder(Order {
+    work_processor.push_raw(OrderEvent::Left(Order {
         work_type: WorkType::CallCustomer,
         customer: Customer {
             name: String::from("John")
@@ -258,9 +123,9 @@ async fn main() {
     })).await;
 
     // there are no pairs yet
-    assert!(!work_processor.process().await);
+    assert!(!work_processor.process().await.is_produced());
 
-    work_processor.push_raw(OrderEvent::AddWorkerAvailability(WorkerAvailability {
+    work_processor.push_raw(OrderEvent::Right(WorkerAvailability {
         work_type: WorkType::InvestigateAccount,
         worker: Worker {
             name: String::from("Bob")
@@ -268,9 +133,9 @@ async fn main() {
     })).await;
 
     // there are no pairs yet
-    assert!(!work_processor.process().await);
+    assert!(!work_processor.process().await.is_produced());
 
-    work_processor.push_raw(OrderEvent::AddWorkerAvailability(WorkerAvailability {
+    work_processor.push_raw(OrderEvent::Right(WorkerAvailability {
         work_type: WorkType::CallCustomer,
         worker: Worker {
             name: String::from("Bill")
@@ -278,9 +143,9 @@ async fn main() {
     })).await;
 
     // a customer needing a call and a worker who can call are now present
-    assert!(work_processor.process().await);
+    assert!(work_processor.process().await.is_produced());
 
-    work_processor.push_raw(OrderEvent::AddOrder(Order {
+    work_processor.push_raw(OrderEvent::Left(Order {
         work_type: WorkType::InvestigateAccount,
         customer: Customer {
             name: String::from("Jane")
@@ -288,5 +153,5 @@ async fn main() {
     })).await;
 
     // a customer needing their account investigated and a worker who investigates are now present
-    assert!(work_processor.process().await);
-}
\ No newline at end of file
+    assert!(work_processor.process().await.is_produced());
+}
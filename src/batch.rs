@@ -0,0 +1,143 @@
+use std::{sync::Arc, time::{Duration, Instant}};
+use tokio::sync::{Mutex, RwLock};
+use crate::{clock::Clock, framework::{Initializable, ProcessOutcome}, item::Item, queue::{ArrivalQueue, Queue}};
+
+pub struct BatcherInitializer {
+    // a batch is produced as soon as it holds this many items
+    pub size: usize,
+    // a smaller batch is produced once its first item has waited this long
+    pub timeout: Duration
+}
+
+// collects inputs into batches, producing a batch when it is full or has waited long enough
+// while a batch is waiting, `process` reports that it is busy so that drivers keep processing until it is produced
+// there is no batch! macro, since naming a Batcher within a chain! already batches the outputs of the ChainLink before it
+pub struct Batcher<T> {
+    size: usize,
+    timeout: Duration,
+    input_queue: ArrivalQueue<T>,
+    // each item along with when it was pushed, so that the items left over after a batch keep how long they have waited
+    pending: Mutex<Vec<(Instant, T)>>,
+    output_queue: Queue<Item<Vec<T>>>
+}
+
+impl<T> Batcher<T> {
+    pub fn new(size: usize, timeout: Duration) -> Self {
+        assert!(size != 0, "The batch size should be at least one.");
+        Batcher {
+            size,
+            timeout,
+            input_queue: ArrivalQueue::new(),
+            pending: Mutex::new(vec![]),
+            output_queue: Queue::default()
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> Initializable for Batcher<T> {
+    type TInitializer = BatcherInitializer;
+
+    async fn initialize(initializer: Arc<RwLock<BatcherInitializer>>) -> Self {
+        let locked_initializer = initializer.read().await;
+        Batcher::new(locked_initializer.size, locked_initializer.timeout)
+    }
+}
+
+impl_chain_link! {
+    impl[T] Batcher<T>
+    where [
+        T: Clone + Send + Sync
+    ]
+    (T => Vec<T>)
+    queued
+    {
+        fn set_clock(&self, clock: Clock) {
+            self.input_queue.set_clock(clock);
+        }
+        async fn process(&self) -> ProcessOutcome {
+            let clock = self.input_queue.clock();
+            let mut locked_pending = self.pending.lock().await;
+            let received_items = self.input_queue.try_pop_up_to(usize::MAX).await;
+            let consumed_count = received_items.len();
+            for (pushed, received) in received_items {
+                let received = received.into_owned().await;
+                locked_pending.push((pushed, received));
+            }
+
+            let mut produced_count: usize = 0;
+            while locked_pending.len() >= self.size {
+                let batch = locked_pending.drain(..self.size).map(|(_, item)| item).collect();
+                self.output_queue.push(Item::Owned(batch)).await;
+                produced_count += 1;
+            }
+            // any leftover items start the next batch, which times out based on the first of them to be pushed
+            let is_timed_out = locked_pending.first().is_some_and(|(started, _)| clock.elapsed_since(*started) >= self.timeout);
+            if is_timed_out {
+                let batch = locked_pending.drain(..).map(|(_, item)| item).collect();
+                self.output_queue.push(Item::Owned(batch)).await;
+                produced_count += 1;
+            }
+
+            let mut outcome = ProcessOutcome::consumed(consumed_count, produced_count);
+            outcome.is_busy = !locked_pending.is_empty();
+            outcome
+        }
+    }
+}
+
+pub struct UnbatchInitializer;
+
+// produces each item of a batch in order
+pub struct Unbatch<T> {
+    input_queue: Queue<Item<Vec<T>>>,
+    output_queue: Queue<Item<T>>
+}
+
+impl<T> Unbatch<T> {
+    pub fn new() -> Self {
+        Unbatch {
+            input_queue: Queue::default(),
+            output_queue: Queue::default()
+        }
+    }
+}
+
+impl<T> Default for Unbatch<T> {
+    fn default() -> Self {
+        Unbatch::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> Initializable for Unbatch<T> {
+    type TInitializer = UnbatchInitializer;
+
+    async fn initialize(_initializer: Arc<RwLock<UnbatchInitializer>>) -> Self {
+        Unbatch::new()
+    }
+}
+
+impl_chain_link! {
+    impl[T] Unbatch<T>
+    where [
+        T: Clone + Send + Sync
+    ]
+    (Vec<T> => T)
+    queued
+    headed
+    {
+        async fn process(&self) -> ProcessOutcome {
+            let Some((batch, headers)) = self.input_queue.try_pop_with_headers().await else {
+                return ProcessOutcome::idle();
+            };
+            let batch = batch.into_owned().await;
+            let produced_count = batch.len();
+            // each item inherits the headers of its batch
+            for item in batch {
+                self.output_queue.push_with_headers(Item::Owned(item), headers.clone()).await;
+            }
+            ProcessOutcome::consumed(1, produced_count)
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use std::{marker::PhantomData, panic::AssertUnwindSafe, sync::Arc, time::{Duration, Instant}};
+use futures::FutureExt;
+use tokio::sync::{Mutex, RwLock, broadcast};
+use crate::{clock::Clock, dead_letter::Failure, framework::{ChainLink, Initializable, ProcessOutcome, WrapperSettings, short_type_name}, item::Item, queue::Queue, side::SideOutput};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    // inputs are passed to the wrapped ChainLink
+    Closed,
+    // inputs are not passed to the wrapped ChainLink until the cooldown has passed
+    Open,
+    // the next input is passed to the wrapped ChainLink as a probe, closing the circuit if it succeeds
+    HalfOpen
+}
+
+// what happens to an input that arrives while the circuit is open
+// an input that fails is handled by the wrapped ChainLink, reaching the dead-letter queue when one is set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenBehavior {
+    // the input goes to `try_pop_fallback`
+    Fallback,
+    // the input waits and is passed to the wrapped ChainLink once the circuit lets it through, during which `process` reports that it is busy
+    Hold
+}
+
+#[derive(Clone)]
+pub struct CircuitBreakerPolicy {
+    // how many failures in a row open the circuit
+    pub failure_threshold: usize,
+    // how long the circuit stays open before probing, measured using the clock of the ChainLink
+    pub cooldown: Duration,
+    pub open_behavior: OpenBehavior
+}
+
+impl Default for CircuitBreakerPolicy {
+    fn default() -> Self {
+        CircuitBreakerPolicy {
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(30),
+            open_behavior: OpenBehavior::Fallback
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitTransition {
+    pub from: CircuitState,
+    pub to: CircuitState,
+    pub at: Instant
+}
+
+// how many transitions a subscriber may fall behind by before missing the oldest
+const TRANSITION_CAPACITY: usize = 64;
+
+pub struct CircuitBreakerInitializer<TChainLinkInitializer> {
+    pub policy: CircuitBreakerPolicy,
+    pub chain_link: Arc<RwLock<TChainLinkInitializer>>
+}
+
+impl<TChainLinkInitializer> CircuitBreakerInitializer<TChainLinkInitializer> {
+    pub fn new(policy: CircuitBreakerPolicy, chain_link: TChainLinkInitializer) -> Self {
+        CircuitBreakerInitializer {
+            policy,
+            chain_link: Arc::new(RwLock::new(chain_link))
+        }
+    }
+}
+
+struct CircuitStatus {
+    state: CircuitState,
+    consecutive_failure_count: usize,
+    opened_at: Option<Instant>
+}
+
+// stops passing inputs to the wrapped ChainLink once it fails, whether by panicking or returning an error, `failure_threshold` times in a row
+// after the cooldown a single input probes whether the wrapped ChainLink has recovered
+// inputs are passed on one at a time, and each succeeds once its output is popped, or once the wrapped ChainLink is done with it without an output
+// a wrapped ChainLink that processes in the background is done with an input once it no longer holds it, failing when it was sent to the dead-letter queue
+// within a chain!, name the type such as `CircuitBreaker<InsertCustomerIntoDatabase>`
+pub struct CircuitBreaker<TChainLink: ChainLink> {
+    policy: CircuitBreakerPolicy,
+    chain_link: TChainLink,
+    input_queue: Queue<Item<TChainLink::TInput>>,
+    // the input passed to the wrapped ChainLink that has neither succeeded nor failed yet
+    in_flight: Mutex<Option<Arc<RwLock<TChainLink::TInput>>>>,
+    output_queue: Queue<Item<TChainLink::TOutput>>,
+    fallback_queue: Queue<Item<TChainLink::TInput>>,
+    status: std::sync::Mutex<CircuitStatus>,
+    transitions: broadcast::Sender<CircuitTransition>,
+    settings: WrapperSettings
+}
+
+impl<TChainLink: ChainLink> CircuitBreaker<TChainLink> {
+    pub fn new(policy: CircuitBreakerPolicy, chain_link: TChainLink) -> Self {
+        assert!(policy.failure_threshold != 0, "The circuit breaker policy should allow at least one failure.");
+        CircuitBreaker {
+            policy,
+            chain_link,
+            input_queue: Queue::default(),
+            in_flight: Mutex::new(None),
+            output_queue: Queue::default(),
+            fallback_queue: Queue::default(),
+            status: std::sync::Mutex::new(CircuitStatus {
+                state: CircuitState::Closed,
+                consecutive_failure_count: 0,
+                opened_at: None
+            }),
+            transitions: broadcast::channel(TRANSITION_CAPACITY).0,
+            settings: WrapperSettings::default()
+        }
+    }
+    pub fn chain_link(&self) -> &TChainLink {
+        &self.chain_link
+    }
+    pub fn state(&self) -> CircuitState {
+        self.status.lock().unwrap().state
+    }
+    // every transition from now on, such as from `Closed` to `Open`
+    pub fn subscribe(&self) -> broadcast::Receiver<CircuitTransition> {
+        self.transitions.subscribe()
+    }
+    // an input that arrived while the circuit was open
+    pub async fn try_pop_fallback(&self) -> Option<Arc<RwLock<TChainLink::TInput>>> {
+        self.fallback_queue.try_pop().await.map(Item::into_shared)
+    }
+    fn transition(&self, status: &mut CircuitStatus, to: CircuitState, at: Instant) {
+        if status.state == to {
+            return;
+        }
+        let transition = CircuitTransition {
+            from: status.state,
+            to,
+            at
+        };
+        status.state = to;
+        if to == CircuitState::Open {
+            status.opened_at = Some(at);
+        }
+        // nobody may be subscribed
+        let _ = self.transitions.send(transition);
+    }
+    // the state the next input meets, moving an open circuit to half-open once the cooldown has passed
+    fn current_state(&self, clock: &Clock) -> CircuitState {
+        let mut locked_status = self.status.lock().unwrap();
+        let is_cooled_down = locked_status.opened_at.is_some_and(|opened_at| clock.elapsed_since(opened_at) >= self.policy.cooldown);
+        if locked_status.state == CircuitState::Open && is_cooled_down {
+            self.transition(&mut locked_status, CircuitState::HalfOpen, clock.now());
+        }
+        locked_status.state
+    }
+    // how long until an open circuit becomes half-open
+    fn cooldown_left(&self, clock: &Clock) -> Option<Duration> {
+        let locked_status = self.status.lock().unwrap();
+        locked_status.opened_at.map(|opened_at| self.policy.cooldown.saturating_sub(clock.elapsed_since(opened_at)))
+    }
+    fn record_success(&self, clock: &Clock) {
+        let mut locked_status = self.status.lock().unwrap();
+        locked_status.consecutive_failure_count = 0;
+        self.transition(&mut locked_status, CircuitState::Closed, clock.now());
+    }
+    fn record_failure(&self, clock: &Clock) {
+        let mut locked_status = self.status.lock().unwrap();
+        locked_status.consecutive_failure_count += 1;
+        if locked_status.state == CircuitState::HalfOpen || locked_status.consecutive_failure_count >= self.policy.failure_threshold {
+            self.transition(&mut locked_status, CircuitState::Open, clock.now());
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<TChainLink> Initializable for CircuitBreaker<TChainLink>
+where
+    TChainLink: ChainLink + Initializable + Send,
+    TChainLink::TInitializer: Send + Sync
+{
+    type TInitializer = CircuitBreakerInitializer<TChainLink::TInitializer>;
+
+    async fn initialize(initializer: Arc<RwLock<CircuitBreakerInitializer<TChainLink::TInitializer>>>) -> Self {
+        let locked_initializer = initializer.read().await;
+        CircuitBreaker::new(locked_initializer.policy.clone(), TChainLink::initialize(locked_initializer.chain_link.clone()).await)
+    }
+}
+
+impl_chain_link! {
+    impl[TChainLink] CircuitBreaker<TChainLink>
+    where [
+        TChainLink: ChainLink + Send + Sync,
+        TChainLink::TInput: Send + Sync + 'static,
+        TChainLink::TOutput: Send + Sync
+    ]
+    (TChainLink::TInput => TChainLink::TOutput)
+    queued
+    headed
+    wrapping chain_link [set_scheduler, set_clock, set_dead_letter_queue, redrive, set_watchdog]
+    {
+        async fn process(&self) -> ProcessOutcome {
+            let clock = self.settings.clock();
+            let mut locked_in_flight = self.in_flight.lock().await;
+            let mut outcome = ProcessOutcome::idle();
+            if locked_in_flight.is_none() {
+                if self.current_state(&clock) == CircuitState::Open {
+                    match self.policy.open_behavior {
+                        OpenBehavior::Fallback => {
+                            if let Some(received) = self.input_queue.try_pop().await {
+                                self.fallback_queue.push(received).await;
+                                outcome.consumed_count = 1;
+                            }
+                        },
+                        OpenBehavior::Hold => {
+                            if !self.input_queue.is_empty().await {
+                                outcome.is_busy = true;
+                                outcome.due_in = self.cooldown_left(&clock);
+                            }
+                        }
+                    }
+                    return outcome;
+                }
+                let Some((received, headers)) = self.input_queue.try_pop_with_headers().await else {
+                    return outcome;
+                };
+                let received = received.into_shared();
+                self.chain_link.push_item_with_headers(Item::Shared(received.clone()), headers).await;
+                *locked_in_flight = Some(received);
+                outcome.consumed_count = 1;
+            }
+
+            let attempt_outcome = AssertUnwindSafe(self.chain_link.process()).catch_unwind().await;
+            while let Some((output, headers)) = self.chain_link.try_pop_item_with_headers().await {
+                self.output_queue.push_with_headers(output, headers).await;
+                outcome.produced_count += 1;
+            }
+            let is_settled = match attempt_outcome {
+                Ok(attempt_outcome) => {
+                    outcome.merge_state(&attempt_outcome);
+                    let in_flight = locked_in_flight.as_ref().expect("An input should be in flight while the wrapped ChainLink is processed.");
+                    let is_dead_lettered = self.settings.dead_letter_queue().is_some_and(|dead_letter_queue| dead_letter_queue.is_dead_lettered(in_flight));
+                    if attempt_outcome.is_failed() || is_dead_lettered {
+                        self.record_failure(&clock);
+                    }
+                    else if outcome.is_produced() || !(attempt_outcome.is_deferred || attempt_outcome.is_busy) || Arc::strong_count(in_flight) == 1 {
+                        self.record_success(&clock);
+                    }
+                    else {
+                        // the wrapped ChainLink is still working on the input
+                        return outcome;
+                    }
+                    true
+                },
+                Err(panic) => {
+                    // the wrapped ChainLink did not catch the panic, so the input is sent on from here
+                    self.record_failure(&clock);
+                    if let (Some(dead_letter_queue), Some(received)) = (self.settings.dead_letter_queue(), locked_in_flight.clone()) {
+                        dead_letter_queue.send(received, short_type_name::<TChainLink>(), Failure::from_panic(panic.as_ref()), 1);
+                    }
+                    outcome.failed_count += 1;
+                    true
+                }
+            };
+            if is_settled {
+                locked_in_flight.take();
+            }
+            outcome
+        }
+    }
+}
+
+// the fallback of a CircuitBreaker as a side output, so that a SideChain can route it to another ChainLink
+// within a chain!, name the type such as `SideChain<CircuitBreakerFallback<InsertCustomerIntoDatabase>, StoreForLater>`
+pub struct CircuitBreakerFallback<TChainLink> {
+    chain_link: PhantomData<TChainLink>
+}
+
+#[async_trait::async_trait]
+impl<TChainLink> SideOutput for CircuitBreakerFallback<TChainLink>
+where
+    TChainLink: ChainLink + Send + Sync,
+    TChainLink::TInput: Send + Sync + 'static,
+    TChainLink::TOutput: Send + Sync
+{
+    type TChainLink = CircuitBreaker<TChainLink>;
+    type TValue = TChainLink::TInput;
+
+    async fn try_pop_item(chain_link: &CircuitBreaker<TChainLink>) -> Option<Item<TChainLink::TInput>> {
+        chain_link.fallback_queue.try_pop().await
+    }
+}
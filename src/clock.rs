@@ -0,0 +1,75 @@
+use std::{sync::Arc, time::{Duration, Instant}};
+use tokio::sync::watch;
+
+// a clock that only moves forward when advanced, so time-dependent chains can be tested without waiting
+pub struct VirtualClock {
+    origin: Instant,
+    elapsed: watch::Sender<Duration>
+}
+
+impl VirtualClock {
+    pub fn new() -> Arc<Self> {
+        Arc::new(VirtualClock {
+            origin: Instant::now(),
+            elapsed: watch::channel(Duration::ZERO).0
+        })
+    }
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.borrow()
+    }
+    // moves time forward, waking every sleep that has finished
+    pub fn advance(&self, duration: Duration) {
+        self.elapsed.send_modify(|elapsed| *elapsed += duration);
+    }
+    async fn sleep_until(&self, elapsed: Duration) {
+        let mut receiver = self.elapsed.subscribe();
+        while *receiver.borrow_and_update() < elapsed {
+            receiver.changed().await.expect("The virtual clock should outlive its sleeps.");
+        }
+    }
+}
+
+// the time source available to map blocks and framework ChainLinks
+// the virtual clock is shared across threads, so background processing honors it regardless of the runtime it runs on
+#[derive(Clone, Default)]
+pub struct Clock {
+    virtual_clock: Option<Arc<VirtualClock>>
+}
+
+impl Clock {
+    pub fn system() -> Self {
+        Clock::default()
+    }
+    pub fn from_virtual(virtual_clock: Arc<VirtualClock>) -> Self {
+        Clock {
+            virtual_clock: Some(virtual_clock)
+        }
+    }
+    pub fn is_virtual(&self) -> bool {
+        self.virtual_clock.is_some()
+    }
+    pub fn now(&self) -> Instant {
+        match self.virtual_clock.as_ref() {
+            Some(virtual_clock) => virtual_clock.origin + virtual_clock.elapsed(),
+            None => Instant::now()
+        }
+    }
+    pub fn elapsed_since(&self, earlier: Instant) -> Duration {
+        self.now().saturating_duration_since(earlier)
+    }
+    pub async fn sleep(&self, duration: Duration) {
+        match self.virtual_clock.as_ref() {
+            Some(virtual_clock) => {
+                let target = virtual_clock.elapsed() + duration;
+                virtual_clock.sleep_until(target).await;
+            },
+            None => {
+                tokio::time::sleep(duration).await;
+            }
+        }
+    }
+    pub async fn sleep_until(&self, deadline: Instant) {
+        let duration = deadline.saturating_duration_since(self.now());
+        self.sleep(duration).await;
+    }
+}
@@ -0,0 +1,233 @@
+use std::{sync::Arc, time::{Duration, Instant}};
+use tokio::sync::RwLock;
+use crate::{clock::Clock, framework::{Initializable, ProcessOutcome}, item::Item, queue::Queue};
+
+pub struct ConflateInitializer;
+
+// keeps only the latest input and output, so a slow consumer never works through stale values
+// within a chain!, place it after a ChainLink such as `CameraSensor => Conflate<Direction>`, since every push replaces what is waiting
+pub struct Conflate<T> {
+    input_queue: LatestSlot<T>,
+    output_queue: Queue<Item<T>>
+}
+
+impl<T> Conflate<T> {
+    pub fn new() -> Self {
+        Conflate {
+            input_queue: LatestSlot::new(),
+            output_queue: Queue::default()
+        }
+    }
+}
+
+impl<T> Default for Conflate<T> {
+    fn default() -> Self {
+        Conflate::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> Initializable for Conflate<T> {
+    type TInitializer = ConflateInitializer;
+
+    async fn initialize(_initializer: Arc<RwLock<ConflateInitializer>>) -> Self {
+        Conflate::new()
+    }
+}
+
+impl_chain_link! {
+    impl[T] Conflate<T>
+    where [
+        T: Send + Sync
+    ]
+    (T => T)
+    queued
+    {
+        async fn process(&self) -> ProcessOutcome {
+            let Some(received) = self.input_queue.try_pop() else {
+                return ProcessOutcome::idle();
+            };
+            self.output_queue.push_replace(received).await;
+            ProcessOutcome::consumed(1, 1)
+        }
+    }
+}
+
+struct Latest<T> {
+    item: Item<T>,
+    pushed_at: Instant
+}
+
+// the latest input waiting to be let through, which each push replaces
+struct LatestSlot<T> {
+    latest: std::sync::Mutex<Option<Latest<T>>>,
+    clock: std::sync::Mutex<Clock>
+}
+
+impl<T> LatestSlot<T> {
+    fn new() -> Self {
+        LatestSlot {
+            latest: std::sync::Mutex::new(None),
+            clock: std::sync::Mutex::new(Clock::default())
+        }
+    }
+    fn now(&self) -> Instant {
+        self.clock.lock().unwrap().now()
+    }
+    // every push replaces the waiting input, so pushing and pushing with latest-wins semantics are the same
+    async fn push(&self, item: Item<T>) {
+        self.push_replace(item).await;
+    }
+    async fn push_replace(&self, item: Item<T>) {
+        let pushed_at = self.now();
+        self.latest.lock().unwrap().replace(Latest {
+            item,
+            pushed_at
+        });
+    }
+    async fn push_if_empty(&self, item: Item<T>) {
+        let pushed_at = self.now();
+        let mut locked_latest = self.latest.lock().unwrap();
+        if locked_latest.is_none() {
+            locked_latest.replace(Latest {
+                item,
+                pushed_at
+            });
+        }
+    }
+    fn try_pop(&self) -> Option<Item<T>> {
+        self.latest.lock().unwrap().take().map(|latest| latest.item)
+    }
+    // takes the latest input when `is_ready` allows it, reporting whether one is still waiting otherwise
+    fn take_if(&self, is_ready: impl FnOnce(&Latest<T>, Instant) -> bool) -> Result<Option<Item<T>>, ()> {
+        let now = self.now();
+        let mut locked_latest = self.latest.lock().unwrap();
+        match locked_latest.as_ref() {
+            None => Ok(None),
+            Some(latest) if is_ready(latest, now) => Ok(locked_latest.take().map(|latest| latest.item)),
+            Some(_) => Err(())
+        }
+    }
+}
+
+pub struct DebounceInitializer {
+    // how long no newer input may arrive before the latest one is let through
+    pub quiet: Duration
+}
+
+// lets through the latest input once no newer one has arrived for `quiet`, measured using the clock of the ChainLink
+// while an input is waiting, `process` reports that it is busy so that drivers keep processing until it is let through
+pub struct Debounce<T> {
+    quiet: Duration,
+    input_queue: LatestSlot<T>,
+    output_queue: Queue<Item<T>>
+}
+
+impl<T> Debounce<T> {
+    pub fn new(quiet: Duration) -> Self {
+        Debounce {
+            quiet,
+            input_queue: LatestSlot::new(),
+            output_queue: Queue::default()
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> Initializable for Debounce<T> {
+    type TInitializer = DebounceInitializer;
+
+    async fn initialize(initializer: Arc<RwLock<DebounceInitializer>>) -> Self {
+        Debounce::new(initializer.read().await.quiet)
+    }
+}
+
+impl_chain_link! {
+    impl[T] Debounce<T>
+    where [
+        T: Send + Sync
+    ]
+    (T => T)
+    queued
+    {
+        fn set_clock(&self, clock: Clock) {
+            *self.input_queue.clock.lock().unwrap() = clock;
+        }
+        async fn process(&self) -> ProcessOutcome {
+            match self.input_queue.take_if(|latest, now| now.saturating_duration_since(latest.pushed_at) >= self.quiet) {
+                Ok(Some(item)) => {
+                    self.output_queue.push(item).await;
+                    ProcessOutcome::consumed(1, 1)
+                },
+                Ok(None) => ProcessOutcome::idle(),
+                Err(()) => ProcessOutcome::busy()
+            }
+        }
+    }
+}
+
+pub struct ThrottleLatestInitializer {
+    // the least time between two inputs being let through
+    pub interval: Duration
+}
+
+// lets through at most one input every `interval`, which is always the latest, measured using the clock of the ChainLink
+// while an input is waiting, `process` reports that it is busy so that drivers keep processing until it is let through
+pub struct ThrottleLatest<T> {
+    interval: Duration,
+    input_queue: LatestSlot<T>,
+    last_let_through: std::sync::Mutex<Option<Instant>>,
+    output_queue: Queue<Item<T>>
+}
+
+impl<T> ThrottleLatest<T> {
+    pub fn new(interval: Duration) -> Self {
+        ThrottleLatest {
+            interval,
+            input_queue: LatestSlot::new(),
+            last_let_through: std::sync::Mutex::new(None),
+            output_queue: Queue::default()
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> Initializable for ThrottleLatest<T> {
+    type TInitializer = ThrottleLatestInitializer;
+
+    async fn initialize(initializer: Arc<RwLock<ThrottleLatestInitializer>>) -> Self {
+        ThrottleLatest::new(initializer.read().await.interval)
+    }
+}
+
+impl_chain_link! {
+    impl[T] ThrottleLatest<T>
+    where [
+        T: Send + Sync
+    ]
+    (T => T)
+    queued
+    {
+        fn set_clock(&self, clock: Clock) {
+            *self.input_queue.clock.lock().unwrap() = clock;
+        }
+        async fn process(&self) -> ProcessOutcome {
+            let taken = {
+                let mut locked_last_let_through = self.last_let_through.lock().unwrap();
+                let taken = self.input_queue.take_if(|_, now| locked_last_let_through.is_none_or(|last_let_through| now.saturating_duration_since(last_let_through) >= self.interval));
+                if let Ok(Some(_)) = taken {
+                    locked_last_let_through.replace(self.input_queue.now());
+                }
+                taken
+            };
+            match taken {
+                Ok(Some(item)) => {
+                    self.output_queue.push(item).await;
+                    ProcessOutcome::consumed(1, 1)
+                },
+                Ok(None) => ProcessOutcome::idle(),
+                Err(()) => ProcessOutcome::busy()
+            }
+        }
+    }
+}
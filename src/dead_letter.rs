@@ -0,0 +1,215 @@
+use std::{any::Any, fmt::{Debug, Display}, sync::{Arc, Weak}};
+use tokio::sync::RwLock;
+use crate::framework::ChainLink;
+
+// why a ChainLink failed to map an input
+#[derive(Clone)]
+pub enum Failure {
+    // returned by a fallible map block, kept so that it can be told apart by its type with `error`
+    Error {
+        message: String,
+        error: Arc<dyn Any + Send + Sync>
+    },
+    Panic {
+        message: String
+    },
+    // an attempt took longer than it was allowed to
+    Timeout
+}
+
+impl Failure {
+    pub fn from_error<E: Display + Send + Sync + 'static>(error: E) -> Self {
+        Failure::Error {
+            message: error.to_string(),
+            error: Arc::new(error)
+        }
+    }
+    pub fn from_panic(panic: &(dyn Any + Send)) -> Self {
+        Failure::Panic {
+            message: panic_message(panic)
+        }
+    }
+    // the error returned by the map block, when it is an `E`
+    pub fn error<E: Send + Sync + 'static>(&self) -> Option<&E> {
+        match self {
+            Failure::Error { error, .. } => error.downcast_ref::<E>(),
+            _ => None
+        }
+    }
+    pub fn is_panic(&self) -> bool {
+        matches!(self, Failure::Panic { .. })
+    }
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, Failure::Timeout)
+    }
+}
+
+impl Display for Failure {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Failure::Error { message, .. } | Failure::Panic { message } => formatter.write_str(message),
+            Failure::Timeout => formatter.write_str("The attempt timed out.")
+        }
+    }
+}
+
+impl Debug for Failure {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Failure::Error { message, .. } => formatter.debug_struct("Error").field("message", message).finish(),
+            Failure::Panic { message } => formatter.debug_struct("Panic").field("message", message).finish(),
+            Failure::Timeout => formatter.write_str("Timeout")
+        }
+    }
+}
+
+// an input that a ChainLink failed to map, kept so that it can be redriven once the failure is fixed
+pub struct DeadLetter {
+    // the `Arc<RwLock<T>>` holding the input of the ChainLink at `path`
+    input: Arc<dyn Any + Send + Sync>,
+    // the names of the chains and ChainLink the input failed within, separated by `/`
+    pub path: String,
+    pub failure: Failure,
+    // how many times the input has failed, counting the attempts made before each redrive
+    pub attempt_count: usize
+}
+
+impl DeadLetter {
+    pub fn new<T: Send + Sync + 'static>(input: Arc<RwLock<T>>, path: String, failure: Failure, attempt_count: usize) -> Self {
+        DeadLetter {
+            input,
+            path,
+            failure,
+            attempt_count
+        }
+    }
+    // reads the input as the type taken by the ChainLink at `path`, returning `None` for any other type
+    pub fn input<T: Send + Sync + 'static>(&self) -> Option<Arc<RwLock<T>>> {
+        self.input.clone().downcast::<RwLock<T>>().ok()
+    }
+}
+
+struct DeadLetterState {
+    dead_letters: Vec<DeadLetter>,
+    // the attempts made for each redriven input, which is recognized by still being the same allocation when it fails again
+    redriven: Vec<(Weak<dyn Any + Send + Sync>, usize)>,
+    // every input sent and not since redriven, even once its dead letter has been taken
+    dead_lettered: Vec<Weak<dyn Any + Send + Sync>>
+}
+
+// collects the dead letters of every ChainLink it is set on, where clones share the same dead letters
+// calling `set_dead_letter_queue` on a chain or duplicate hands it to every inner ChainLink
+pub struct DeadLetterQueue {
+    path: String,
+    state: Arc<std::sync::Mutex<DeadLetterState>>
+}
+
+impl Clone for DeadLetterQueue {
+    fn clone(&self) -> Self {
+        DeadLetterQueue {
+            path: self.path.clone(),
+            state: self.state.clone()
+        }
+    }
+}
+
+impl Default for DeadLetterQueue {
+    fn default() -> Self {
+        DeadLetterQueue {
+            path: String::new(),
+            state: Arc::new(std::sync::Mutex::new(DeadLetterState {
+                dead_letters: vec![],
+                redriven: vec![],
+                dead_lettered: vec![]
+            }))
+        }
+    }
+}
+
+impl DeadLetterQueue {
+    pub fn new() -> Self {
+        DeadLetterQueue::default()
+    }
+    // the same dead letters, reached from within the chain or ChainLink named `name`
+    pub fn within(&self, name: &str) -> Self {
+        DeadLetterQueue {
+            path: self.path_to(name),
+            state: self.state.clone()
+        }
+    }
+    pub fn path_to(&self, name: &str) -> String {
+        if self.path.is_empty() {
+            name.to_string()
+        }
+        else {
+            format!("{}/{}", self.path, name)
+        }
+    }
+    // records the failure of `attempt_count` attempts at the input by the ChainLink named `name`
+    pub fn send<T: Send + Sync + 'static>(&self, input: Arc<RwLock<T>>, name: &str, failure: Failure, attempt_count: usize) {
+        let input: Arc<dyn Any + Send + Sync> = input;
+        let mut locked_state = self.state.lock().unwrap();
+        locked_state.redriven.retain(|(redriven, _)| redriven.strong_count() != 0);
+        let previous_attempt_count = locked_state.redriven
+            .iter()
+            .find(|(redriven, _)| std::ptr::addr_eq(redriven.as_ptr(), Arc::as_ptr(&input)))
+            .map_or(0, |(_, attempt_count)| *attempt_count);
+        locked_state.dead_lettered.retain(|dead_lettered| dead_lettered.strong_count() != 0);
+        locked_state.dead_lettered.push(Arc::downgrade(&input));
+        locked_state.dead_letters.push(DeadLetter {
+            input,
+            path: self.path_to(name),
+            failure,
+            attempt_count: previous_attempt_count + attempt_count
+        });
+    }
+    // whether the input was sent here, which lets a wrapper know that the ChainLink it wraps is done with it
+    pub(crate) fn is_dead_lettered<T: Send + Sync + 'static>(&self, input: &Arc<RwLock<T>>) -> bool {
+        self.state.lock().unwrap().dead_lettered.iter().any(|dead_lettered| std::ptr::addr_eq(dead_lettered.as_ptr(), Arc::as_ptr(input)))
+    }
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().dead_letters.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn take_all(&self) -> Vec<DeadLetter> {
+        std::mem::take(&mut self.state.lock().unwrap().dead_letters)
+    }
+    // puts back a dead letter, such as one taken to be inspected
+    pub fn push(&self, dead_letter: DeadLetter) {
+        self.state.lock().unwrap().dead_letters.push(dead_letter);
+    }
+    // pushes every dead letter back into the ChainLink at its path within `chain_link`, returning how many were accepted
+    // a dead letter without a matching ChainLink stays in the queue
+    pub async fn redrive<TChainLink: ChainLink + Sync>(&self, chain_link: &TChainLink) -> usize {
+        let mut redriven_count: usize = 0;
+        for dead_letter in self.take_all() {
+            let input = Arc::downgrade(&dead_letter.input);
+            let attempt_count = dead_letter.attempt_count;
+            match chain_link.redrive(dead_letter).await {
+                Ok(()) => {
+                    let mut locked_state = self.state.lock().unwrap();
+                    locked_state.dead_lettered.retain(|dead_lettered| !dead_lettered.ptr_eq(&input));
+                    locked_state.redriven.push((input, attempt_count));
+                    redriven_count += 1;
+                },
+                Err(dead_letter) => {
+                    self.push(dead_letter);
+                }
+            }
+        }
+        redriven_count
+    }
+}
+
+// describes the payload of a caught panic
+pub fn panic_message(panic: &(dyn Any + Send)) -> String {
+    match panic.downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => match panic.downcast_ref::<String>() {
+            Some(message) => message.clone(),
+            None => String::from("The map block panicked.")
+        }
+    }
+}
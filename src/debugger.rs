@@ -0,0 +1,130 @@
+use std::{any::Any, sync::{Arc, Mutex}};
+use tokio::sync::{RwLock, watch};
+
+// the value moving from one ChainLink of a chain sequence into the next
+pub struct StageEvent {
+    pub chain_name: &'static str,
+    pub stage_index: usize,
+    pub stage_name: &'static str,
+    value: Arc<dyn Any + Send + Sync>
+}
+
+impl StageEvent {
+    pub fn new<T: Send + Sync + 'static>(chain_name: &'static str, stage_index: usize, stage_name: &'static str, value: Arc<RwLock<T>>) -> Self {
+        StageEvent {
+            chain_name,
+            stage_index,
+            stage_name,
+            value
+        }
+    }
+    // returns the value if it is of type `T`
+    pub fn value<T: Send + Sync + 'static>(&self) -> Option<Arc<RwLock<T>>> {
+        self.value.clone().downcast::<RwLock<T>>().ok()
+    }
+}
+
+type Breakpoint = Box<dyn Fn(&StageEvent) -> bool + Send + Sync>;
+
+struct DebuggerState {
+    is_stepping: bool,
+    breakpoints: Vec<Breakpoint>,
+    paused_stage: Option<Arc<StageEvent>>
+}
+
+// pauses a chain after each stage of its sequences so that the values between ChainLinks can be inspected
+pub struct Debugger {
+    state: Mutex<DebuggerState>,
+    // incremented each time the debugger pauses or resumes
+    generation: watch::Sender<u64>
+}
+
+impl Default for Debugger {
+    fn default() -> Self {
+        Debugger::new()
+    }
+}
+
+impl Debugger {
+    // the debugger starts by pausing on every stage
+    pub fn new() -> Self {
+        Debugger {
+            state: Mutex::new(DebuggerState {
+                is_stepping: true,
+                breakpoints: vec![],
+                paused_stage: None
+            }),
+            generation: watch::channel(0).0
+        }
+    }
+    // pause on any stage whose event satisfies the predicate, even while continuing
+    pub fn break_when(&self, predicate: impl Fn(&StageEvent) -> bool + Send + Sync + 'static) {
+        self.state.lock().unwrap().breakpoints.push(Box::new(predicate));
+    }
+    pub fn clear_breakpoints(&self) {
+        self.state.lock().unwrap().breakpoints.clear();
+    }
+    // release the paused stage and pause again at the next stage
+    pub fn step(&self) {
+        self.release(true);
+    }
+    // release the paused stage and only pause again at a breakpoint
+    pub fn resume(&self) {
+        self.release(false);
+    }
+    pub fn paused_stage(&self) -> Option<Arc<StageEvent>> {
+        self.state.lock().unwrap().paused_stage.clone()
+    }
+    // waits until a chain has paused at a stage
+    pub async fn wait_for_pause(&self) -> Arc<StageEvent> {
+        let mut receiver = self.generation.subscribe();
+        loop {
+            if let Some(paused_stage) = self.paused_stage() {
+                return paused_stage;
+            }
+            receiver.changed().await.expect("The debugger should outlive its receivers.");
+        }
+    }
+    // called by a chain after each stage, waiting while the debugger is paused
+    pub async fn on_stage(&self, stage_event: StageEvent) {
+        let mut receiver = self.generation.subscribe();
+        let stage_event = Arc::new(stage_event);
+        loop {
+            {
+                let mut locked_state = self.state.lock().unwrap();
+                let is_breakpoint = locked_state.breakpoints
+                    .iter()
+                    .any(|breakpoint| breakpoint(&stage_event));
+                if !locked_state.is_stepping && !is_breakpoint {
+                    return;
+                }
+
+                // another sequence may already be paused, so wait for it to be released first
+                if locked_state.paused_stage.is_none() {
+                    locked_state.paused_stage = Some(stage_event.clone());
+                    break;
+                }
+            }
+            receiver.changed().await.expect("The debugger should outlive its receivers.");
+        }
+        self.generation.send_modify(|generation| *generation += 1);
+        loop {
+            receiver.changed().await.expect("The debugger should outlive its receivers.");
+            let is_released = match self.state.lock().unwrap().paused_stage.as_ref() {
+                Some(paused_stage) => !Arc::ptr_eq(paused_stage, &stage_event),
+                None => true
+            };
+            if is_released {
+                return;
+            }
+        }
+    }
+    fn release(&self, is_stepping: bool) {
+        {
+            let mut locked_state = self.state.lock().unwrap();
+            locked_state.is_stepping = is_stepping;
+            locked_state.paused_stage = None;
+        }
+        self.generation.send_modify(|generation| *generation += 1);
+    }
+}
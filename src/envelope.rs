@@ -0,0 +1,160 @@
+use std::{collections::BTreeMap, sync::Arc};
+use tokio::sync::RwLock;
+use crate::{framework::{ChainLink, Initializable, ProcessOutcome, WrapperSettings}, item::Item, queue::Queue};
+
+// the headers of an envelope by name
+pub type HeaderMap = BTreeMap<String, String>;
+
+// a payload along with metadata headers, such as a correlation id, ingestion timestamp, source file name or tenant id
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Envelope<T> {
+    pub headers: HeaderMap,
+    pub payload: T
+}
+
+impl<T> Envelope<T> {
+    pub fn new(payload: T) -> Self {
+        Envelope {
+            headers: HeaderMap::new(),
+            payload
+        }
+    }
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+}
+
+impl<T> From<T> for Envelope<T> {
+    fn from(payload: T) -> Self {
+        Envelope::new(payload)
+    }
+}
+
+// the headers of the input being mapped, which a map block reads and sets through `input.headers`
+// clones share the same headers, so that whatever a map block sets is inherited by the outputs of that input
+pub struct Headers {
+    values: Arc<std::sync::Mutex<HeaderMap>>
+}
+
+impl Clone for Headers {
+    fn clone(&self) -> Self {
+        Headers {
+            values: self.values.clone()
+        }
+    }
+}
+
+impl Default for Headers {
+    fn default() -> Self {
+        Headers::new(HeaderMap::new())
+    }
+}
+
+impl Headers {
+    pub fn new(values: HeaderMap) -> Self {
+        Headers {
+            values: Arc::new(std::sync::Mutex::new(values))
+        }
+    }
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.values.lock().unwrap().get(name).cloned()
+    }
+    pub fn insert(&self, name: impl Into<String>, value: impl Into<String>) {
+        self.values.lock().unwrap().insert(name.into(), value.into());
+    }
+    pub fn remove(&self, name: &str) -> Option<String> {
+        self.values.lock().unwrap().remove(name)
+    }
+    pub fn to_map(&self) -> HeaderMap {
+        self.values.lock().unwrap().clone()
+    }
+}
+
+pub struct EnvelopedInitializer<TChainLinkInitializer> {
+    pub chain_link: Arc<RwLock<TChainLinkInitializer>>
+}
+
+impl<TChainLinkInitializer> EnvelopedInitializer<TChainLinkInitializer> {
+    pub fn new(chain_link: TChainLinkInitializer) -> Self {
+        EnvelopedInitializer {
+            chain_link: Arc::new(RwLock::new(chain_link))
+        }
+    }
+}
+
+// passes the payload of each envelope to the wrapped ChainLink along with its headers, and wraps each output in an envelope with the headers it inherited
+// the headers travel with each item through a chain or duplicate, so each map block along the way reads and sets those of the input it is mapping
+// a ChainLink that combines or holds back its inputs, such as a Batcher, Window or Conflate, drops their headers
+// within a chain!, name the type such as `Enveloped<EtlProcess>`
+pub struct Enveloped<TChainLink: ChainLink> {
+    chain_link: TChainLink,
+    input_queue: Queue<Item<Envelope<TChainLink::TInput>>>,
+    output_queue: Queue<Item<Envelope<TChainLink::TOutput>>>,
+    settings: WrapperSettings
+}
+
+impl<TChainLink: ChainLink> Enveloped<TChainLink> {
+    pub fn new(chain_link: TChainLink) -> Self {
+        Enveloped {
+            chain_link,
+            input_queue: Queue::default(),
+            output_queue: Queue::default(),
+            settings: WrapperSettings::default()
+        }
+    }
+    pub fn chain_link(&self) -> &TChainLink {
+        &self.chain_link
+    }
+}
+
+#[async_trait::async_trait]
+impl<TChainLink> Initializable for Enveloped<TChainLink>
+where
+    TChainLink: ChainLink + Initializable + Send,
+    TChainLink::TInitializer: Send + Sync
+{
+    type TInitializer = EnvelopedInitializer<TChainLink::TInitializer>;
+
+    async fn initialize(initializer: Arc<RwLock<EnvelopedInitializer<TChainLink::TInitializer>>>) -> Self {
+        Enveloped::new(TChainLink::initialize(initializer.read().await.chain_link.clone()).await)
+    }
+}
+
+impl_chain_link! {
+    impl[TChainLink] Enveloped<TChainLink>
+    where [
+        TChainLink: ChainLink + Send + Sync,
+        TChainLink::TInput: Clone + Send + Sync,
+        TChainLink::TOutput: Clone + Send + Sync
+    ]
+    (Envelope<TChainLink::TInput> => Envelope<TChainLink::TOutput>)
+    queued
+    wrapping chain_link [set_scheduler, set_clock, set_dead_letter_queue, redrive, set_watchdog]
+    {
+        async fn process(&self) -> ProcessOutcome {
+            let received_items = self.input_queue.try_pop_up_to(usize::MAX).await;
+            let consumed_count = received_items.len();
+            for received in received_items {
+                let envelope = received.into_owned().await;
+                self.chain_link.push_item_with_headers(Item::Owned(envelope.payload), envelope.headers).await;
+            }
+            let mut outcome = self.chain_link.process().await;
+            outcome.consumed_count = consumed_count;
+            outcome.produced_count = 0;
+
+            // outputs of a wrapped ChainLink that processes in the background may arrive later, still carrying their headers
+            while let Some((output, headers)) = self.chain_link.try_pop_item_with_headers().await {
+                self.output_queue.push(Item::Owned(Envelope {
+                    headers,
+                    payload: output.into_owned().await
+                })).await;
+                outcome.produced_count += 1;
+            }
+            outcome
+        }
+    }
+}
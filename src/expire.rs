@@ -0,0 +1,219 @@
+use std::{marker::PhantomData, sync::Arc, time::{Duration, Instant}};
+use tokio::sync::{Mutex, RwLock};
+use crate::{clock::Clock, envelope::HeaderMap, framework::{ChainLink, Initializable, ProcessOutcome, WrapperSettings}, item::Item, queue::Queue, side::SideOutput};
+
+// what happens to an input whose deadline passes before it is processed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expiry {
+    Drop,
+    // the input goes to `try_pop_expired`
+    Route
+}
+
+pub struct ExpireInitializer<TChainLinkInitializer> {
+    // how long an input may wait from when it is pushed, unless it is pushed with its own deadline
+    pub ttl: Duration,
+    pub expiry: Expiry,
+    pub chain_link: Arc<RwLock<TChainLinkInitializer>>
+}
+
+impl<TChainLinkInitializer> ExpireInitializer<TChainLinkInitializer> {
+    pub fn new(ttl: Duration, expiry: Expiry, chain_link: TChainLinkInitializer) -> Self {
+        ExpireInitializer {
+            ttl,
+            expiry,
+            chain_link: Arc::new(RwLock::new(chain_link))
+        }
+    }
+}
+
+struct Expiring<T> {
+    item: Item<T>,
+    deadline: Instant
+}
+
+// the inputs of an Expire, each given its deadline as it is pushed
+struct ExpiringQueue<T> {
+    ttl: Duration,
+    clock: std::sync::Mutex<Clock>,
+    queue: Queue<Expiring<T>>
+}
+
+impl<T> ExpiringQueue<T> {
+    fn new(ttl: Duration) -> Self {
+        ExpiringQueue {
+            ttl,
+            clock: std::sync::Mutex::new(Clock::default()),
+            queue: Queue::default()
+        }
+    }
+    fn set_clock(&self, clock: Clock) {
+        *self.clock.lock().unwrap() = clock;
+    }
+    fn expiring(&self, item: Item<T>) -> Expiring<T> {
+        Expiring {
+            item,
+            deadline: self.clock.lock().unwrap().now() + self.ttl
+        }
+    }
+    async fn push(&self, item: Item<T>) {
+        self.queue.push(self.expiring(item)).await;
+    }
+    async fn push_if_empty(&self, item: Item<T>) {
+        self.queue.push_if_empty(self.expiring(item)).await;
+    }
+    async fn push_replace(&self, item: Item<T>) {
+        self.queue.push_replace(self.expiring(item)).await;
+    }
+    async fn push_until(&self, item: Item<T>, deadline: Instant) {
+        self.queue.push(Expiring {
+            item,
+            deadline
+        }).await;
+    }
+    async fn push_with_headers(&self, item: Item<T>, headers: HeaderMap) {
+        self.queue.push_with_headers(self.expiring(item), headers).await;
+    }
+    async fn try_pop_with_headers(&self) -> Option<(Expiring<T>, HeaderMap)> {
+        self.queue.try_pop_with_headers().await
+    }
+}
+
+// gives each input of the wrapped ChainLink a deadline, measured using the clock of the ChainLink, after which it is no longer processed
+// inputs are passed to the wrapped ChainLink one at a time, the next being held back until the wrapped ChainLink produces an output, is idle or no longer holds the input
+// so an input only waits past its deadline while the wrapped ChainLink is working on it, rather than within its queue
+// within a chain!, name the type such as `Expire<RobotInterface>`
+pub struct Expire<TChainLink: ChainLink> {
+    expiry: Expiry,
+    chain_link: TChainLink,
+    input_queue: ExpiringQueue<TChainLink::TInput>,
+    output_queue: Queue<Item<TChainLink::TOutput>>,
+    expired_queue: Queue<Item<TChainLink::TInput>>,
+    // the input last passed to the wrapped ChainLink, while it is still working on it
+    in_flight: Mutex<Option<Arc<RwLock<TChainLink::TInput>>>>,
+    settings: WrapperSettings
+}
+
+impl<TChainLink: ChainLink> Expire<TChainLink> {
+    pub fn new(ttl: Duration, expiry: Expiry, chain_link: TChainLink) -> Self {
+        Expire {
+            expiry,
+            chain_link,
+            input_queue: ExpiringQueue::new(ttl),
+            output_queue: Queue::default(),
+            expired_queue: Queue::default(),
+            in_flight: Mutex::new(None),
+            settings: WrapperSettings::default()
+        }
+    }
+    pub fn chain_link(&self) -> &TChainLink {
+        &self.chain_link
+    }
+    // pushes an input that is no longer processed once `deadline` has passed
+    pub async fn push_until(&self, input: Arc<RwLock<TChainLink::TInput>>, deadline: Instant) {
+        self.input_queue.push_until(Item::Shared(input), deadline).await;
+    }
+    pub async fn push_raw_until(&self, input: TChainLink::TInput, deadline: Instant) {
+        self.input_queue.push_until(Item::Owned(input), deadline).await;
+    }
+    // an input whose deadline passed before it was processed, when expired inputs are routed
+    pub async fn try_pop_expired(&self) -> Option<Arc<RwLock<TChainLink::TInput>>> {
+        self.expired_queue.try_pop().await.map(Item::into_shared)
+    }
+}
+
+#[async_trait::async_trait]
+impl<TChainLink> Initializable for Expire<TChainLink>
+where
+    TChainLink: ChainLink + Initializable + Send,
+    TChainLink::TInitializer: Send + Sync
+{
+    type TInitializer = ExpireInitializer<TChainLink::TInitializer>;
+
+    async fn initialize(initializer: Arc<RwLock<ExpireInitializer<TChainLink::TInitializer>>>) -> Self {
+        let locked_initializer = initializer.read().await;
+        Expire::new(locked_initializer.ttl, locked_initializer.expiry, TChainLink::initialize(locked_initializer.chain_link.clone()).await)
+    }
+}
+
+impl_chain_link! {
+    impl[TChainLink] Expire<TChainLink>
+    where [
+        TChainLink: ChainLink + Send + Sync,
+        TChainLink::TInput: Send + Sync + 'static,
+        TChainLink::TOutput: Send + Sync
+    ]
+    (TChainLink::TInput => TChainLink::TOutput)
+    queued
+    headed
+    wrapping chain_link [set_scheduler, set_dead_letter_queue, redrive, set_watchdog]
+    {
+        fn set_clock(&self, clock: Clock) {
+            self.chain_link.set_clock(clock.clone());
+            self.input_queue.set_clock(clock.clone());
+            self.settings.set_clock(clock);
+        }
+        async fn process(&self) -> ProcessOutcome {
+            let clock = self.settings.clock();
+            let mut locked_in_flight = self.in_flight.lock().await;
+            let mut outcome = ProcessOutcome::idle();
+            if locked_in_flight.is_none() {
+                while let Some((expiring, headers)) = self.input_queue.try_pop_with_headers().await {
+                    outcome.consumed_count += 1;
+                    if clock.now() > expiring.deadline {
+                        if self.expiry == Expiry::Route {
+                            self.expired_queue.push(expiring.item).await;
+                        }
+                        continue;
+                    }
+
+                    let received = expiring.item.into_shared();
+                    self.chain_link.push_item_with_headers(Item::Shared(received.clone()), headers).await;
+                    *locked_in_flight = Some(received);
+                    break;
+                }
+            }
+
+            let chain_link_outcome = match locked_in_flight.is_some() {
+                true => Some(self.chain_link.process().await),
+                false => None
+            };
+            // outputs of a wrapped ChainLink that processes in the background may arrive later
+            while let Some((output, headers)) = self.chain_link.try_pop_item_with_headers().await {
+                self.output_queue.push_with_headers(output, headers).await;
+                outcome.produced_count += 1;
+            }
+            if let (Some(chain_link_outcome), Some(in_flight)) = (chain_link_outcome, locked_in_flight.as_ref()) {
+                outcome.merge_state(&chain_link_outcome);
+                // a wrapped ChainLink that processes in the background is done with the input once it no longer holds it, such as after filtering it out
+                let is_released = Arc::strong_count(in_flight) == 1 || self.settings.dead_letter_queue().is_some_and(|dead_letter_queue| dead_letter_queue.is_dead_lettered(in_flight));
+                let is_settled = outcome.is_produced() || chain_link_outcome.is_failed() || !(chain_link_outcome.is_deferred || chain_link_outcome.is_busy) || is_released;
+                if is_settled {
+                    locked_in_flight.take();
+                }
+            }
+            outcome
+        }
+    }
+}
+
+// the expired inputs of an Expire as a side output, so that a SideChain can route them to another ChainLink
+// within a chain!, name the type such as `SideChain<Expired<RobotInterface>, LogExpired>`
+pub struct Expired<TChainLink> {
+    chain_link: PhantomData<TChainLink>
+}
+
+#[async_trait::async_trait]
+impl<TChainLink> SideOutput for Expired<TChainLink>
+where
+    TChainLink: ChainLink + Send + Sync,
+    TChainLink::TInput: Send + Sync + 'static,
+    TChainLink::TOutput: Send + Sync
+{
+    type TChainLink = Expire<TChainLink>;
+    type TValue = TChainLink::TInput;
+
+    async fn try_pop_item(chain_link: &Expire<TChainLink>) -> Option<Item<TChainLink::TInput>> {
+        chain_link.expired_queue.try_pop().await
+    }
+}
@@ -1,3 +1,94 @@
+// the result of a single `process` invocation, allowing drivers to make scheduling decisions
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ProcessOutcome {
+    // how many inputs were taken from the input queue
+    pub consumed_count: usize,
+    // how many outputs were placed into the output queue
+    pub produced_count: usize,
+    // the indexes of the parallel ChainLinks that consumed or produced
+    pub branch_indexes: Vec<usize>,
+    // work was handed to a background thread and its outcome is not yet known
+    pub is_deferred: bool,
+    // work from a previous invocation was still running, or items are being held for a later invocation
+    pub is_busy: bool,
+    // how many inputs taken failed to be mapped, each of which went to the dead-letter queue when one was set and was dropped otherwise
+    pub failed_count: usize,
+    // while items are being held, how long until the first of them is due, measured using the clock of the ChainLink, so that drivers can wait rather than poll
+    pub due_in: Option<std::time::Duration>
+}
+
+impl ProcessOutcome {
+    pub fn idle() -> Self {
+        ProcessOutcome::default()
+    }
+    pub fn consumed(consumed_count: usize, produced_count: usize) -> Self {
+        ProcessOutcome {
+            consumed_count,
+            produced_count,
+            ..ProcessOutcome::default()
+        }
+    }
+    pub fn deferred() -> Self {
+        ProcessOutcome {
+            is_deferred: true,
+            ..ProcessOutcome::default()
+        }
+    }
+    pub fn busy() -> Self {
+        ProcessOutcome {
+            is_busy: true,
+            ..ProcessOutcome::default()
+        }
+    }
+    pub fn is_produced(&self) -> bool {
+        self.produced_count != 0
+    }
+    pub fn is_consumed(&self) -> bool {
+        self.consumed_count != 0
+    }
+    pub fn is_failed(&self) -> bool {
+        self.failed_count != 0
+    }
+    pub fn is_idle(&self) -> bool {
+        !self.is_consumed() && !self.is_produced() && !self.is_deferred && !self.is_busy
+    }
+    // carries over the background state and failures of another outcome without counting its items
+    pub fn merge_state(&mut self, other: &ProcessOutcome) {
+        self.failed_count += other.failed_count;
+        self.is_deferred |= other.is_deferred;
+        self.is_busy |= other.is_busy;
+        self.due_in = match (self.due_in, other.due_in) {
+            (Some(due_in), Some(other_due_in)) => Some(due_in.min(other_due_in)),
+            (due_in, other_due_in) => due_in.or(other_due_in)
+        };
+    }
+    // combines the outcome of the parallel ChainLink at `branch_index` into this outcome
+    pub fn merge_branch(&mut self, branch_index: usize, other: ProcessOutcome) {
+        if other.is_consumed() || other.is_produced() {
+            self.branch_indexes.push(branch_index);
+        }
+        self.consumed_count += other.consumed_count;
+        self.produced_count += other.produced_count;
+        self.merge_state(&other);
+    }
+}
+
+// the name a wrapped ChainLink is known by within a path, such as `GetParentById`
+pub(crate) fn short_type_name<T: ?Sized>() -> &'static str {
+    let type_name = std::any::type_name::<T>();
+    let without_generics = &type_name[..type_name.find('<').unwrap_or(type_name.len())];
+    without_generics.rsplit("::").next().unwrap_or(without_generics)
+}
+
+// how chain! and duplicate! construct their inner ChainLinks from a shared initializer
+// chain_link!, chain! and duplicate! implement it, while a ChainLink written by hand needs its own implementation to be named within them, usually calling its `new`
+#[async_trait::async_trait]
+pub trait Initializable {
+    type TInitializer;
+
+    async fn initialize(initializer: std::sync::Arc<tokio::sync::RwLock<Self::TInitializer>>) -> Self;
+}
+
 #[async_trait::async_trait]
 pub trait ChainLink {
     type TInput;
@@ -7,6 +98,111 @@ pub trait ChainLink {
     async fn push_raw(&self, input: Self::TInput);
     async fn push_if_empty(&self, input: std::sync::Arc<tokio::sync::RwLock<Self::TInput>>);
     async fn push_raw_if_empty(&self, input: Self::TInput);
+    // latest wins, so every input still queued is dropped in favor of this one
+    // the defaults are not latest-wins: they only fall back to `push` and `push_raw`, keeping every queued input
+    // so a ChainLink written by hand that queues its inputs should implement both, as every ChainLink of this crate does
+    async fn push_replace(&self, input: std::sync::Arc<tokio::sync::RwLock<Self::TInput>>)
+    where
+        Self::TInput: Send + Sync
+    {
+        self.push(input).await
+    }
+    async fn push_raw_replace(&self, input: Self::TInput)
+    where
+        Self::TInput: Send
+    {
+        self.push_raw(input).await
+    }
     async fn try_pop(&self) -> Option<std::sync::Arc<tokio::sync::RwLock<Self::TOutput>>>;
-    async fn process(&self) -> bool;
-}
\ No newline at end of file
+    async fn process(&self) -> ProcessOutcome;
+    // passes an item without wrapping it, so ChainLinks that keep items as they arrive avoid an allocation and lock per hop
+    async fn push_item(&self, input: crate::item::Item<Self::TInput>)
+    where
+        Self::TInput: Send + Sync
+    {
+        self.push(input.into_shared()).await
+    }
+    async fn try_pop_item(&self) -> Option<crate::item::Item<Self::TOutput>> {
+        self.try_pop().await.map(crate::item::Item::Shared)
+    }
+    // pushes an item along with the headers of the envelope it came from, which ChainLinks that combine or hold back their inputs drop
+    async fn push_item_with_headers(&self, input: crate::item::Item<Self::TInput>, _headers: crate::envelope::HeaderMap)
+    where
+        Self::TInput: Send + Sync
+    {
+        self.push_item(input).await
+    }
+    // pops an output along with the headers it inherited from the input that produced it
+    async fn try_pop_item_with_headers(&self) -> Option<(crate::item::Item<Self::TOutput>, crate::envelope::HeaderMap)> {
+        self.try_pop_item().await.map(|output| (output, crate::envelope::HeaderMap::new()))
+    }
+    // pushes a value into one of the input ports declared by the ChainLink, such as `push_to::<SensorProcessorCamera>(direction)`
+    async fn push_to<TPort>(&self, value: TPort::TValue)
+    where
+        Self: Sized,
+        TPort: crate::port::Port<TChainLink = Self>,
+        TPort::TValue: Send,
+        Self::TInput: Send
+    {
+        self.push_raw(TPort::wrap(value)).await
+    }
+    // only ChainLinks that process in the background or make random choices need a scheduler
+    fn set_scheduler(&self, _scheduler: std::sync::Arc<dyn crate::schedule::Scheduler>) { }
+    // only ChainLinks that read the time need a clock
+    fn set_clock(&self, _clock: crate::clock::Clock) { }
+    // only ChainLinks that map their inputs send dead letters
+    fn set_dead_letter_queue(&self, _dead_letter_queue: crate::dead_letter::DeadLetterQueue) { }
+    // pushes a dead letter back into the ChainLink at its path, returning it when there is no such ChainLink
+    async fn redrive(&self, dead_letter: crate::dead_letter::DeadLetter) -> Result<(), crate::dead_letter::DeadLetter> {
+        Err(dead_letter)
+    }
+    // only ChainLinks with a deadline are watched
+    fn set_watchdog(&self, _watchdog: crate::watchdog::Watchdog) { }
+}
+
+// the settings a wrapper hands to the ChainLink it wraps, kept so that the wrapper can use them as well
+pub(crate) struct WrapperSettings {
+    scheduler: std::sync::Mutex<std::sync::Arc<dyn crate::schedule::Scheduler>>,
+    clock: std::sync::Mutex<crate::clock::Clock>,
+    dead_letter_queue: std::sync::Mutex<Option<crate::dead_letter::DeadLetterQueue>>,
+    watchdog: std::sync::Mutex<Option<crate::watchdog::Watchdog>>
+}
+
+impl Default for WrapperSettings {
+    fn default() -> Self {
+        WrapperSettings {
+            scheduler: std::sync::Mutex::new(std::sync::Arc::new(crate::schedule::ThreadScheduler)),
+            clock: std::sync::Mutex::new(crate::clock::Clock::default()),
+            dead_letter_queue: std::sync::Mutex::new(None),
+            watchdog: std::sync::Mutex::new(None)
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl WrapperSettings {
+    pub(crate) fn scheduler(&self) -> std::sync::Arc<dyn crate::schedule::Scheduler> {
+        self.scheduler.lock().unwrap().clone()
+    }
+    pub(crate) fn set_scheduler(&self, scheduler: std::sync::Arc<dyn crate::schedule::Scheduler>) {
+        *self.scheduler.lock().unwrap() = scheduler;
+    }
+    pub(crate) fn clock(&self) -> crate::clock::Clock {
+        self.clock.lock().unwrap().clone()
+    }
+    pub(crate) fn set_clock(&self, clock: crate::clock::Clock) {
+        *self.clock.lock().unwrap() = clock;
+    }
+    pub(crate) fn dead_letter_queue(&self) -> Option<crate::dead_letter::DeadLetterQueue> {
+        self.dead_letter_queue.lock().unwrap().clone()
+    }
+    pub(crate) fn set_dead_letter_queue(&self, dead_letter_queue: crate::dead_letter::DeadLetterQueue) {
+        self.dead_letter_queue.lock().unwrap().replace(dead_letter_queue);
+    }
+    pub(crate) fn watchdog(&self) -> Option<crate::watchdog::Watchdog> {
+        self.watchdog.lock().unwrap().clone()
+    }
+    pub(crate) fn set_watchdog(&self, watchdog: crate::watchdog::Watchdog) {
+        self.watchdog.lock().unwrap().replace(watchdog);
+    }
+}
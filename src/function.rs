@@ -0,0 +1,125 @@
+use std::{future::Future, sync::Arc};
+use tokio::sync::RwLock;
+use crate::{framework::{Initializable, ProcessOutcome}, item::Item, queue::Queue};
+
+// a ChainLink that maps each input with a function, producing nothing when the function returns `None`
+// within a chain! or duplicate!, the function is the initializer, so name the type with a function pointer such as `FnLink<String, i32, fn(&String) -> Option<i32>>`
+pub struct FnLink<TInput, TOutput, TFunction> {
+    function: TFunction,
+    input_queue: Queue<Item<TInput>>,
+    output_queue: Queue<Item<TOutput>>
+}
+
+impl<TInput, TOutput, TFunction> FnLink<TInput, TOutput, TFunction>
+where
+    TFunction: Fn(&TInput) -> Option<TOutput>
+{
+    pub fn new(function: TFunction) -> Self {
+        FnLink {
+            function,
+            input_queue: Queue::default(),
+            output_queue: Queue::default()
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<TInput, TOutput, TFunction> Initializable for FnLink<TInput, TOutput, TFunction>
+where
+    TFunction: Fn(&TInput) -> Option<TOutput> + Clone + Send + Sync
+{
+    type TInitializer = TFunction;
+
+    async fn initialize(initializer: Arc<RwLock<TFunction>>) -> Self {
+        FnLink::new(initializer.read().await.clone())
+    }
+}
+
+impl_chain_link! {
+    impl[TInput, TOutput, TFunction] FnLink<TInput, TOutput, TFunction>
+    where [
+        TInput: Send + Sync,
+        TOutput: Send + Sync,
+        TFunction: Fn(&TInput) -> Option<TOutput> + Send + Sync
+    ]
+    (TInput => TOutput)
+    queued
+    headed
+    {
+        async fn process(&self) -> ProcessOutcome {
+            let Some((input, headers)) = self.input_queue.try_pop_with_headers().await else {
+                return ProcessOutcome::idle();
+            };
+            let output = (self.function)(&*input.read().await);
+            match output {
+                Some(output) => {
+                    self.output_queue.push_with_headers(Item::Owned(output), headers).await;
+                    ProcessOutcome::consumed(1, 1)
+                },
+                None => ProcessOutcome::consumed(1, 0)
+            }
+        }
+    }
+}
+
+// a ChainLink that maps each input with an async function, producing nothing when the function returns `None`
+// within a chain! or duplicate!, name the type with a function pointer returning a boxed future such as `AsyncFnLink<String, i32, fn(Item<String>) -> BoxFuture<'static, Option<i32>>>`
+pub struct AsyncFnLink<TInput, TOutput, TFunction> {
+    function: TFunction,
+    input_queue: Queue<Item<TInput>>,
+    output_queue: Queue<Item<TOutput>>
+}
+
+impl<TInput, TOutput, TFunction, TFuture> AsyncFnLink<TInput, TOutput, TFunction>
+where
+    TFunction: Fn(Item<TInput>) -> TFuture,
+    TFuture: Future<Output = Option<TOutput>>
+{
+    pub fn new(function: TFunction) -> Self {
+        AsyncFnLink {
+            function,
+            input_queue: Queue::default(),
+            output_queue: Queue::default()
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<TInput, TOutput, TFunction, TFuture> Initializable for AsyncFnLink<TInput, TOutput, TFunction>
+where
+    TFunction: Fn(Item<TInput>) -> TFuture + Clone + Send + Sync,
+    TFuture: Future<Output = Option<TOutput>>
+{
+    type TInitializer = TFunction;
+
+    async fn initialize(initializer: Arc<RwLock<TFunction>>) -> Self {
+        AsyncFnLink::new(initializer.read().await.clone())
+    }
+}
+
+impl_chain_link! {
+    impl[TInput, TOutput, TFunction, TFuture] AsyncFnLink<TInput, TOutput, TFunction>
+    where [
+        TInput: Send + Sync,
+        TOutput: Send + Sync,
+        TFunction: Fn(Item<TInput>) -> TFuture + Send + Sync,
+        TFuture: Future<Output = Option<TOutput>> + Send
+    ]
+    (TInput => TOutput)
+    queued
+    headed
+    {
+        async fn process(&self) -> ProcessOutcome {
+            let Some((input, headers)) = self.input_queue.try_pop_with_headers().await else {
+                return ProcessOutcome::idle();
+            };
+            match (self.function)(input).await {
+                Some(output) => {
+                    self.output_queue.push_with_headers(Item::Owned(output), headers).await;
+                    ProcessOutcome::consumed(1, 1)
+                },
+                None => ProcessOutcome::consumed(1, 0)
+            }
+        }
+    }
+}
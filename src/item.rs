@@ -0,0 +1,77 @@
+use std::{ops::Deref, sync::Arc};
+use tokio::sync::{RwLock, RwLockReadGuard};
+
+// a value moving between ChainLinks
+// a value with a single consumer is passed by value, while a value fanned out to parallel ChainLinks is shared behind a lock
+pub enum Item<T> {
+    Owned(T),
+    Shared(Arc<RwLock<T>>)
+}
+
+impl<T> Item<T> {
+    pub fn is_shared(&self) -> bool {
+        matches!(self, Item::Shared(_))
+    }
+    // wraps an owned value so that it can be handed to more than one consumer
+    pub fn into_shared(self) -> Arc<RwLock<T>> {
+        match self {
+            Item::Owned(value) => Arc::new(RwLock::new(value)),
+            Item::Shared(value) => value
+        }
+    }
+    // takes the value without cloning when no other consumer is holding it
+    pub fn try_into_owned(self) -> Result<T, Self> {
+        match self {
+            Item::Owned(value) => Ok(value),
+            Item::Shared(value) => match Arc::try_unwrap(value) {
+                Ok(lock) => Ok(lock.into_inner()),
+                Err(value) => Err(Item::Shared(value))
+            }
+        }
+    }
+    pub async fn read(&self) -> ItemReadGuard<'_, T> {
+        match self {
+            Item::Owned(value) => ItemReadGuard::Owned(value),
+            Item::Shared(value) => ItemReadGuard::Shared(value.read().await)
+        }
+    }
+}
+
+impl<T: Clone> Item<T> {
+    // takes the value, cloning it only when another consumer is still holding it
+    pub async fn into_owned(self) -> T {
+        match self.try_into_owned() {
+            Ok(value) => value,
+            Err(item) => item.read().await.clone()
+        }
+    }
+}
+
+impl<T> From<T> for Item<T> {
+    fn from(value: T) -> Self {
+        Item::Owned(value)
+    }
+}
+
+impl<T> From<Arc<RwLock<T>>> for Item<T> {
+    fn from(value: Arc<RwLock<T>>) -> Self {
+        Item::Shared(value)
+    }
+}
+
+// reading an owned value needs no lock
+pub enum ItemReadGuard<'a, T> {
+    Owned(&'a T),
+    Shared(RwLockReadGuard<'a, T>)
+}
+
+impl<T> Deref for ItemReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match self {
+            ItemReadGuard::Owned(value) => value,
+            ItemReadGuard::Shared(guard) => guard
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use std::{collections::{HashMap, VecDeque}, hash::Hash, sync::Arc, time::{Duration, Instant}};
+use tokio::sync::{Mutex, RwLock};
+use crate::{clock::Clock, framework::{Initializable, ProcessOutcome}, item::Item, queue::{ArrivalQueue, Queue}};
+
+// the two kinds of input a KeyedJoin pairs with each other
+#[derive(Debug, Clone, PartialEq)]
+pub enum JoinInput<TLeft, TRight> {
+    Left(TLeft),
+    Right(TRight)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinKind {
+    // only matched pairs are produced
+    Inner,
+    // a left input that expires without a match is produced alone
+    Left
+}
+
+// which waiting input is matched first when more than one has the same key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fairness {
+    Oldest,
+    Newest
+}
+
+pub struct KeyedJoinInitializer<TLeft, TRight, TKey> {
+    pub left_key: fn(&TLeft) -> TKey,
+    pub right_key: fn(&TRight) -> TKey,
+    pub kind: JoinKind,
+    pub fairness: Fairness,
+    // unmatched inputs are removed once they have waited this long since they were pushed, measured using the clock of the ChainLink
+    // without one, unmatched inputs wait for a match forever, so a left join requires one
+    pub ttl: Option<Duration>
+}
+
+impl<TLeft, TRight, TKey> Clone for KeyedJoinInitializer<TLeft, TRight, TKey> {
+    fn clone(&self) -> Self {
+        KeyedJoinInitializer {
+            left_key: self.left_key,
+            right_key: self.right_key,
+            kind: self.kind,
+            fairness: self.fairness,
+            ttl: self.ttl
+        }
+    }
+}
+
+type Waiting<TKey, T> = HashMap<TKey, VecDeque<(Instant, T)>>;
+
+struct JoinState<TLeft, TRight, TKey> {
+    lefts: Waiting<TKey, TLeft>,
+    rights: Waiting<TKey, TRight>
+}
+
+fn take_waiting<TKey: Eq + Hash, T>(waiting: &mut Waiting<TKey, T>, key: &TKey, fairness: Fairness) -> Option<T> {
+    let entries = waiting.get_mut(key)?;
+    let entry = match fairness {
+        Fairness::Oldest => entries.pop_front(),
+        Fairness::Newest => entries.pop_back()
+    };
+    if entries.is_empty() {
+        waiting.remove(key);
+    }
+    entry.map(|(_, value)| value)
+}
+
+fn remove_expired<TKey, T>(waiting: &mut Waiting<TKey, T>, now: Instant, ttl: Duration) -> Vec<(Instant, T)> {
+    let mut expired = vec![];
+    waiting.retain(|_, entries| {
+        while entries.front().is_some_and(|(pushed, _)| now.saturating_duration_since(*pushed) >= ttl) {
+            expired.extend(entries.pop_front());
+        }
+        !entries.is_empty()
+    });
+    expired
+}
+
+// pairs left and right inputs whose keys match, each input being matched at most once
+// within a chain!, name the type such as `KeyedJoin<Order, WorkerAvailability, WorkType>`
+pub struct KeyedJoin<TLeft, TRight, TKey> {
+    initializer: KeyedJoinInitializer<TLeft, TRight, TKey>,
+    input_queue: ArrivalQueue<JoinInput<TLeft, TRight>>,
+    state: Mutex<JoinState<TLeft, TRight, TKey>>,
+    output_queue: Queue<Item<(TLeft, Option<TRight>)>>
+}
+
+impl<TLeft, TRight, TKey> KeyedJoin<TLeft, TRight, TKey> {
+    pub fn new(initializer: KeyedJoinInitializer<TLeft, TRight, TKey>) -> Self {
+        assert!(initializer.kind != JoinKind::Left || initializer.ttl.is_some(), "A left join should have a ttl after which unmatched left inputs are produced.");
+        KeyedJoin {
+            initializer,
+            input_queue: ArrivalQueue::new(),
+            state: Mutex::new(JoinState {
+                lefts: HashMap::new(),
+                rights: HashMap::new()
+            }),
+            output_queue: Queue::default()
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<TLeft, TRight, TKey> Initializable for KeyedJoin<TLeft, TRight, TKey> {
+    type TInitializer = KeyedJoinInitializer<TLeft, TRight, TKey>;
+
+    async fn initialize(initializer: Arc<RwLock<KeyedJoinInitializer<TLeft, TRight, TKey>>>) -> Self {
+        KeyedJoin::new(initializer.read().await.clone())
+    }
+}
+
+impl_chain_link! {
+    impl[TLeft, TRight, TKey] KeyedJoin<TLeft, TRight, TKey>
+    where [
+        TLeft: Clone + Send + Sync,
+        TRight: Clone + Send + Sync,
+        TKey: Eq + Hash + Send + Sync
+    ]
+    (JoinInput<TLeft, TRight> => (TLeft, Option<TRight>))
+    queued
+    {
+        fn set_clock(&self, clock: Clock) {
+            self.input_queue.set_clock(clock);
+        }
+        async fn process(&self) -> ProcessOutcome {
+            let now = self.input_queue.clock().now();
+            let mut locked_state = self.state.lock().await;
+            let received_items = self.input_queue.try_pop_up_to(usize::MAX).await;
+            let consumed_count = received_items.len();
+            let mut produced_count: usize = 0;
+            for (pushed, received) in received_items {
+                let output = match received.into_owned().await {
+                    JoinInput::Left(left) => {
+                        let key = (self.initializer.left_key)(&left);
+                        match take_waiting(&mut locked_state.rights, &key, self.initializer.fairness) {
+                            Some(right) => Some((left, Some(right))),
+                            None => {
+                                locked_state.lefts.entry(key).or_default().push_back((pushed, left));
+                                None
+                            }
+                        }
+                    },
+                    JoinInput::Right(right) => {
+                        let key = (self.initializer.right_key)(&right);
+                        match take_waiting(&mut locked_state.lefts, &key, self.initializer.fairness) {
+                            Some(left) => Some((left, Some(right))),
+                            None => {
+                                locked_state.rights.entry(key).or_default().push_back((pushed, right));
+                                None
+                            }
+                        }
+                    }
+                };
+                if let Some(output) = output {
+                    self.output_queue.push(Item::Owned(output)).await;
+                    produced_count += 1;
+                }
+            }
+
+            if let Some(ttl) = self.initializer.ttl {
+                let mut expired_lefts = remove_expired(&mut locked_state.lefts, now, ttl);
+                remove_expired(&mut locked_state.rights, now, ttl);
+                if self.initializer.kind == JoinKind::Left {
+                    // produce the expired inputs in the order they were pushed regardless of their keys
+                    expired_lefts.sort_by_key(|(pushed, _)| *pushed);
+                    for (_, left) in expired_lefts {
+                        self.output_queue.push(Item::Owned((left, None))).await;
+                        produced_count += 1;
+                    }
+                }
+            }
+
+            let mut outcome = ProcessOutcome::consumed(consumed_count, produced_count);
+            // a waiting left input of a left join is produced once it expires
+            if let (JoinKind::Left, Some(ttl)) = (self.initializer.kind, self.initializer.ttl) {
+                let first_pushed = locked_state.lefts.values().filter_map(|entries| entries.front()).map(|(pushed, _)| *pushed).min();
+                outcome.is_busy = first_pushed.is_some();
+                outcome.due_in = first_pushed.map(|pushed| ttl.saturating_sub(now.saturating_duration_since(pushed)));
+            }
+            outcome
+        }
+    }
+}
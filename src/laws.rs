@@ -0,0 +1,200 @@
+use std::{fmt::Debug, future::Future, sync::Arc};
+use proptest::{arbitrary::{Arbitrary, any}, collection::vec, strategy::Strategy, test_runner::{Config, TestCaseError, TestRunner}};
+use tokio::sync::RwLock;
+use crate::{clock::Clock, dead_letter::{DeadLetter, DeadLetterQueue}, envelope::HeaderMap, framework::{ChainLink, ProcessOutcome}, item::Item, schedule::Scheduler, testing::feed_and_collect, watchdog::Watchdog};
+
+// the longest input sequence generated for a law
+pub const MAX_INPUT_SEQUENCE_LENGTH: usize = 16;
+
+// sequences of arbitrary inputs that shrink toward the shortest and simplest failing sequence
+pub fn input_sequences<TInput: Arbitrary>() -> impl Strategy<Value = Vec<TInput>> {
+    vec(any::<TInput>(), 0..MAX_INPUT_SEQUENCE_LENGTH)
+}
+
+// runs the law against generated input sequences, panicking with the minimal failing sequence
+// this must be called outside of an async runtime since each case runs on its own runtime
+fn check_law<TInput, TFuture>(law: impl Fn(Vec<TInput>) -> TFuture)
+where
+    TInput: Arbitrary + Debug,
+    TFuture: Future<Output = Result<(), TestCaseError>>
+{
+    let mut runner = TestRunner::new(Config {
+        // a ChainLink under test has no source file to persist failures next to
+        failure_persistence: None,
+        ..Config::default()
+    });
+    let result = runner.run(&input_sequences::<TInput>(), |inputs| {
+        let tokio_runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        tokio_runtime.block_on(law(inputs))
+    });
+    if let Err(error) = result {
+        panic!("{}", error);
+    }
+}
+
+pub fn check_never_panics<TChainLink, TFuture>(new_chain_link: impl Fn() -> TFuture)
+where
+    TChainLink: ChainLink + Sync,
+    TFuture: Future<Output = TChainLink>,
+    TChainLink::TInput: Arbitrary + Debug + Send,
+    TChainLink::TOutput: Clone + Send + Sync
+{
+    check_law(|inputs: Vec<TChainLink::TInput>| {
+        let chain_link = new_chain_link();
+        async move {
+            // a chain_link! catches a panicking map block, which then reaches the dead-letter queue
+            let chain_link = chain_link.await;
+            let dead_letter_queue = DeadLetterQueue::new();
+            chain_link.set_dead_letter_queue(dead_letter_queue.clone());
+            feed_and_collect(&chain_link, inputs).await;
+            match dead_letter_queue.take_all().into_iter().next() {
+                Some(dead_letter) => Err(TestCaseError::fail(dead_letter.failure.to_string())),
+                None => Ok(())
+            }
+        }
+    });
+}
+
+pub fn check_output_count_equals_input_count<TChainLink, TFuture>(new_chain_link: impl Fn() -> TFuture)
+where
+    TChainLink: ChainLink + Sync,
+    TFuture: Future<Output = TChainLink>,
+    TChainLink::TInput: Arbitrary + Debug + Send,
+    TChainLink::TOutput: Clone + Send + Sync
+{
+    check_law(|inputs: Vec<TChainLink::TInput>| {
+        let chain_link = new_chain_link();
+        async move {
+            let input_count = inputs.len();
+            let output_count = feed_and_collect(&chain_link.await, inputs).await.len();
+            proptest::prop_assert_eq!(input_count, output_count);
+            Ok(())
+        }
+    });
+}
+
+pub fn check_deterministic<TChainLink, TFuture>(new_chain_link: impl Fn() -> TFuture)
+where
+    TChainLink: ChainLink + Sync,
+    TFuture: Future<Output = TChainLink>,
+    TChainLink::TInput: Arbitrary + Clone + Debug + Send,
+    TChainLink::TOutput: Clone + Debug + PartialEq + Send + Sync
+{
+    check_equivalent(&new_chain_link, &new_chain_link);
+}
+
+// both ChainLinks must produce the same outputs, such as a chain! and the manual composition of its ChainLinks
+pub fn check_equivalent<TLeft, TRight, TLeftFuture, TRightFuture>(new_left: impl Fn() -> TLeftFuture, new_right: impl Fn() -> TRightFuture)
+where
+    TLeft: ChainLink + Sync,
+    TRight: ChainLink<TInput = TLeft::TInput, TOutput = TLeft::TOutput> + Sync,
+    TLeftFuture: Future<Output = TLeft>,
+    TRightFuture: Future<Output = TRight>,
+    TLeft::TInput: Arbitrary + Clone + Debug + Send,
+    TLeft::TOutput: Clone + Debug + PartialEq + Send + Sync
+{
+    check_law(|inputs: Vec<TLeft::TInput>| {
+        let left = new_left();
+        let right = new_right();
+        async move {
+            let left_outputs = feed_and_collect(&left.await, inputs.clone()).await;
+            let right_outputs = feed_and_collect(&right.await, inputs).await;
+            proptest::prop_assert_eq!(left_outputs, right_outputs);
+            Ok(())
+        }
+    });
+}
+
+// the manual composition of two ChainLinks, passing each output of the first into the second
+pub struct Composed<TFirst, TSecond> {
+    first: TFirst,
+    second: TSecond
+}
+
+impl<TFirst, TSecond> Composed<TFirst, TSecond> {
+    pub fn new(first: TFirst, second: TSecond) -> Self {
+        Composed {
+            first,
+            second
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<TFirst, TSecond> ChainLink for Composed<TFirst, TSecond>
+where
+    TFirst: ChainLink + Send + Sync,
+    TSecond: ChainLink<TInput = TFirst::TOutput> + Send + Sync,
+    TFirst::TInput: Send + Sync,
+    TFirst::TOutput: Send + Sync,
+    TSecond::TOutput: Send + Sync
+{
+    type TInput = TFirst::TInput;
+    type TOutput = TSecond::TOutput;
+
+    async fn push(&self, input: Arc<RwLock<Self::TInput>>) {
+        self.first.push(input).await;
+    }
+    async fn push_raw(&self, input: Self::TInput) {
+        self.first.push_raw(input).await;
+    }
+    async fn push_if_empty(&self, input: Arc<RwLock<Self::TInput>>) {
+        self.first.push_if_empty(input).await;
+    }
+    async fn push_raw_if_empty(&self, input: Self::TInput) {
+        self.first.push_raw_if_empty(input).await;
+    }
+    async fn push_replace(&self, input: Arc<RwLock<Self::TInput>>) {
+        self.first.push_replace(input).await;
+    }
+    async fn push_raw_replace(&self, input: Self::TInput) {
+        self.first.push_raw_replace(input).await;
+    }
+    async fn try_pop(&self) -> Option<Arc<RwLock<Self::TOutput>>> {
+        self.second.try_pop().await
+    }
+    async fn push_item_with_headers(&self, input: Item<Self::TInput>, headers: HeaderMap) {
+        self.first.push_item_with_headers(input, headers).await;
+    }
+    async fn try_pop_item_with_headers(&self) -> Option<(Item<Self::TOutput>, HeaderMap)> {
+        self.second.try_pop_item_with_headers().await
+    }
+    async fn process(&self) -> ProcessOutcome {
+        let first_outcome = self.first.process().await;
+        // a flat or batching ChainLink may produce several outputs at once, each of which is handed along
+        while let Some((next_input, headers)) = self.first.try_pop_item_with_headers().await {
+            self.second.push_item_with_headers(next_input, headers).await;
+        }
+        let second_outcome = self.second.process().await;
+        let mut outcome = ProcessOutcome::consumed(first_outcome.consumed_count, second_outcome.produced_count);
+        outcome.merge_state(&first_outcome);
+        outcome.merge_state(&second_outcome);
+        outcome
+    }
+    fn set_scheduler(&self, scheduler: Arc<dyn Scheduler>) {
+        self.first.set_scheduler(scheduler.clone());
+        self.second.set_scheduler(scheduler);
+    }
+    fn set_clock(&self, clock: Clock) {
+        self.first.set_clock(clock.clone());
+        self.second.set_clock(clock);
+    }
+    fn set_dead_letter_queue(&self, dead_letter_queue: DeadLetterQueue) {
+        self.first.set_dead_letter_queue(dead_letter_queue.clone());
+        self.second.set_dead_letter_queue(dead_letter_queue);
+    }
+    fn set_watchdog(&self, watchdog: Watchdog) {
+        self.first.set_watchdog(watchdog.clone());
+        self.second.set_watchdog(watchdog);
+    }
+    async fn redrive(&self, dead_letter: DeadLetter) -> Result<(), DeadLetter> {
+        match self.first.redrive(dead_letter).await {
+            Ok(()) => Ok(()),
+            Err(dead_letter) => self.second.redrive(dead_letter).await
+        }
+    }
+}
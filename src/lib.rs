@@ -3,4 +3,26 @@ pub mod macros;
 pub mod framework;
 mod test;
 pub mod queue;
-pub use macros::{paste, async_trait, RwLock, Mutex, join, join_all, Builder, Rng, thread_rng, SliceRandom};
\ No newline at end of file
+pub mod debugger;
+pub mod record;
+pub mod schedule;
+pub mod testing;
+pub mod clock;
+pub mod item;
+pub mod function;
+pub mod batch;
+pub mod window;
+pub mod join;
+pub mod port;
+pub mod side;
+pub mod dead_letter;
+pub mod retry;
+pub mod watchdog;
+pub mod circuit;
+pub mod rate;
+pub mod conflate;
+pub mod expire;
+pub mod envelope;
+#[cfg(feature = "proptest")]
+pub mod laws;
+pub use macros::{paste, async_trait, RwLock, Mutex, OwnedRwLockReadGuard, spawn_blocking, join, join_all, FutureExt, FuturesUnordered, Stream, StreamExt, Builder, Rng, thread_rng, SliceRandom};
\ No newline at end of file
@@ -1,17 +1,222 @@
 pub use async_trait::async_trait;
 pub use paste::paste;
-pub use tokio::{sync::{RwLock, Mutex}, runtime::Builder};
-pub use futures::{join, future::join_all};
+pub use tokio::{sync::{RwLock, Mutex, OwnedRwLockReadGuard}, runtime::Builder, task::spawn_blocking};
+pub use futures::{join, future::{join_all, FutureExt}, stream::{FuturesUnordered, Stream, StreamExt}};
 pub use rand::{Rng, thread_rng, seq::SliceRandom};
 
 #[macro_export]
 macro_rules! chain_link {
-    ($type:ty => ($($property_name:ident: $property_type:ty),*), $receive_name:ident: $receive_type:ty => $output_type:ty, $map_block:block) => {
+    (map shared () $type:ty, $receive_name:ident: $receive_type:ty => $output_type:ty, $map_block:block) => {
+        $crate::paste! {
+            #[allow(dead_code)]
+            pub struct [<_ $type Input>] {
+                received: Option<std::sync::Arc<$crate::RwLock<$receive_type>>>,
+                initializer: std::sync::Arc<$crate::RwLock<[<$type Initializer>]>>,
+                clock: $crate::clock::Clock,
+                side: [<$type SideOutputs>],
+                headers: $crate::envelope::Headers
+            }
+
+            impl $type {
+                async fn map_received(&self, received: Option<$crate::item::Item<$receive_type>>, headers: $crate::envelope::Headers) -> Result<Option<$output_type>, $crate::dead_letter::Failure> {
+                    async fn get_map_block_result($receive_name: [<_ $type Input>]) -> Option<$output_type> {
+                        $map_block
+                    }
+                    let side = [<$type SideOutputs>]::default();
+                    let $receive_name = [<_ $type Input>] {
+                        received: received.map($crate::item::Item::into_shared),
+                        initializer: self.initializer.clone(),
+                        clock: self.clock.lock().unwrap().clone(),
+                        side: side.clone(),
+                        headers
+                    };
+                    let output = get_map_block_result($receive_name).await;
+                    self.queue_side_outputs(&side).await;
+                    return Ok(output);
+                }
+            }
+        }
+    };
+    (map owned () $type:ty, $receive_name:ident: $receive_type:ty => $output_type:ty, $map_block:block) => {
+        $crate::paste! {
+            #[allow(dead_code)]
+            pub struct [<_ $type Input>] {
+                received: Option<$crate::item::Item<$receive_type>>,
+                initializer: std::sync::Arc<$crate::RwLock<[<$type Initializer>]>>,
+                clock: $crate::clock::Clock,
+                side: [<$type SideOutputs>],
+                headers: $crate::envelope::Headers
+            }
+
+            impl $type {
+                async fn map_received(&self, received: Option<$crate::item::Item<$receive_type>>, headers: $crate::envelope::Headers) -> Result<Option<$output_type>, $crate::dead_letter::Failure> {
+                    async fn get_map_block_result($receive_name: [<_ $type Input>]) -> Option<$output_type> {
+                        $map_block
+                    }
+                    let side = [<$type SideOutputs>]::default();
+                    let $receive_name = [<_ $type Input>] {
+                        received,
+                        initializer: self.initializer.clone(),
+                        clock: self.clock.lock().unwrap().clone(),
+                        side: side.clone(),
+                        headers
+                    };
+                    let output = get_map_block_result($receive_name).await;
+                    self.queue_side_outputs(&side).await;
+                    return Ok(output);
+                }
+            }
+        }
+    };
+    (map blocking () $type:ty, $receive_name:ident: $receive_type:ty => $output_type:ty, $map_block:block) => {
+        $crate::paste! {
+            #[allow(dead_code)]
+            pub struct [<_ $type Input>] {
+                received: Option<$receive_type>,
+                initializer: $crate::OwnedRwLockReadGuard<[<$type Initializer>]>,
+                clock: $crate::clock::Clock,
+                side: [<$type SideOutputs>],
+                headers: $crate::envelope::Headers
+            }
+
+            impl $type {
+                async fn map_received(&self, received: Option<$crate::item::Item<$receive_type>>, headers: $crate::envelope::Headers) -> Result<Option<$output_type>, $crate::dead_letter::Failure> {
+                    fn get_map_block_result($receive_name: [<_ $type Input>]) -> Option<$output_type> {
+                        $map_block
+                    }
+                    // a blocking map block is only run for an input, so an idle `process` neither locks the initializer nor takes a blocking thread
+                    let Some(received) = received else {
+                        return Ok(None);
+                    };
+                    let received = received.into_owned().await;
+                    let side = [<$type SideOutputs>]::default();
+                    let $receive_name = [<_ $type Input>] {
+                        received: Some(received),
+                        initializer: self.initializer.clone().read_owned().await,
+                        clock: self.clock.lock().unwrap().clone(),
+                        side: side.clone(),
+                        headers
+                    };
+                    // the map block runs on a blocking thread so that it does not hold up the async runtime
+                    let output = match $crate::spawn_blocking(move || get_map_block_result($receive_name)).await {
+                        Ok(output) => output,
+                        Err(error) => std::panic::resume_unwind(error.into_panic())
+                    };
+                    self.queue_side_outputs(&side).await;
+                    return Ok(output);
+                }
+            }
+        }
+    };
+    (map flat () $type:ty, $receive_name:ident: $receive_type:ty => $output_type:ty, $map_block:block) => {
+        $crate::paste! {
+            #[allow(dead_code)]
+            pub struct [<_ $type Input>] {
+                received: Option<std::sync::Arc<$crate::RwLock<$receive_type>>>,
+                initializer: std::sync::Arc<$crate::RwLock<[<$type Initializer>]>>,
+                clock: $crate::clock::Clock,
+                side: [<$type SideOutputs>],
+                headers: $crate::envelope::Headers
+            }
+
+            impl $type {
+                async fn map_received(&self, received: Option<$crate::item::Item<$receive_type>>, headers: $crate::envelope::Headers) -> Result<Vec<$output_type>, $crate::dead_letter::Failure> {
+                    async fn get_map_block_result($receive_name: [<_ $type Input>]) -> impl IntoIterator<Item = $output_type> {
+                        $map_block
+                    }
+                    let side = [<$type SideOutputs>]::default();
+                    let $receive_name = [<_ $type Input>] {
+                        received: received.map($crate::item::Item::into_shared),
+                        initializer: self.initializer.clone(),
+                        clock: self.clock.lock().unwrap().clone(),
+                        side: side.clone(),
+                        headers
+                    };
+                    let outputs = get_map_block_result($receive_name).await.into_iter().collect();
+                    self.queue_side_outputs(&side).await;
+                    return Ok(outputs);
+                }
+            }
+        }
+    };
+    (map stream () $type:ty, $receive_name:ident: $receive_type:ty => $output_type:ty, $map_block:block) => {
+        $crate::paste! {
+            #[allow(dead_code)]
+            pub struct [<_ $type Input>] {
+                received: Option<std::sync::Arc<$crate::RwLock<$receive_type>>>,
+                initializer: std::sync::Arc<$crate::RwLock<[<$type Initializer>]>>,
+                clock: $crate::clock::Clock,
+                side: [<$type SideOutputs>],
+                headers: $crate::envelope::Headers
+            }
+
+            impl $type {
+                async fn map_received(&self, received: Option<$crate::item::Item<$receive_type>>, headers: $crate::envelope::Headers) -> Result<Vec<$output_type>, $crate::dead_letter::Failure> {
+                    async fn get_map_block_result($receive_name: [<_ $type Input>]) -> impl $crate::Stream<Item = $output_type> + Send {
+                        $map_block
+                    }
+                    let side = [<$type SideOutputs>]::default();
+                    let $receive_name = [<_ $type Input>] {
+                        received: received.map($crate::item::Item::into_shared),
+                        initializer: self.initializer.clone(),
+                        clock: self.clock.lock().unwrap().clone(),
+                        side: side.clone(),
+                        headers
+                    };
+                    // the stream is read to its end within a single `process` invocation
+                    let outputs = $crate::StreamExt::collect(get_map_block_result($receive_name).await).await;
+                    self.queue_side_outputs(&side).await;
+                    return Ok(outputs);
+                }
+            }
+        }
+    };
+    (map fallible ($error_type:ty) $type:ty, $receive_name:ident: $receive_type:ty => $output_type:ty, $map_block:block) => {
+        $crate::paste! {
+            #[allow(dead_code)]
+            pub struct [<_ $type Input>] {
+                received: Option<std::sync::Arc<$crate::RwLock<$receive_type>>>,
+                initializer: std::sync::Arc<$crate::RwLock<[<$type Initializer>]>>,
+                clock: $crate::clock::Clock,
+                side: [<$type SideOutputs>],
+                headers: $crate::envelope::Headers
+            }
+
+            impl $type {
+                async fn map_received(&self, received: Option<$crate::item::Item<$receive_type>>, headers: $crate::envelope::Headers) -> Result<Option<$output_type>, $crate::dead_letter::Failure> {
+                    async fn get_map_block_result($receive_name: [<_ $type Input>]) -> Result<Option<$output_type>, $error_type> {
+                        $map_block
+                    }
+                    let side = [<$type SideOutputs>]::default();
+                    let $receive_name = [<_ $type Input>] {
+                        received: received.map($crate::item::Item::into_shared),
+                        initializer: self.initializer.clone(),
+                        clock: self.clock.lock().unwrap().clone(),
+                        side: side.clone(),
+                        headers
+                    };
+                    let output = get_map_block_result($receive_name).await;
+                    // side outputs sent before an error are kept
+                    self.queue_side_outputs(&side).await;
+                    return output.map_err($crate::dead_letter::Failure::from_error);
+                }
+            }
+        }
+    };
+    (end $type:ty => ($($property_name:ident: $property_type:ty),*) ($($side_name:ident: $side_type:ty),*) ($($rate_limit:ident $($unshared:ident)?)?) ($($error_type:ty)?), $receive_name:ident: $receive_type:ty => $output_type:ty, $kind:ident, ($concurrency:expr) ($is_ordered:expr), $map_block:block) => {
         $crate::paste! {
             pub struct $type {
                 initializer: std::sync::Arc<$crate::RwLock<[<$type Initializer>]>>,
-                input_queue: $crate::queue::Queue<std::sync::Arc<$crate::RwLock<$receive_type>>>,
-                output_queue: $crate::queue::Queue<std::sync::Arc<$crate::RwLock<$output_type>>>
+                input_queue: $crate::queue::Queue<$crate::item::Item<$receive_type>>,
+                output_queue: $crate::queue::Queue<$crate::item::Item<$output_type>>,
+                $(
+                    [<$side_name _queue>]: $crate::queue::Queue<$crate::item::Item<$side_type>>,
+                )*
+                clock: std::sync::Mutex<$crate::clock::Clock>,
+                dead_letter_queue: std::sync::Mutex<Option<$crate::dead_letter::DeadLetterQueue>>,
+                // the limiter of this instance alone, when the rate limit is unshared
+                #[allow(dead_code)]
+                rate_limiter: Option<$crate::rate::RateLimiter>
             }
 
             pub struct [<$type Initializer>] {
@@ -20,132 +225,367 @@ macro_rules! chain_link {
                 )*
             }
 
+            // the map block sends to each side output through `input.side`
+            #[derive(Clone, Default)]
+            pub struct [<$type SideOutputs>] {
+                $(
+                    pub $side_name: $crate::side::SideSender<$side_type>,
+                )*
+            }
+
+            $(
+                pub struct [<$type $side_name:camel>];
+
+                #[$crate::async_trait]
+                impl $crate::side::SideOutput for [<$type $side_name:camel>] {
+                    type TChainLink = $type;
+                    type TValue = $side_type;
+
+                    async fn try_pop_item(chain_link: &$type) -> Option<$crate::item::Item<$side_type>> {
+                        return chain_link.[<$side_name _queue>].try_pop().await;
+                    }
+                }
+            )*
+
             #[allow(dead_code)]
             impl $type {
                 pub async fn new(initializer: std::sync::Arc<$crate::RwLock::<[<$type Initializer>]>>) -> Self {
+                    let rate_limiter: Option<$crate::rate::RateLimiter> = None $($(.or(Some(initializer.read().await.$rate_limit.$unshared())))?)?;
                     $type {
                         initializer,
-                        input_queue: $crate::queue::Queue::<std::sync::Arc<$crate::RwLock<$receive_type>>>::default(),
-                        output_queue: $crate::queue::Queue::<std::sync::Arc<$crate::RwLock<$output_type>>>::default()
+                        input_queue: $crate::queue::Queue::<$crate::item::Item<$receive_type>>::default(),
+                        output_queue: $crate::queue::Queue::<$crate::item::Item<$output_type>>::default(),
+                        $(
+                            [<$side_name _queue>]: $crate::queue::Queue::<$crate::item::Item<$side_type>>::default(),
+                        )*
+                        clock: std::sync::Mutex::new($crate::clock::Clock::default()),
+                        dead_letter_queue: std::sync::Mutex::new(None),
+                        rate_limiter
                     }
                 }
                 pub async fn new_raw(initializer: [<$type Initializer>]) -> Self {
                     $type::new(std::sync::Arc::new($crate::RwLock::new(initializer))).await
                 }
+                $(
+                    pub async fn [<try_pop_ $side_name>](&self) -> Option<std::sync::Arc<$crate::RwLock<$side_type>>> {
+                        return self.[<$side_name _queue>].try_pop().await.map($crate::item::Item::into_shared);
+                    }
+                )*
+                // an input whose map block panics or returns an error goes to the dead-letter queue when one is set and is dropped otherwise, returning `None`
+                // the outputs inherit the headers of the input as the map block left them
+                async fn map_input(&self, received: $crate::item::Item<$receive_type>, headers: $crate::envelope::HeaderMap) -> Option<(Vec<$output_type>, $crate::envelope::HeaderMap)> {
+                    let dead_letter_queue = self.dead_letter_queue.lock().unwrap().clone();
+                    // the input is only shared when it has to be kept for the dead-letter queue
+                    let (received, kept) = match dead_letter_queue {
+                        Some(_) => {
+                            let received = received.into_shared();
+                            ($crate::item::Item::Shared(received.clone()), Some(received))
+                        },
+                        None => (received, None)
+                    };
+                    let headers = $crate::envelope::Headers::new(headers);
+                    let mapped = std::panic::AssertUnwindSafe(self.map_received(Some(received), headers.clone()));
+                    let failure = match $crate::FutureExt::catch_unwind(mapped).await {
+                        Ok(Ok(outputs)) => return Some((IntoIterator::into_iter(outputs).collect(), headers.to_map())),
+                        Ok(Err(failure)) => failure,
+                        Err(panic) => $crate::dead_letter::Failure::from_panic(panic.as_ref())
+                    };
+                    if let (Some(dead_letter_queue), Some(received)) = (dead_letter_queue, kept) {
+                        dead_letter_queue.send(received, stringify!($type), failure, 1);
+                    }
+                    return None;
+                }
+                #[allow(unused_variables)]
+                async fn queue_side_outputs(&self, side: &[<$type SideOutputs>]) {
+                    $(
+                        for value in side.$side_name.take() {
+                            self.[<$side_name _queue>].push($crate::item::Item::Owned(value)).await;
+                        }
+                    )*
+                }
             }
 
-            #[allow(dead_code)]
-            pub struct [<_ $type Input>] {
-                received: Option<std::sync::Arc<$crate::RwLock<$receive_type>>>,
-                initializer: std::sync::Arc<$crate::RwLock<[<$type Initializer>]>>
+            #[$crate::async_trait]
+            impl $crate::framework::Initializable for $type {
+                type TInitializer = [<$type Initializer>];
+
+                async fn initialize(initializer: std::sync::Arc<$crate::RwLock<[<$type Initializer>]>>) -> Self {
+                    $type::new(initializer).await
+                }
             }
 
+            chain_link!(map $kind ($($error_type)?) $type, $receive_name: $receive_type => $output_type, $map_block);
+
             #[$crate::async_trait]
             impl $crate::framework::ChainLink for $type {
                 type TInput = $receive_type;
                 type TOutput = $output_type;
 
                 async fn push(&self, input: std::sync::Arc<$crate::RwLock<$receive_type>>) -> () {
-                    self.input_queue.push(input).await;
+                    self.input_queue.push($crate::item::Item::Shared(input)).await;
                 }
                 async fn push_raw(&self, input: $receive_type) -> () {
-                    self.push(std::sync::Arc::new($crate::RwLock::new(input))).await
+                    self.input_queue.push($crate::item::Item::Owned(input)).await;
                 }
                 async fn push_if_empty(&self, input: std::sync::Arc<$crate::RwLock<$receive_type>>) -> () {
-                    self.input_queue.push_if_empty(input).await;
+                    self.input_queue.push_if_empty($crate::item::Item::Shared(input)).await;
                 }
                 async fn push_raw_if_empty(&self, input: $receive_type) -> () {
-                    self.push_if_empty(std::sync::Arc::new($crate::RwLock::new(input))).await
+                    self.input_queue.push_if_empty($crate::item::Item::Owned(input)).await;
+                }
+                async fn push_replace(&self, input: std::sync::Arc<$crate::RwLock<$receive_type>>) -> () {
+                    self.input_queue.push_replace($crate::item::Item::Shared(input)).await;
+                }
+                async fn push_raw_replace(&self, input: $receive_type) -> () {
+                    self.input_queue.push_replace($crate::item::Item::Owned(input)).await;
                 }
                 async fn try_pop(&self) -> Option<std::sync::Arc<$crate::RwLock<$output_type>>> {
-                    self.output_queue.try_pop().await.map(|element| {
-                        element.into()
-                    })
+                    self.output_queue.try_pop().await.map($crate::item::Item::into_shared)
                 }
-                async fn process(&self) -> bool {
-                    async fn get_map_block_result($receive_name: [<_ $type Input>]) -> Option<$output_type> {
-                        $map_block
-                    }
-                    let $receive_name = [<_ $type Input>] {
-                        received: None,
-                        initializer: self.initializer.clone()
+                async fn push_item(&self, input: $crate::item::Item<$receive_type>) -> () {
+                    self.input_queue.push(input).await;
+                }
+                async fn try_pop_item(&self) -> Option<$crate::item::Item<$output_type>> {
+                    self.output_queue.try_pop().await
+                }
+                async fn push_item_with_headers(&self, input: $crate::item::Item<$receive_type>, headers: $crate::envelope::HeaderMap) -> () {
+                    self.input_queue.push_with_headers(input, headers).await;
+                }
+                async fn try_pop_item_with_headers(&self) -> Option<($crate::item::Item<$output_type>, $crate::envelope::HeaderMap)> {
+                    self.output_queue.try_pop_with_headers().await
+                }
+                fn set_clock(&self, clock: $crate::clock::Clock) {
+                    *self.clock.lock().unwrap() = clock;
+                }
+                fn set_dead_letter_queue(&self, dead_letter_queue: $crate::dead_letter::DeadLetterQueue) {
+                    self.dead_letter_queue.lock().unwrap().replace(dead_letter_queue);
+                }
+                async fn redrive(&self, dead_letter: $crate::dead_letter::DeadLetter) -> Result<(), $crate::dead_letter::DeadLetter> {
+                    let dead_letter_queue = self.dead_letter_queue.lock().unwrap().clone();
+                    let is_matching_path = dead_letter_queue.is_some_and(|dead_letter_queue| dead_letter_queue.path_to(stringify!($type)) == dead_letter.path);
+                    let input = dead_letter.input::<$receive_type>();
+                    return match (is_matching_path, input) {
+                        (true, Some(input)) => {
+                            self.input_queue.push($crate::item::Item::Shared(input)).await;
+                            Ok(())
+                        },
+                        _ => Err(dead_letter)
                     };
-                    if let Some(output) = get_map_block_result($receive_name).await {
-                        self.output_queue.push(std::sync::Arc::new($crate::RwLock::new(output))).await;
-                        return true;
+                }
+                async fn process(&self) -> $crate::framework::ProcessOutcome {
+                    // a map block may produce outputs without an input, and a flat or stream map block may produce several
+                    let mut produced_count: usize = 0;
+                    let mut failed_count: usize = 0;
+                    // without an input there is nothing to send to the dead-letter queue, so an error or panic is only counted
+                    let headers = $crate::envelope::Headers::default();
+                    let mapped = std::panic::AssertUnwindSafe(self.map_received(None, headers.clone()));
+                    match $crate::FutureExt::catch_unwind(mapped).await {
+                        Ok(Ok(outputs)) => {
+                            for output in IntoIterator::into_iter(outputs) {
+                                self.output_queue.push_with_headers($crate::item::Item::Owned(output), headers.to_map()).await;
+                                produced_count += 1;
+                            }
+                        },
+                        _ => failed_count += 1
+                    }
+                    if produced_count != 0 {
+                        let mut outcome = $crate::framework::ProcessOutcome::consumed(0, produced_count);
+                        outcome.failed_count = failed_count;
+                        return outcome;
                     }
-                    else if let Some($receive_name) = self.input_queue.try_pop().await {
-                        let $receive_name = [<_ $type Input>] {
-                            received: Some($receive_name),
-                            initializer: self.initializer.clone()
+
+                    // every input taken is mapped at the same time against the same initializer
+                    #[allow(unused_mut)]
+                    let mut permitted_count: usize = $concurrency;
+                    $(
+                        // unless the rate limit is unshared, the limiter is read from the initializer, so every instance made from it shares the limit, such as each within a duplicate!
+                        let waiting_count = self.input_queue.len().await;
+                        let limiter = match self.rate_limiter.as_ref() {
+                            Some(rate_limiter) => rate_limiter.clone(),
+                            None => self.initializer.read().await.$rate_limit.clone()
                         };
-                        if let Some(output) = get_map_block_result($receive_name).await {
-                            self.output_queue.push(std::sync::Arc::new($crate::RwLock::new(output))).await;
-                            return true;
+                        let now = self.clock.lock().unwrap().now();
+                        permitted_count = $crate::rate::RateLimiter::acquire_up_to(&limiter, permitted_count.min(waiting_count), now);
+                        if permitted_count == 0 && waiting_count != 0 {
+                            let mut outcome = $crate::framework::ProcessOutcome::busy();
+                            outcome.due_in = Some(limiter.due_in(now));
+                            outcome.failed_count = failed_count;
+                            return outcome;
+                        }
+                    )?
+                    let received_items = self.input_queue.try_pop_up_to_with_headers(permitted_count).await;
+                    if received_items.is_empty() {
+                        let mut outcome = $crate::framework::ProcessOutcome::idle();
+                        outcome.failed_count = failed_count;
+                        return outcome;
+                    }
+                    let consumed_count = received_items.len();
+                    if $is_ordered {
+                        let outputs = $crate::join_all(received_items.into_iter().map(|(received, headers)| self.map_input(received, headers))).await;
+                        for outputs in outputs {
+                            let Some((outputs, headers)) = outputs else {
+                                failed_count += 1;
+                                continue;
+                            };
+                            for output in outputs {
+                                self.output_queue.push_with_headers($crate::item::Item::Owned(output), headers.clone()).await;
+                                produced_count += 1;
+                            }
                         }
-                    } 
-                    return false;
+                    }
+                    else {
+                        // each output is available as soon as it is mapped
+                        let mut outputs = received_items.into_iter().map(|(received, headers)| self.map_input(received, headers)).collect::<$crate::FuturesUnordered<_>>();
+                        while let Some(outputs) = $crate::StreamExt::next(&mut outputs).await {
+                            let Some((outputs, headers)) = outputs else {
+                                failed_count += 1;
+                                continue;
+                            };
+                            for output in outputs {
+                                self.output_queue.push_with_headers($crate::item::Item::Owned(output), headers.clone()).await;
+                                produced_count += 1;
+                            }
+                        }
+                    }
+                    let mut outcome = $crate::framework::ProcessOutcome::consumed(consumed_count, produced_count);
+                    outcome.failed_count = failed_count;
+                    return outcome;
                 }
             }
         }
     };
-    ($type:ty, $receive_name:ident: $receive_type:ty => $output_type:ty, $map_block:block) => {
-        chain_link!($type => (), $receive_name: $receive_type => $output_type, $map_block);
+    // a fallible map block returns `Result<Option<T>, E>`, where an error goes to the dead-letter queue as a `Failure` that keeps it, so E must implement `Display + Send + Sync + 'static`
+    (options $type:ty => ($($property_name:ident: $property_type:ty),*) ($($side_name:ident: $side_type:ty),*) ($($rate_limit:tt)*), $receive_name:ident: $receive_type:ty => $output_type:ty, fallible = $error_type:ty, concurrency = $concurrency:expr, ordered, $map_block:block) => {
+        chain_link!(end $type => ($($property_name: $property_type),*) ($($side_name: $side_type),*) ($($rate_limit)*) ($error_type), $receive_name: $receive_type => $output_type, fallible, ($concurrency) (true), $map_block);
+    };
+    (options $type:ty => ($($property_name:ident: $property_type:ty),*) ($($side_name:ident: $side_type:ty),*) ($($rate_limit:tt)*), $receive_name:ident: $receive_type:ty => $output_type:ty, fallible = $error_type:ty, concurrency = $concurrency:expr, $map_block:block) => {
+        chain_link!(end $type => ($($property_name: $property_type),*) ($($side_name: $side_type),*) ($($rate_limit)*) ($error_type), $receive_name: $receive_type => $output_type, fallible, ($concurrency) (false), $map_block);
+    };
+    (options $type:ty => ($($property_name:ident: $property_type:ty),*) ($($side_name:ident: $side_type:ty),*) ($($rate_limit:tt)*), $receive_name:ident: $receive_type:ty => $output_type:ty, fallible = $error_type:ty, $map_block:block) => {
+        chain_link!(end $type => ($($property_name: $property_type),*) ($($side_name: $side_type),*) ($($rate_limit)*) ($error_type), $receive_name: $receive_type => $output_type, fallible, (1) (true), $map_block);
+    };
+    // up to `concurrency` queued inputs are mapped at the same time, and `ordered` keeps the outputs in the order of the inputs
+    (options $type:ty => ($($property_name:ident: $property_type:ty),*) ($($side_name:ident: $side_type:ty),*) ($($rate_limit:tt)*), $receive_name:ident: $receive_type:ty => $output_type:ty, concurrency = $concurrency:expr, ordered, $map_block:block) => {
+        chain_link!(end $type => ($($property_name: $property_type),*) ($($side_name: $side_type),*) ($($rate_limit)*) (), $receive_name: $receive_type => $output_type, shared, ($concurrency) (true), $map_block);
+    };
+    (options $type:ty => ($($property_name:ident: $property_type:ty),*) ($($side_name:ident: $side_type:ty),*) ($($rate_limit:tt)*), $receive_name:ident: $receive_type:ty => $output_type:ty, concurrency = $concurrency:expr, $map_block:block) => {
+        chain_link!(end $type => ($($property_name: $property_type),*) ($($side_name: $side_type),*) ($($rate_limit)*) (), $receive_name: $receive_type => $output_type, shared, ($concurrency) (false), $map_block);
+    };
+    // the received item is passed by value when it has a single consumer, so the map block reads it through `Item`
+    // a blocking map block is synchronous, receiving the input by value and running on a blocking thread
+    // a flat map block returns any iterator of outputs and a stream map block returns a stream of outputs, each of which is queued
+    (options $type:ty => ($($property_name:ident: $property_type:ty),*) ($($side_name:ident: $side_type:ty),*) ($($rate_limit:tt)*), $receive_name:ident: $receive_type:ty => $output_type:ty, $kind:ident, concurrency = $concurrency:expr, ordered, $map_block:block) => {
+        chain_link!(end $type => ($($property_name: $property_type),*) ($($side_name: $side_type),*) ($($rate_limit)*) (), $receive_name: $receive_type => $output_type, $kind, ($concurrency) (true), $map_block);
+    };
+    (options $type:ty => ($($property_name:ident: $property_type:ty),*) ($($side_name:ident: $side_type:ty),*) ($($rate_limit:tt)*), $receive_name:ident: $receive_type:ty => $output_type:ty, $kind:ident, concurrency = $concurrency:expr, $map_block:block) => {
+        chain_link!(end $type => ($($property_name: $property_type),*) ($($side_name: $side_type),*) ($($rate_limit)*) (), $receive_name: $receive_type => $output_type, $kind, ($concurrency) (false), $map_block);
+    };
+    (options $type:ty => ($($property_name:ident: $property_type:ty),*) ($($side_name:ident: $side_type:ty),*) ($($rate_limit:tt)*), $receive_name:ident: $receive_type:ty => $output_type:ty, $kind:ident, $map_block:block) => {
+        chain_link!(end $type => ($($property_name: $property_type),*) ($($side_name: $side_type),*) ($($rate_limit)*) (), $receive_name: $receive_type => $output_type, $kind, (1) (true), $map_block);
+    };
+    (options $type:ty => ($($property_name:ident: $property_type:ty),*) ($($side_name:ident: $side_type:ty),*) ($($rate_limit:tt)*), $receive_name:ident: $receive_type:ty => $output_type:ty, $map_block:block) => {
+        chain_link!(end $type => ($($property_name: $property_type),*) ($($side_name: $side_type),*) ($($rate_limit)*) (), $receive_name: $receive_type => $output_type, shared, (1) (true), $map_block);
+    };
+    // a rate limit names a `RateLimiter` property of the initializer, and `process` takes no more inputs than it allows
+    // `unshared` gives each instance its own limiter with the same limit, made when the instance is
+    (limit $type:ty => ($($property_name:ident: $property_type:ty),*) ($($side_name:ident: $side_type:ty),*), $receive_name:ident: $receive_type:ty => $output_type:ty, rate_limit = $rate_limit:ident, unshared, $($rest:tt)*) => {
+        chain_link!(options $type => ($($property_name: $property_type),*) ($($side_name: $side_type),*) ($rate_limit unshared), $receive_name: $receive_type => $output_type, $($rest)*);
+    };
+    (limit $type:ty => ($($property_name:ident: $property_type:ty),*) ($($side_name:ident: $side_type:ty),*), $receive_name:ident: $receive_type:ty => $output_type:ty, rate_limit = $rate_limit:ident, $($rest:tt)*) => {
+        chain_link!(options $type => ($($property_name: $property_type),*) ($($side_name: $side_type),*) ($rate_limit), $receive_name: $receive_type => $output_type, $($rest)*);
+    };
+    (limit $type:ty => ($($property_name:ident: $property_type:ty),*) ($($side_name:ident: $side_type:ty),*), $receive_name:ident: $receive_type:ty => $output_type:ty, $($rest:tt)*) => {
+        chain_link!(options $type => ($($property_name: $property_type),*) ($($side_name: $side_type),*) (), $receive_name: $receive_type => $output_type, $($rest)*);
+    };
+    ($type:ty, $receive_name:ident: $($rest:tt)*) => {
+        chain_link!($type => (), $receive_name: $($rest)*);
+    };
+    // each named input port becomes a variant of the `PortInput` enum that the map block receives, along with a `Port` type used by `push_to` and `ToPort`
+    ($type:ty => ($($property_name:ident: $property_type:ty),*), $receive_name:ident: [$($port_name:ident: $port_type:ty),+] => $output_type:ty, $($rest:tt)*) => {
+        $crate::paste! {
+            pub enum [<$type PortInput>] {
+                $(
+                    [<$port_name:camel>]($port_type),
+                )+
+            }
+
+            $(
+                pub struct [<$type $port_name:camel>];
+
+                impl $crate::port::Port for [<$type $port_name:camel>] {
+                    type TChainLink = $type;
+                    type TValue = $port_type;
+
+                    fn wrap(value: $port_type) -> [<$type PortInput>] {
+                        return [<$type PortInput>]::[<$port_name:camel>](value);
+                    }
+                }
+            )+
+
+            chain_link!($type => ($($property_name: $property_type),*), $receive_name: [<$type PortInput>] => $output_type, $($rest)*);
+        }
+    };
+    // each side output has its own queue, popped with `try_pop_<side>`
+    ($type:ty => ($($property_name:ident: $property_type:ty),*), $receive_name:ident: $receive_type:ty => $output_type:ty, side: [$($side_name:ident: $side_type:ty),+], $($rest:tt)*) => {
+        chain_link!(limit $type => ($($property_name: $property_type),*) ($($side_name: $side_type),+), $receive_name: $receive_type => $output_type, $($rest)*);
+    };
+    ($type:ty => ($($property_name:ident: $property_type:ty),*), $receive_name:ident: $($rest:tt)*) => {
+        chain_link!(limit $type => ($($property_name: $property_type),*) (), $receive_name: $($rest)*);
     };
 }
 
 #[macro_export]
 macro_rules! chain {
     ($name:ty, $from:ty => $to:ty, [$($($field:ty)=>*),*]: ($choice:ident $mode:ident)) => {
-        chain!(apple $name, $from, $to, $choice, $mode, () (0) () () () () () () () () () () (x) $($($field)=>*),*);
+        chain!(apple $name, $from, $to, $choice, $mode, (0) () () () () () () () () () () (x) $($($field)=>*),*);
     };
     // only one new solo type left
-    (apple $name:ty, $from:ty, $to:ty, $choice:ident, $mode:ident, ($($bool:tt)*) ($index:expr) ($($solo_index_past:tt)*) ($($chain_index_past:tt)*) ($($solo:tt)*) ($($solo_name:tt)*) ($($first:tt)*) ($($first_name:tt)*) ($($mid:tt)*) ($($mid_name:tt)*) ($($last:tt)*) ($($last_name:tt)*) ($($prefix:tt)*) $next:ty) => {
+    (apple $name:ty, $from:ty, $to:ty, $choice:ident, $mode:ident, ($index:expr) ($($solo_index_past:tt)*) ($($chain_index_past:tt)*) ($($solo:tt)*) ($($solo_name:tt)*) ($($first:tt)*) ($($first_name:tt)*) ($($mid:tt)*) ($($mid_name:tt)*) ($($last:tt)*) ($($last_name:tt)*) ($($prefix:tt)*) $next:ty) => {
         $crate::paste! {
-            chain!(end $name, $from, $to, $choice, $mode, ($($bool)* [false]) ($index + 1) ($($solo_index_past)* [$index]) ($($chain_index_past)*) ($($solo)* [$next]) ($($solo_name)* [<$($prefix)* _ $next:snake>]) ($($first)*) ($($first_name)*) ($($mid)*) ($($mid_name)*) ($($last)*) ($($last_name)*));
+            chain!(end $name, $from, $to, $choice, $mode, ($index + 1) ($($solo_index_past)* [$index]) ($($chain_index_past)*) ($($solo)* [$next]) ($($solo_name)* [<$($prefix)* _ $next:snake>]) ($($first)*) ($($first_name)*) ($($mid)*) ($($mid_name)*) ($($last)*) ($($last_name)*));
         }
     };
     // one solo type following by another set
-    (apple $name:ty, $from:ty, $to:ty, $choice:ident, $mode:ident, ($($bool:tt)*) ($index:expr) ($($solo_index_past:tt)*) ($($chain_index_past:tt)*) ($($solo:tt)*) ($($solo_name:tt)*) ($($first:tt)*) ($($first_name:tt)*) ($($mid:tt)*) ($($mid_name:tt)*) ($($last:tt)*) ($($last_name:tt)*) ($($prefix:tt)*) $next:ty, $($($rest:ty)=>*),*) => {
+    (apple $name:ty, $from:ty, $to:ty, $choice:ident, $mode:ident, ($index:expr) ($($solo_index_past:tt)*) ($($chain_index_past:tt)*) ($($solo:tt)*) ($($solo_name:tt)*) ($($first:tt)*) ($($first_name:tt)*) ($($mid:tt)*) ($($mid_name:tt)*) ($($last:tt)*) ($($last_name:tt)*) ($($prefix:tt)*) $next:ty, $($($rest:ty)=>*),*) => {
         $crate::paste! {
             // since this is the end of a chain, the next $next will be the first or solo
-            chain!(apple $name, $from, $to, $choice, $mode, ($($bool)* [false]) ($index + 1) ($($solo_index_past)* [$index]) ($($chain_index_past)*) ($($solo)* [$next]) ($($solo_name)* [<$($prefix)* _ $next:snake>]) ($($first)*) ($($first_name)*) ($($mid)*) ($($mid_name)*) ($($last)*) ($($last_name)*) ($($prefix)* x ) $($($rest)=>*),*);
+            chain!(apple $name, $from, $to, $choice, $mode, ($index + 1) ($($solo_index_past)* [$index]) ($($chain_index_past)*) ($($solo)* [$next]) ($($solo_name)* [<$($prefix)* _ $next:snake>]) ($($first)*) ($($first_name)*) ($($mid)*) ($($mid_name)*) ($($last)*) ($($last_name)*) ($($prefix)* x ) $($($rest)=>*),*);
         }
     };
     // the first type following by a last type (no mid type) with no more types
-    (apple $name:ty, $from:ty, $to:ty, $choice:ident, $mode:ident, ($($bool:tt)*) ($index:expr) ($($solo_index_past:tt)*) ($($chain_index_past:tt)*) ($($solo:tt)*) ($($solo_name:tt)*) ($($first:tt)*) ($($first_name:tt)*) ($($mid:tt)*) ($($mid_name:tt)*) ($($last:tt)*) ($($last_name:tt)*) ($($prefix:tt)*) $next:ty => $another:ty) => {
+    (apple $name:ty, $from:ty, $to:ty, $choice:ident, $mode:ident, ($index:expr) ($($solo_index_past:tt)*) ($($chain_index_past:tt)*) ($($solo:tt)*) ($($solo_name:tt)*) ($($first:tt)*) ($($first_name:tt)*) ($($mid:tt)*) ($($mid_name:tt)*) ($($last:tt)*) ($($last_name:tt)*) ($($prefix:tt)*) $next:ty => $another:ty) => {
         $crate::paste! {
-            chain!(end $name, $from, $to, $choice, $mode, ($($bool)* [false]) ($index + 1) ($($solo_index_past)*) ($($chain_index_past)* [$index]) ($($solo)*) ($($solo_name)*) ($($first)* [$next]) ($($first_name)* [<$($prefix)* _ $next:snake>]) ($($mid)* []) ($($mid_name)* []) ($($last)* [$another]) ($($last_name)* [<$($prefix)* x _ $another:snake>]));
+            chain!(end $name, $from, $to, $choice, $mode, ($index + 1) ($($solo_index_past)*) ($($chain_index_past)* [$index]) ($($solo)*) ($($solo_name)*) ($($first)* [$next]) ($($first_name)* [<$($prefix)* _ $next:snake>]) ($($mid)* []) ($($mid_name)* []) ($($last)* [$another]) ($($last_name)* [<$($prefix)* x _ $another:snake>]));
         }
     };
     // the first type following by a last type (no mid type) with more types
-    (apple $name:ty, $from:ty, $to:ty, $choice:ident, $mode:ident, ($($bool:tt)*) ($index:expr) ($($solo_index_past:tt)*) ($($chain_index_past:tt)*) ($($solo:tt)*) ($($solo_name:tt)*) ($($first:tt)*) ($($first_name:tt)*) ($($mid:tt)*) ($($mid_name:tt)*) ($($last:tt)*) ($($last_name:tt)*) ($($prefix:tt)*) $next:ty => $another:ty, $($($rest:ty)=>*),*) => {
+    (apple $name:ty, $from:ty, $to:ty, $choice:ident, $mode:ident, ($index:expr) ($($solo_index_past:tt)*) ($($chain_index_past:tt)*) ($($solo:tt)*) ($($solo_name:tt)*) ($($first:tt)*) ($($first_name:tt)*) ($($mid:tt)*) ($($mid_name:tt)*) ($($last:tt)*) ($($last_name:tt)*) ($($prefix:tt)*) $next:ty => $another:ty, $($($rest:ty)=>*),*) => {
         $crate::paste! {
-            chain!(apple $name, $from, $to, $choice, $mode, ($($bool)* [false]) ($index + 1) ($($solo_index_past)*) ($($chain_index_past)* [$index]) ($($solo)*) ($($solo_name)*) ($($first)* [$next]) ($($first_name)* [<$($prefix)* _ $next:snake>]) ($($mid)* []) ($($mid_name)* []) ($($last)* [$another]) ($($last_name)* [<$($prefix)* x _ $another:snake>]) ($($prefix)* x x ) $($($rest)=>*),*);
+            chain!(apple $name, $from, $to, $choice, $mode, ($index + 1) ($($solo_index_past)*) ($($chain_index_past)* [$index]) ($($solo)*) ($($solo_name)*) ($($first)* [$next]) ($($first_name)* [<$($prefix)* _ $next:snake>]) ($($mid)* []) ($($mid_name)* []) ($($last)* [$another]) ($($last_name)* [<$($prefix)* x _ $another:snake>]) ($($prefix)* x x ) $($($rest)=>*),*);
         }
     };
     // the first type following by a chain
-    (apple $name:ty, $from:ty, $to:ty, $choice:ident, $mode:ident, ($($bool:tt)*) ($index:expr) ($($solo_index_past:tt)*) ($($chain_index_past:tt)*) ($($solo:tt)*) ($($solo_name:tt)*) ($($first:tt)*) ($($first_name:tt)*) ($($mid:tt)*) ($($mid_name:tt)*) ($($last:tt)*) ($($last_name:tt)*) ($($prefix:tt)*) $next:ty => $another:ty => $($($rest:ty)=>*),*) => {
+    (apple $name:ty, $from:ty, $to:ty, $choice:ident, $mode:ident, ($index:expr) ($($solo_index_past:tt)*) ($($chain_index_past:tt)*) ($($solo:tt)*) ($($solo_name:tt)*) ($($first:tt)*) ($($first_name:tt)*) ($($mid:tt)*) ($($mid_name:tt)*) ($($last:tt)*) ($($last_name:tt)*) ($($prefix:tt)*) $next:ty => $another:ty => $($($rest:ty)=>*),*) => {
         $crate::paste! {
-            chain!(carrot $name, $from, $to, $choice, $mode, ($($bool)* [false]) ($index + 1) ($($solo_index_past)*) ($($chain_index_past)* [$index]) ($($solo)*) ($($solo_name)*) ($($first)* [$next]) ($($first_name)* [<$($prefix)* _ $next:snake>]) ($($mid)*) ($($mid_name)*) ($($last)*) ($($last_name)*) ($($prefix)* x x ) ([$another]) ([<$($prefix)* x _ $another:snake>]) $($($rest)=>*),*);
+            chain!(carrot $name, $from, $to, $choice, $mode, ($index + 1) ($($solo_index_past)*) ($($chain_index_past)* [$index]) ($($solo)*) ($($solo_name)*) ($($first)* [$next]) ($($first_name)* [<$($prefix)* _ $next:snake>]) ($($mid)*) ($($mid_name)*) ($($last)*) ($($last_name)*) ($($prefix)* x x ) ([$another]) ([<$($prefix)* x _ $another:snake>]) $($($rest)=>*),*);
         }
     };
     // the middle type of a chain after already being in the middle
-    (carrot $name:ty, $from:ty, $to:ty, $choice:ident, $mode:ident, ($($bool:tt)*) ($index:expr) ($($solo_index_past:tt)*) ($($chain_index_past:tt)*) ($($solo:tt)*) ($($solo_name:tt)*) ($($first:tt)*) ($($first_name:tt)*) ($($mid:tt)*) ($($mid_name:tt)*) ($($last:tt)*) ($($last_name:tt)*) ($($prefix:tt)*) ($($past:tt)*) ($($past_name:tt)*) $next:ty => $($($rest:ty)=>*),*) => {
+    (carrot $name:ty, $from:ty, $to:ty, $choice:ident, $mode:ident, ($index:expr) ($($solo_index_past:tt)*) ($($chain_index_past:tt)*) ($($solo:tt)*) ($($solo_name:tt)*) ($($first:tt)*) ($($first_name:tt)*) ($($mid:tt)*) ($($mid_name:tt)*) ($($last:tt)*) ($($last_name:tt)*) ($($prefix:tt)*) ($($past:tt)*) ($($past_name:tt)*) $next:ty => $($($rest:ty)=>*),*) => {
         $crate::paste! {
-            chain!(carrot $name, $from, $to, $choice, $mode, ($($bool)*) ($index) ($($solo_index_past)*) ($($chain_index_past)*) ($($solo)*) ($($solo_name)*) ($($first)*) ($($first_name)*) ($($mid)*) ($($mid_name)*) ($($last)*) ($($last_name)*) ($($prefix)* x) ($($past)* [$next]) ($($past_name)* [<$($prefix)* _ $next:snake>]) $($($rest)=>*),*);
+            chain!(carrot $name, $from, $to, $choice, $mode, ($index) ($($solo_index_past)*) ($($chain_index_past)*) ($($solo)*) ($($solo_name)*) ($($first)*) ($($first_name)*) ($($mid)*) ($($mid_name)*) ($($last)*) ($($last_name)*) ($($prefix)* x) ($($past)* [$next]) ($($past_name)* [<$($prefix)* _ $next:snake>]) $($($rest)=>*),*);
         }
     };
     // the last type of a chain after already being in the middle and there is another chain
-    (carrot $name:ty, $from:ty, $to:ty, $choice:ident, $mode:ident, ($($bool:tt)*) ($index:expr) ($($solo_index_past:tt)*) ($($chain_index_past:tt)*) ($($solo:tt)*) ($($solo_name:tt)*) ($($first:tt)*) ($($first_name:tt)*) ($($mid:tt)*) ($($mid_name:tt)*) ($($last:tt)*) ($($last_name:tt)*) ($($prefix:tt)*) ($($past:tt)*) ($($past_name:tt)*) $next:ty, $($($rest:ty)=>*),*) => {
+    (carrot $name:ty, $from:ty, $to:ty, $choice:ident, $mode:ident, ($index:expr) ($($solo_index_past:tt)*) ($($chain_index_past:tt)*) ($($solo:tt)*) ($($solo_name:tt)*) ($($first:tt)*) ($($first_name:tt)*) ($($mid:tt)*) ($($mid_name:tt)*) ($($last:tt)*) ($($last_name:tt)*) ($($prefix:tt)*) ($($past:tt)*) ($($past_name:tt)*) $next:ty, $($($rest:ty)=>*),*) => {
         $crate::paste! {
-            chain!(apple $name, $from, $to, $choice, $mode, ($($bool)*) ($index) ($($solo_index_past)*) ($($chain_index_past)*) ($($solo)*) ($($solo_name)*) ($($first)*) ($($first_name)*) ($($mid)* [$($past)*]) ($($mid_name)* [$($past_name)*]) ($($last)* [$next]) ($($last_name)* [<$($prefix)* _ $next:snake>]) ($($prefix)* x ) $($($rest)=>*),*);
+            chain!(apple $name, $from, $to, $choice, $mode, ($index) ($($solo_index_past)*) ($($chain_index_past)*) ($($solo)*) ($($solo_name)*) ($($first)*) ($($first_name)*) ($($mid)* [$($past)*]) ($($mid_name)* [$($past_name)*]) ($($last)* [$next]) ($($last_name)* [<$($prefix)* _ $next:snake>]) ($($prefix)* x ) $($($rest)=>*),*);
         }
     };
     // the last type of a chain after already being in the middle and the end
-    (carrot $name:ty, $from:ty, $to:ty, $choice:ident, $mode:ident, ($($bool:tt)*) ($index:expr) ($($solo_index_past:tt)*) ($($chain_index_past:tt)*) ($($solo:tt)*) ($($solo_name:tt)*) ($($first:tt)*) ($($first_name:tt)*) ($($mid:tt)*) ($($mid_name:tt)*) ($($last:tt)*) ($($last_name:tt)*) ($($prefix:tt)*) ($($past:tt)*) ($($past_name:tt)*) $next:ty) => {
+    (carrot $name:ty, $from:ty, $to:ty, $choice:ident, $mode:ident, ($index:expr) ($($solo_index_past:tt)*) ($($chain_index_past:tt)*) ($($solo:tt)*) ($($solo_name:tt)*) ($($first:tt)*) ($($first_name:tt)*) ($($mid:tt)*) ($($mid_name:tt)*) ($($last:tt)*) ($($last_name:tt)*) ($($prefix:tt)*) ($($past:tt)*) ($($past_name:tt)*) $next:ty) => {
         $crate::paste! {
-            chain!(end $name, $from, $to, $choice, $mode, ($($bool)*) ($index) ($($solo_index_past)*) ($($chain_index_past)*) ($($solo)*) ($($solo_name)*) ($($first)*) ($($first_name)*) ($($mid)* [$($past)*]) ($($mid_name)* [$($past_name)*]) ($($last)* [$next]) ($($last_name)* [<$($prefix)* _ $next:snake>]));
+            chain!(end $name, $from, $to, $choice, $mode, ($index) ($($solo_index_past)*) ($($chain_index_past)*) ($($solo)*) ($($solo_name)*) ($($first)*) ($($first_name)*) ($($mid)* [$($past)*]) ($($mid_name)* [$($past_name)*]) ($($last)* [$next]) ($($last_name)* [<$($prefix)* _ $next:snake>]));
         }
     };
     (end
@@ -154,7 +594,6 @@ macro_rules! chain {
         $to:ty,
         $choice:ident,
         $mode:ident,
-        ($([$bool:expr])*)
         ($count:expr)
         ($([$solo_index:expr])*)
         ($([$chain_index:expr])*)
@@ -182,6 +621,10 @@ macro_rules! chain {
                 )*
                 // necessary for processing one chainlink
                 next_process_field_index: std::sync::Arc<$crate::Mutex<usize>>,
+                // necessary for pausing between the ChainLinks of each sequence
+                debugger: std::sync::Mutex<Option<std::sync::Arc<$crate::debugger::Debugger>>>,
+                // necessary for background processing and random choices
+                scheduler: std::sync::Mutex<std::sync::Arc<dyn $crate::schedule::Scheduler>>,
                 // each internal ChainLink
                 $(
                     $first_name: std::sync::Arc<$first>,
@@ -198,20 +641,20 @@ macro_rules! chain {
             #[allow(dead_code)]
             pub struct [<$name Initializer>] {
                 $(
-                    pub $first_name: std::sync::Arc<$crate::RwLock<[<$first Initializer>]>>,
+                    pub $first_name: std::sync::Arc<$crate::RwLock<<$first as $crate::framework::Initializable>::TInitializer>>,
                     $(
-                        pub $mid_name: std::sync::Arc<$crate::RwLock<[<$mid Initializer>]>>,
+                        pub $mid_name: std::sync::Arc<$crate::RwLock<<$mid as $crate::framework::Initializable>::TInitializer>>,
                     )*
-                    pub $last_name: std::sync::Arc<$crate::RwLock<[<$last Initializer>]>>,
+                    pub $last_name: std::sync::Arc<$crate::RwLock<<$last as $crate::framework::Initializable>::TInitializer>>,
                 )*
                 $(
-                    $solo_name: std::sync::Arc<$crate::RwLock<[<$solo Initializer>]>>,
+                    $solo_name: std::sync::Arc<$crate::RwLock<<$solo as $crate::framework::Initializable>::TInitializer>>,
                 )*
             }
 
             #[allow(dead_code)]
             impl [<$name Initializer>] {
-                pub fn new($($first_name: [<$first Initializer>], $($mid_name: [<$mid Initializer>],)* $last_name: [<$last Initializer>],)* $($solo_name: [<$solo Initializer>],)*) -> Self {
+                pub fn new($($first_name: <$first as $crate::framework::Initializable>::TInitializer, $($mid_name: <$mid as $crate::framework::Initializable>::TInitializer,)* $last_name: <$last as $crate::framework::Initializable>::TInitializer,)* $($solo_name: <$solo as $crate::framework::Initializable>::TInitializer,)*) -> Self {
                     [<$name Initializer>] {
                         $(
                             $first_name: std::sync::Arc::new($crate::RwLock::new($first_name)),
@@ -239,15 +682,17 @@ macro_rules! chain {
                             [<is_running_ $solo_name>]: std::sync::Arc::new($crate::Mutex::new(false)),
                         )*
                         next_process_field_index: std::sync::Arc::new($crate::Mutex::new(0)),
+                        debugger: std::sync::Mutex::new(None),
+                        scheduler: std::sync::Mutex::new(std::sync::Arc::new($crate::schedule::ThreadScheduler)),
                         $(
-                            $first_name: std::sync::Arc::new($first::new(initializer.read().await.$first_name.clone()).await),
+                            $first_name: std::sync::Arc::new(<$first as $crate::framework::Initializable>::initialize(initializer.read().await.$first_name.clone()).await),
                             $(
-                                $mid_name: std::sync::Arc::new($mid::new(initializer.read().await.$mid_name.clone()).await),
+                                $mid_name: std::sync::Arc::new(<$mid as $crate::framework::Initializable>::initialize(initializer.read().await.$mid_name.clone()).await),
                             )*
-                            $last_name: std::sync::Arc::new($last::new(initializer.read().await.$last_name.clone()).await),
+                            $last_name: std::sync::Arc::new(<$last as $crate::framework::Initializable>::initialize(initializer.read().await.$last_name.clone()).await),
                         )*
                         $(
-                            $solo_name: std::sync::Arc::new($solo::new(initializer.read().await.$solo_name.clone()).await),
+                            $solo_name: std::sync::Arc::new(<$solo as $crate::framework::Initializable>::initialize(initializer.read().await.$solo_name.clone()).await),
                         )*
                     }
                 }
@@ -261,50 +706,105 @@ macro_rules! chain {
                             [<is_running_ $solo_name>]: std::sync::Arc::new($crate::Mutex::new(false)),
                         )*
                         next_process_field_index: std::sync::Arc::new($crate::Mutex::new(0)),
+                        debugger: std::sync::Mutex::new(None),
+                        scheduler: std::sync::Mutex::new(std::sync::Arc::new($crate::schedule::ThreadScheduler)),
                         $(
-                            $first_name: std::sync::Arc::new($first::new(initializer.$first_name.clone()).await),
+                            $first_name: std::sync::Arc::new(<$first as $crate::framework::Initializable>::initialize(initializer.$first_name.clone()).await),
                             $(
-                                $mid_name: std::sync::Arc::new($mid::new(initializer.$mid_name.clone()).await),
+                                $mid_name: std::sync::Arc::new(<$mid as $crate::framework::Initializable>::initialize(initializer.$mid_name.clone()).await),
                             )*
-                            $last_name: std::sync::Arc::new($last::new(initializer.$last_name.clone()).await),
+                            $last_name: std::sync::Arc::new(<$last as $crate::framework::Initializable>::initialize(initializer.$last_name.clone()).await),
                         )*
                         $(
-                            $solo_name: std::sync::Arc::new($solo::new(initializer.$solo_name.clone()).await),
+                            $solo_name: std::sync::Arc::new(<$solo as $crate::framework::Initializable>::initialize(initializer.$solo_name.clone()).await),
                         )*
                     }
                 }
 
+                pub fn attach_debugger(&self, debugger: std::sync::Arc<$crate::debugger::Debugger>) {
+                    self.debugger.lock().unwrap().replace(debugger);
+                }
+                pub fn detach_debugger(&self) {
+                    self.debugger.lock().unwrap().take();
+                }
+                fn scheduler(&self) -> std::sync::Arc<dyn $crate::schedule::Scheduler> {
+                    self.scheduler.lock().unwrap().clone()
+                }
+
                 // useful functions for processing chainlinks
                 $(
-                    async fn [<process_ $first_name>](&self) -> bool {
+                    async fn [<process_ $first_name>](&self) -> $crate::framework::ProcessOutcome {
+                        let debugger = self.debugger.lock().unwrap().clone();
+                        Self::[<process_sequence_ $first_name>](debugger, self.$first_name.as_ref(), $(self.$mid_name.as_ref(),)* self.$last_name.as_ref()).await
+                    }
+                    // pulls input through the sequence until the last ChainLink produces or none of them do
+                    async fn [<process_sequence_ $first_name>](debugger: Option<std::sync::Arc<$crate::debugger::Debugger>>, $first_name: &$first, $($mid_name: &$mid,)* $last_name: &$last) -> $crate::framework::ProcessOutcome {
+                        let mut outcome = $crate::framework::ProcessOutcome::idle();
                         let mut is_at_least_one_processed = true;
                         let mut is_last_processed = false;
                         while is_at_least_one_processed && !is_last_processed {
-                            is_at_least_one_processed = $crate::framework::ChainLink::process(self.$first_name.as_ref()).await;
-                            let next_input = $crate::framework::ChainLink::try_pop(self.$first_name.as_ref()).await;
+                            let first_outcome = $crate::framework::ChainLink::process($first_name).await;
+                            is_at_least_one_processed = first_outcome.is_produced();
+                            outcome.consumed_count += first_outcome.consumed_count;
+                            outcome.merge_state(&first_outcome);
+                            // each ChainLink of a sequence is the only consumer of the previous output, so items are passed along as they are, along with their headers
+                            let next_input = $crate::framework::ChainLink::try_pop_item_with_headers($first_name).await;
+                            // a ChainLink may produce several outputs at once, so handing one along also keeps the sequence flowing
+                            is_at_least_one_processed |= next_input.is_some();
+                            let stage_index: usize = 0;
+                            let next_input = match (debugger.as_ref(), next_input) {
+                                (Some(debugger), Some((next_input, headers))) => {
+                                    // the debugger holds onto the value while the next ChainLink reads it
+                                    let next_input = next_input.into_shared();
+                                    debugger.on_stage($crate::debugger::StageEvent::new(stringify!($name), stage_index, stringify!($first), next_input.clone())).await;
+                                    Some(($crate::item::Item::Shared(next_input), headers))
+                                },
+                                (_, next_input) => next_input
+                            };
                             $(
-                                if let Some(next_input) = next_input {
-                                    $crate::framework::ChainLink::push(self.$mid_name.as_ref(), next_input).await;
+                                if let Some((next_input, headers)) = next_input {
+                                    $crate::framework::ChainLink::push_item_with_headers($mid_name, next_input, headers).await;
                                 }
-                                is_at_least_one_processed |= $crate::framework::ChainLink::process(self.$mid_name.as_ref()).await;
-                                let next_input = $crate::framework::ChainLink::try_pop(self.$mid_name.as_ref()).await;
+                                let mid_outcome = $crate::framework::ChainLink::process($mid_name).await;
+                                is_at_least_one_processed |= mid_outcome.is_produced();
+                                outcome.merge_state(&mid_outcome);
+                                let next_input = $crate::framework::ChainLink::try_pop_item_with_headers($mid_name).await;
+                                is_at_least_one_processed |= next_input.is_some();
+                                let stage_index = stage_index + 1;
+                                let next_input = match (debugger.as_ref(), next_input) {
+                                    (Some(debugger), Some((next_input, headers))) => {
+                                        let next_input = next_input.into_shared();
+                                        debugger.on_stage($crate::debugger::StageEvent::new(stringify!($name), stage_index, stringify!($mid), next_input.clone())).await;
+                                        Some(($crate::item::Item::Shared(next_input), headers))
+                                    },
+                                    (_, next_input) => next_input
+                                };
                             )*
-                            if let Some(next_input) = next_input {
-                                $crate::framework::ChainLink::push(self.$last_name.as_ref(), next_input).await;
+                            if let Some((next_input, headers)) = next_input {
+                                $crate::framework::ChainLink::push_item_with_headers($last_name, next_input, headers).await;
                             }
-                            is_last_processed = $crate::framework::ChainLink::process(self.$last_name.as_ref()).await;
+                            let last_outcome = $crate::framework::ChainLink::process($last_name).await;
+                            is_last_processed = last_outcome.is_produced();
+                            outcome.produced_count += last_outcome.produced_count;
+                            outcome.merge_state(&last_outcome);
                         }
-                        return is_last_processed;
+                        return outcome;
                     }
                 )*
 
                 // each of these functions represents all of the possible permutations for processing chains
-                async fn process_all_join(&self) -> bool {
-                    let bool_tuple = $crate::join!($($crate::framework::ChainLink::process(self.$solo_name.as_ref()),)*$(self.[<process_ $first_name>]()),*);
-                    let false_tuple = ($($bool,)*);
-                    return bool_tuple != false_tuple;
+                async fn process_all_join(&self) -> $crate::framework::ProcessOutcome {
+                    let ($($solo_name,)* $($first_name,)*) = $crate::join!($($crate::framework::ChainLink::process(self.$solo_name.as_ref()),)*$(self.[<process_ $first_name>]()),*);
+                    let mut outcome = $crate::framework::ProcessOutcome::idle();
+                    $(
+                        outcome.merge_branch($solo_index, $solo_name);
+                    )*
+                    $(
+                        outcome.merge_branch($chain_index, $first_name);
+                    )*
+                    return outcome;
                 }
-                async fn process_all_free(&self) -> bool {
+                async fn process_all_free(&self) -> $crate::framework::ProcessOutcome {
                     $(
                         {
                             let $first_name = self.$first_name.clone();
@@ -312,52 +812,24 @@ macro_rules! chain {
                                 let $mid_name = self.$mid_name.clone();
                             )*
                             let $last_name = self.$last_name.clone();
-                            std::thread::spawn(move || {
-                                let tokio_runtime = $crate::Builder::new_current_thread()
-                                    .enable_time()
-                                    .build()
-                                    .unwrap();
-
-                                tokio_runtime.block_on(async {
-                                    let mut is_at_least_one_processed = true;
-                                    let mut is_last_processed = false;
-                                    while is_at_least_one_processed && !is_last_processed {
-                                        is_at_least_one_processed = $crate::framework::ChainLink::process($first_name.as_ref()).await;
-                                        let next_input = $crate::framework::ChainLink::try_pop($first_name.as_ref()).await;
-                                        $(
-                                            if let Some(next_input) = next_input {
-                                                $crate::framework::ChainLink::push($mid_name.as_ref(), next_input).await;
-                                            }
-                                            is_at_least_one_processed |= $crate::framework::ChainLink::process($mid_name.as_ref()).await;
-                                            let next_input = $crate::framework::ChainLink::try_pop($mid_name.as_ref()).await;
-                                        )*
-                                        if let Some(next_input) = next_input {
-                                            $crate::framework::ChainLink::push($last_name.as_ref(), next_input).await;
-                                        }
-                                        is_last_processed = $crate::framework::ChainLink::process($last_name.as_ref()).await;
-                                    }
-                                });
-                            });
+                            let debugger = self.debugger.lock().unwrap().clone();
+                            self.scheduler().spawn(Box::pin(async move {
+                                Self::[<process_sequence_ $first_name>](debugger, $first_name.as_ref(), $($mid_name.as_ref(),)* $last_name.as_ref()).await;
+                            }));
                         }
                     )*
                     $(
                         {
                             let $solo_name = self.$solo_name.clone();
-                            std::thread::spawn(move || {
-                                let tokio_runtime = $crate::Builder::new_current_thread()
-                                    .enable_time()
-                                    .build()
-                                    .unwrap();
-
-                                tokio_runtime.block_on(async {
-                                    $crate::framework::ChainLink::process($solo_name.as_ref()).await;
-                                });
-                            });
+                            self.scheduler().spawn(Box::pin(async move {
+                                $crate::framework::ChainLink::process($solo_name.as_ref()).await;
+                            }));
                         }
                     )*
-                    return false;
+                    return $crate::framework::ProcessOutcome::deferred();
                 }
-                async fn process_all_unique(&self) -> bool {
+                async fn process_all_unique(&self) -> $crate::framework::ProcessOutcome {
+                    let mut outcome = $crate::framework::ProcessOutcome::idle();
                     $(
                         {
                             let mut [<locked_is_running_ $first_name>] = self.[<is_running_ $first_name>].lock().await;
@@ -368,34 +840,16 @@ macro_rules! chain {
                                     let $mid_name = self.$mid_name.clone();
                                 )*
                                 let $last_name = self.$last_name.clone();
+                                let debugger = self.debugger.lock().unwrap().clone();
                                 let [<is_running_ $first_name>] = self.[<is_running_ $first_name>].clone();
-                                std::thread::spawn(move || {
-                                    let tokio_runtime = $crate::Builder::new_current_thread()
-                                        .enable_time()
-                                        .build()
-                                        .unwrap();
-
-                                    tokio_runtime.block_on(async {
-                                        let mut is_at_least_one_processed = true;
-                                        let mut is_last_processed = false;
-                                        while is_at_least_one_processed && !is_last_processed {
-                                            is_at_least_one_processed = $crate::framework::ChainLink::process($first_name.as_ref()).await;
-                                            let next_input = $crate::framework::ChainLink::try_pop($first_name.as_ref()).await;
-                                            $(
-                                                if let Some(next_input) = next_input {
-                                                    $crate::framework::ChainLink::push($mid_name.as_ref(), next_input).await;
-                                                }
-                                                is_at_least_one_processed |= $crate::framework::ChainLink::process($mid_name.as_ref()).await;
-                                                let next_input = $crate::framework::ChainLink::try_pop($mid_name.as_ref()).await;
-                                            )*
-                                            if let Some(next_input) = next_input {
-                                                $crate::framework::ChainLink::push($last_name.as_ref(), next_input).await;
-                                            }
-                                            is_last_processed = $crate::framework::ChainLink::process($last_name.as_ref()).await;
-                                        }
-                                        *[<is_running_ $first_name>].lock().await = false;
-                                    });
-                                });
+                                self.scheduler().spawn(Box::pin(async move {
+                                    Self::[<process_sequence_ $first_name>](debugger, $first_name.as_ref(), $($mid_name.as_ref(),)* $last_name.as_ref()).await;
+                                    *[<is_running_ $first_name>].lock().await = false;
+                                }));
+                                outcome.is_deferred = true;
+                            }
+                            else {
+                                outcome.is_busy = true;
                             }
                         }
                     )*
@@ -406,23 +860,20 @@ macro_rules! chain {
                                 *[<locked_is_running_ $solo_name>] = true;
                                 let $solo_name = self.$solo_name.clone();
                                 let [<is_running_ $solo_name>] = self.[<is_running_ $solo_name>].clone();
-                                std::thread::spawn(move || {
-                                    let tokio_runtime = $crate::Builder::new_current_thread()
-                                        .enable_time()
-                                        .build()
-                                        .unwrap();
-
-                                    tokio_runtime.block_on(async {
-                                        $crate::framework::ChainLink::process($solo_name.as_ref()).await;
-                                        *[<is_running_ $solo_name>].lock().await = false;
-                                    });
-                                });
+                                self.scheduler().spawn(Box::pin(async move {
+                                    $crate::framework::ChainLink::process($solo_name.as_ref()).await;
+                                    *[<is_running_ $solo_name>].lock().await = false;
+                                }));
+                                outcome.is_deferred = true;
+                            }
+                            else {
+                                outcome.is_busy = true;
                             }
                         }
                     )*
-                    return false;
+                    return outcome;
                 }
-                async fn process_one_join(&self) -> bool {
+                async fn process_one_join(&self) -> $crate::framework::ProcessOutcome {
                     // get the next field to process
                     let next_process_field_index;
                     {
@@ -454,9 +905,11 @@ macro_rules! chain {
                     else {
                         panic!("Index out of bounds: next_process_field_index");
                     }
-                    return output;
+                    let mut outcome = $crate::framework::ProcessOutcome::idle();
+                    outcome.merge_branch(next_process_field_index, output);
+                    return outcome;
                 }
-                async fn process_one_free(&self) -> bool {
+                async fn process_one_free(&self) -> $crate::framework::ProcessOutcome {
                     // get the next field to process
                     let next_process_field_index;
                     {
@@ -481,55 +934,26 @@ macro_rules! chain {
                                 let $mid_name = self.$mid_name.clone();
                             )*
                             let $last_name = self.$last_name.clone();
-                            std::thread::spawn(move || {
-                                let tokio_runtime = $crate::Builder::new_current_thread()
-                                    .enable_time()
-                                    .build()
-                                    .unwrap();
-
-                                tokio_runtime.block_on(async {
-                                    let mut is_at_least_one_processed = true;
-                                    let mut is_last_processed = false;
-                                    while is_at_least_one_processed && !is_last_processed {
-                                        is_at_least_one_processed = $crate::framework::ChainLink::process($first_name.as_ref()).await;
-                                        let next_input = $crate::framework::ChainLink::try_pop($first_name.as_ref()).await;
-                                        $(
-                                            if let Some(next_input) = next_input {
-                                                $crate::framework::ChainLink::push($mid_name.as_ref(), next_input).await;
-                                            }
-                                            is_at_least_one_processed |= $crate::framework::ChainLink::process($mid_name.as_ref()).await;
-                                            let next_input = $crate::framework::ChainLink::try_pop($mid_name.as_ref()).await;
-                                        )*
-                                        if let Some(next_input) = next_input {
-                                            $crate::framework::ChainLink::push($last_name.as_ref(), next_input).await;
-                                        }
-                                        is_last_processed = $crate::framework::ChainLink::process($last_name.as_ref()).await;
-                                    }
-                                });
-                            });
+                            let debugger = self.debugger.lock().unwrap().clone();
+                            self.scheduler().spawn(Box::pin(async move {
+                                Self::[<process_sequence_ $first_name>](debugger, $first_name.as_ref(), $($mid_name.as_ref(),)* $last_name.as_ref()).await;
+                            }));
                         }
                     )*
                     $(
                         else if next_process_field_index == ($solo_index) {
                             let $solo_name = self.$solo_name.clone();
-                            std::thread::spawn(move || {
-                                let tokio_runtime = $crate::Builder::new_current_thread()
-                                    .enable_time()
-                                    .build()
-                                    .unwrap();
-
-                                tokio_runtime.block_on(async {
-                                    $crate::framework::ChainLink::process($solo_name.as_ref()).await;
-                                });
-                            });
+                            self.scheduler().spawn(Box::pin(async move {
+                                $crate::framework::ChainLink::process($solo_name.as_ref()).await;
+                            }));
                         }
                     )*
                     else {
                         panic!("Index out of bounds: next_process_field_index");
                     }
-                    return false;
+                    return $crate::framework::ProcessOutcome::deferred();
                 }
-                async fn process_one_unique(&self) -> bool {
+                async fn process_one_unique(&self) -> $crate::framework::ProcessOutcome {
                     // iterate over all internal chainlinks, potentially
                     for _ in 0..($count) {
                         
@@ -560,37 +984,15 @@ macro_rules! chain {
                                         let $mid_name = self.$mid_name.clone();
                                     )*
                                     let $last_name = self.$last_name.clone();
+                                    let debugger = self.debugger.lock().unwrap().clone();
                                     let [<is_running_ $first_name>] = self.[<is_running_ $first_name>].clone();
-                                    std::thread::spawn(move || {
-                                        let tokio_runtime = $crate::Builder::new_current_thread()
-                                            .enable_time()
-                                            .build()
-                                            .unwrap();
-
-                                        tokio_runtime.block_on(async {
-                                            let mut is_at_least_one_processed = true;
-                                            let mut is_last_processed = false;
-                                            while is_at_least_one_processed && !is_last_processed {
-                                                is_at_least_one_processed = $crate::framework::ChainLink::process($first_name.as_ref()).await;
-                                                let next_input = $crate::framework::ChainLink::try_pop($first_name.as_ref()).await;
-                                                $(
-                                                    if let Some(next_input) = next_input {
-                                                        $crate::framework::ChainLink::push($mid_name.as_ref(), next_input).await;
-                                                    }
-                                                    is_at_least_one_processed |= $crate::framework::ChainLink::process($mid_name.as_ref()).await;
-                                                    let next_input = $crate::framework::ChainLink::try_pop($mid_name.as_ref()).await;
-                                                )*
-                                                if let Some(next_input) = next_input {
-                                                    $crate::framework::ChainLink::push($last_name.as_ref(), next_input).await;
-                                                }
-                                                is_last_processed = $crate::framework::ChainLink::process($last_name.as_ref()).await;
-                                            }
-                                            *[<is_running_ $first_name>].lock().await = false;
-                                        });
-                                    });
+                                    self.scheduler().spawn(Box::pin(async move {
+                                        Self::[<process_sequence_ $first_name>](debugger, $first_name.as_ref(), $($mid_name.as_ref(),)* $last_name.as_ref()).await;
+                                        *[<is_running_ $first_name>].lock().await = false;
+                                    }));
 
                                     // only one thread is started
-                                    return false;
+                                    return $crate::framework::ProcessOutcome::deferred();
                                 }
                             }
                         )*
@@ -601,20 +1003,13 @@ macro_rules! chain {
                                     *[<locked_is_running_ $solo_name>] = true;
                                     let $solo_name = self.$solo_name.clone();
                                     let [<is_running_ $solo_name>] = self.[<is_running_ $solo_name>].clone();
-                                    std::thread::spawn(move || {
-                                        let tokio_runtime = $crate::Builder::new_current_thread()
-                                            .enable_time()
-                                            .build()
-                                            .unwrap();
-
-                                        tokio_runtime.block_on(async {
-                                            $crate::framework::ChainLink::process($solo_name.as_ref()).await;
-                                            *[<is_running_ $solo_name>].lock().await = false;
-                                        });
-                                    });
+                                    self.scheduler().spawn(Box::pin(async move {
+                                        $crate::framework::ChainLink::process($solo_name.as_ref()).await;
+                                        *[<is_running_ $solo_name>].lock().await = false;
+                                    }));
 
                                     // only one thread is started
-                                    return false;
+                                    return $crate::framework::ProcessOutcome::deferred();
                                 }
                             }
                         )*
@@ -624,17 +1019,11 @@ macro_rules! chain {
                     }
 
                     // no threads were started because they were all already running
-                    return false;
+                    return $crate::framework::ProcessOutcome::busy();
                 }
-                async fn process_random_join(&self) -> bool {
-                    let next_process_field_index;
-                    {
-                        use $crate::Rng;
-
-                        // get the next field to process
-                        let mut rng = $crate::thread_rng();
-                        next_process_field_index = rng.gen_range(0..($count));
-                    }
+                async fn process_random_join(&self) -> $crate::framework::ProcessOutcome {
+                    // get the next field to process
+                    let next_process_field_index = self.scheduler().choose_index($count);
 
                     // get the output for the current field index
                     let output;
@@ -654,17 +1043,13 @@ macro_rules! chain {
                     else {
                         panic!("Index out of bounds: next_process_field_index");
                     }
-                    return output;
+                    let mut outcome = $crate::framework::ProcessOutcome::idle();
+                    outcome.merge_branch(next_process_field_index, output);
+                    return outcome;
                 }
-                async fn process_random_free(&self) -> bool {
-                    let next_process_field_index;
-                    {
-                        use $crate::Rng;
-
-                        // get the next field to process
-                        let mut rng = $crate::thread_rng();
-                        next_process_field_index = rng.gen_range(0..($count));
-                    }
+                async fn process_random_free(&self) -> $crate::framework::ProcessOutcome {
+                    // get the next field to process
+                    let next_process_field_index = self.scheduler().choose_index($count);
 
                     // get the output for the current field index
                     if false {
@@ -677,63 +1062,29 @@ macro_rules! chain {
                                 let $mid_name = self.$mid_name.clone();
                             )*
                             let $last_name = self.$last_name.clone();
-                            std::thread::spawn(move || {
-                                let tokio_runtime = $crate::Builder::new_current_thread()
-                                    .enable_time()
-                                    .build()
-                                    .unwrap();
-
-                                tokio_runtime.block_on(async {
-                                    let mut is_at_least_one_processed = true;
-                                    let mut is_last_processed = false;
-                                    while is_at_least_one_processed && !is_last_processed {
-                                        is_at_least_one_processed = $crate::framework::ChainLink::process($first_name.as_ref()).await;
-                                        let next_input = $crate::framework::ChainLink::try_pop($first_name.as_ref()).await;
-                                        $(
-                                            if let Some(next_input) = next_input {
-                                                $crate::framework::ChainLink::push($mid_name.as_ref(), next_input).await;
-                                            }
-                                            is_at_least_one_processed |= $crate::framework::ChainLink::process($mid_name.as_ref()).await;
-                                            let next_input = $crate::framework::ChainLink::try_pop($mid_name.as_ref()).await;
-                                        )*
-                                        if let Some(next_input) = next_input {
-                                            $crate::framework::ChainLink::push($last_name.as_ref(), next_input).await;
-                                        }
-                                        is_last_processed = $crate::framework::ChainLink::process($last_name.as_ref()).await;
-                                    }
-                                });
-                            });
+                            let debugger = self.debugger.lock().unwrap().clone();
+                            self.scheduler().spawn(Box::pin(async move {
+                                Self::[<process_sequence_ $first_name>](debugger, $first_name.as_ref(), $($mid_name.as_ref(),)* $last_name.as_ref()).await;
+                            }));
                         }
                     )*
                     $(
                         else if next_process_field_index == ($solo_index) {
                             let $solo_name = self.$solo_name.clone();
-                            std::thread::spawn(move || {
-                                let tokio_runtime = $crate::Builder::new_current_thread()
-                                    .enable_time()
-                                    .build()
-                                    .unwrap();
-
-                                tokio_runtime.block_on(async {
-                                    $crate::framework::ChainLink::process($solo_name.as_ref()).await;
-                                });
-                            });
+                            self.scheduler().spawn(Box::pin(async move {
+                                $crate::framework::ChainLink::process($solo_name.as_ref()).await;
+                            }));
                         }
                     )*
                     else {
                         panic!("Index out of bounds: next_process_field_index");
                     }
-                    return false;
+                    return $crate::framework::ProcessOutcome::deferred();
                 }
-                async fn process_random_unique(&self) -> bool {
+                async fn process_random_unique(&self) -> $crate::framework::ProcessOutcome {
                     
                     // create a mapping of indexes to attempt before exhausting all indexes
-                    let mut mapped_next_process_field_index: Vec<usize> = (0..($count)).collect();
-                    {
-                        use $crate::SliceRandom;
-
-                        mapped_next_process_field_index.shuffle(&mut $crate::thread_rng());
-                    }
+                    let mapped_next_process_field_index = self.scheduler().shuffled_indexes($count);
 
                     // cycle over all field indexes until one is found
                     for _ in 0..($count) {
@@ -767,37 +1118,15 @@ macro_rules! chain {
                                         let $mid_name = self.$mid_name.clone();
                                     )*
                                     let $last_name = self.$last_name.clone();
+                                    let debugger = self.debugger.lock().unwrap().clone();
                                     let [<is_running_ $first_name>] = self.[<is_running_ $first_name>].clone();
-                                    std::thread::spawn(move || {
-                                        let tokio_runtime = $crate::Builder::new_current_thread()
-                                            .enable_time()
-                                            .build()
-                                            .unwrap();
-
-                                        tokio_runtime.block_on(async {
-                                            let mut is_at_least_one_processed = true;
-                                            let mut is_last_processed = false;
-                                            while is_at_least_one_processed && !is_last_processed {
-                                                is_at_least_one_processed = $crate::framework::ChainLink::process($first_name.as_ref()).await;
-                                                let next_input = $crate::framework::ChainLink::try_pop($first_name.as_ref()).await;
-                                                $(
-                                                    if let Some(next_input) = next_input {
-                                                        $crate::framework::ChainLink::push($mid_name.as_ref(), next_input).await;
-                                                    }
-                                                    is_at_least_one_processed |= $crate::framework::ChainLink::process($mid_name.as_ref()).await;
-                                                    let next_input = $crate::framework::ChainLink::try_pop($mid_name.as_ref()).await;
-                                                )*
-                                                if let Some(next_input) = next_input {
-                                                    $crate::framework::ChainLink::push($last_name.as_ref(), next_input).await;
-                                                }
-                                                is_last_processed = $crate::framework::ChainLink::process($last_name.as_ref()).await;
-                                            }
-                                            *[<is_running_ $first_name>].lock().await = false;
-                                        });
-                                    });
+                                    self.scheduler().spawn(Box::pin(async move {
+                                        Self::[<process_sequence_ $first_name>](debugger, $first_name.as_ref(), $($mid_name.as_ref(),)* $last_name.as_ref()).await;
+                                        *[<is_running_ $first_name>].lock().await = false;
+                                    }));
 
                                     // only one thread is started
-                                    return false;
+                                    return $crate::framework::ProcessOutcome::deferred();
                                 }
                             }
                         )*
@@ -808,20 +1137,13 @@ macro_rules! chain {
                                     *[<locked_is_running_ $solo_name>] = true;
                                     let $solo_name = self.$solo_name.clone();
                                     let [<is_running_ $solo_name>] = self.[<is_running_ $solo_name>].clone();
-                                    std::thread::spawn(move || {
-                                        let tokio_runtime = $crate::Builder::new_current_thread()
-                                            .enable_time()
-                                            .build()
-                                            .unwrap();
-
-                                        tokio_runtime.block_on(async {
-                                            $crate::framework::ChainLink::process($solo_name.as_ref()).await;
-                                            *[<is_running_ $solo_name>].lock().await = false;
-                                        });
-                                    });
+                                    self.scheduler().spawn(Box::pin(async move {
+                                        $crate::framework::ChainLink::process($solo_name.as_ref()).await;
+                                        *[<is_running_ $solo_name>].lock().await = false;
+                                    }));
 
                                     // only one thread is started
-                                    return false;
+                                    return $crate::framework::ProcessOutcome::deferred();
                                 }
                             }
                         )*
@@ -831,7 +1153,16 @@ macro_rules! chain {
                     }
 
                     // no threads were started because they were all already running
-                    return false;
+                    return $crate::framework::ProcessOutcome::busy();
+                }
+            }
+
+            #[$crate::async_trait]
+            impl $crate::framework::Initializable for $name {
+                type TInitializer = [<$name Initializer>];
+
+                async fn initialize(initializer: std::sync::Arc<$crate::RwLock<[<$name Initializer>]>>) -> Self {
+                    $name::new(initializer).await
                 }
             }
 
@@ -851,7 +1182,33 @@ macro_rules! chain {
                     $crate::join_all(push_futures).await;
                 }
                 async fn push_raw(&self, input: $from) -> () {
-                    self.push(std::sync::Arc::new($crate::RwLock::new(input))).await
+                    self.push_item($crate::item::Item::Owned(input)).await
+                }
+                async fn push_item(&self, input: $crate::item::Item<$from>) -> () {
+                    self.push_item_with_headers(input, $crate::envelope::HeaderMap::new()).await
+                }
+                async fn push_item_with_headers(&self, input: $crate::item::Item<$from>, headers: $crate::envelope::HeaderMap) -> () {
+                    if ($count) == 1 {
+                        // the only parallel ChainLink is the only consumer, so the item does not need to be shared
+                        let mut input = Some(input);
+                        $(
+                            $crate::framework::ChainLink::push_item_with_headers(self.$first_name.as_ref(), input.take().expect("The item should only be pushed once."), headers.clone()).await;
+                        )*
+                        $(
+                            $crate::framework::ChainLink::push_item_with_headers(self.$solo_name.as_ref(), input.take().expect("The item should only be pushed once."), headers.clone()).await;
+                        )*
+                    }
+                    else {
+                        let input = input.into_shared();
+                        let mut push_futures = vec![];
+                        $(
+                            push_futures.push($crate::framework::ChainLink::push_item_with_headers(self.$first_name.as_ref(), $crate::item::Item::Shared(input.clone()), headers.clone()));
+                        )*
+                        $(
+                            push_futures.push($crate::framework::ChainLink::push_item_with_headers(self.$solo_name.as_ref(), $crate::item::Item::Shared(input.clone()), headers.clone()));
+                        )*
+                        $crate::join_all(push_futures).await;
+                    }
                 }
                 async fn push_if_empty(&self, input: std::sync::Arc<$crate::RwLock<$from>>) -> () {
                     let mut futures = vec![];
@@ -866,7 +1223,26 @@ macro_rules! chain {
                 async fn push_raw_if_empty(&self, input: $from) -> () {
                     self.push_if_empty(std::sync::Arc::new($crate::RwLock::new(input))).await
                 }
+                async fn push_replace(&self, input: std::sync::Arc<$crate::RwLock<$from>>) -> () {
+                    let mut futures = vec![];
+                    $(
+                        futures.push(self.$first_name.push_replace(input.clone()));
+                    )*
+                    $(
+                        futures.push(self.$solo_name.push_replace(input.clone()));
+                    )*
+                    $crate::join_all(futures).await;
+                }
+                async fn push_raw_replace(&self, input: $from) -> () {
+                    self.push_replace(std::sync::Arc::new($crate::RwLock::new(input))).await
+                }
                 async fn try_pop(&self) -> Option<std::sync::Arc<$crate::RwLock<$to>>> {
+                    self.try_pop_item().await.map($crate::item::Item::into_shared)
+                }
+                async fn try_pop_item(&self) -> Option<$crate::item::Item<$to>> {
+                    self.try_pop_item_with_headers().await.map(|(output, _)| output)
+                }
+                async fn try_pop_item_with_headers(&self) -> Option<($crate::item::Item<$to>, $crate::envelope::HeaderMap)> {
                     
                     let mut locked_next_try_pop_index = self.next_try_pop_index.lock().await;
                     let mut try_pop_attempt_count: usize = 0;
@@ -888,12 +1264,12 @@ macro_rules! chain {
                         }
                         $(
                             else if next_try_pop_index == ($solo_index) {
-                                output = self.$solo_name.try_pop().await;
+                                output = $crate::framework::ChainLink::try_pop_item_with_headers(self.$solo_name.as_ref()).await;
                             }
                         )*
                         $(
                             else if next_try_pop_index == ($chain_index) {
-                                output = self.$last_name.try_pop().await;
+                                output = $crate::framework::ChainLink::try_pop_item_with_headers(self.$last_name.as_ref()).await;
                             }
                         )*
                         else {
@@ -910,7 +1286,84 @@ macro_rules! chain {
                     // if the parallel set of ChainLinks have been exhausted
                     return None;
                 }
-                async fn process(&self) -> bool {
+                fn set_scheduler(&self, scheduler: std::sync::Arc<dyn $crate::schedule::Scheduler>) {
+                    $(
+                        $crate::framework::ChainLink::set_scheduler(self.$first_name.as_ref(), scheduler.clone());
+                        $(
+                            $crate::framework::ChainLink::set_scheduler(self.$mid_name.as_ref(), scheduler.clone());
+                        )*
+                        $crate::framework::ChainLink::set_scheduler(self.$last_name.as_ref(), scheduler.clone());
+                    )*
+                    $(
+                        $crate::framework::ChainLink::set_scheduler(self.$solo_name.as_ref(), scheduler.clone());
+                    )*
+                    *self.scheduler.lock().unwrap() = scheduler;
+                }
+                fn set_clock(&self, clock: $crate::clock::Clock) {
+                    $(
+                        $crate::framework::ChainLink::set_clock(self.$first_name.as_ref(), clock.clone());
+                        $(
+                            $crate::framework::ChainLink::set_clock(self.$mid_name.as_ref(), clock.clone());
+                        )*
+                        $crate::framework::ChainLink::set_clock(self.$last_name.as_ref(), clock.clone());
+                    )*
+                    $(
+                        $crate::framework::ChainLink::set_clock(self.$solo_name.as_ref(), clock.clone());
+                    )*
+                }
+                fn set_dead_letter_queue(&self, dead_letter_queue: $crate::dead_letter::DeadLetterQueue) {
+                    let dead_letter_queue = dead_letter_queue.within(stringify!($name));
+                    $(
+                        $crate::framework::ChainLink::set_dead_letter_queue(self.$first_name.as_ref(), dead_letter_queue.clone());
+                        $(
+                            $crate::framework::ChainLink::set_dead_letter_queue(self.$mid_name.as_ref(), dead_letter_queue.clone());
+                        )*
+                        $crate::framework::ChainLink::set_dead_letter_queue(self.$last_name.as_ref(), dead_letter_queue.clone());
+                    )*
+                    $(
+                        $crate::framework::ChainLink::set_dead_letter_queue(self.$solo_name.as_ref(), dead_letter_queue.clone());
+                    )*
+                }
+                fn set_watchdog(&self, watchdog: $crate::watchdog::Watchdog) {
+                    let watchdog = watchdog.within(stringify!($name));
+                    $(
+                        $crate::framework::ChainLink::set_watchdog(self.$first_name.as_ref(), watchdog.clone());
+                        $(
+                            $crate::framework::ChainLink::set_watchdog(self.$mid_name.as_ref(), watchdog.clone());
+                        )*
+                        $crate::framework::ChainLink::set_watchdog(self.$last_name.as_ref(), watchdog.clone());
+                    )*
+                    $(
+                        $crate::framework::ChainLink::set_watchdog(self.$solo_name.as_ref(), watchdog.clone());
+                    )*
+                }
+                // the dead letter goes to the first inner ChainLink at its path
+                async fn redrive(&self, dead_letter: $crate::dead_letter::DeadLetter) -> Result<(), $crate::dead_letter::DeadLetter> {
+                    $(
+                        let dead_letter = match $crate::framework::ChainLink::redrive(self.$first_name.as_ref(), dead_letter).await {
+                            Ok(()) => return Ok(()),
+                            Err(dead_letter) => dead_letter
+                        };
+                        $(
+                            let dead_letter = match $crate::framework::ChainLink::redrive(self.$mid_name.as_ref(), dead_letter).await {
+                                Ok(()) => return Ok(()),
+                                Err(dead_letter) => dead_letter
+                            };
+                        )*
+                        let dead_letter = match $crate::framework::ChainLink::redrive(self.$last_name.as_ref(), dead_letter).await {
+                            Ok(()) => return Ok(()),
+                            Err(dead_letter) => dead_letter
+                        };
+                    )*
+                    $(
+                        let dead_letter = match $crate::framework::ChainLink::redrive(self.$solo_name.as_ref(), dead_letter).await {
+                            Ok(()) => return Ok(()),
+                            Err(dead_letter) => dead_letter
+                        };
+                    )*
+                    return Err(dead_letter);
+                }
+                async fn process(&self) -> $crate::framework::ProcessOutcome {
                     let mode = stringify!($mode);
                     let choice = stringify!($choice);
                     match mode {
@@ -975,29 +1428,30 @@ macro_rules! chain {
 #[macro_export]
 macro_rules! duplicate {
     ($name:ty, $from:ty => $to:ty, $duplicate:ty) => {
-        duplicate!(end $name, $from => $to, $duplicate, false, false)
+        duplicate!(end $name, $from => $to, $duplicate, false, false);
     };
     ($name:ty, $from:ty => $to:ty, $duplicate:ty, join) => {
-        duplicate!(end $name, $from => $to, $duplicate, true, false)
+        duplicate!(end $name, $from => $to, $duplicate, true, false);
     };
     ($name:ty, $from:ty => $to:ty, $duplicate:ty, unique) => {
-        duplicate!(end $name, $from => $to, $duplicate, false, true)
+        duplicate!(end $name, $from => $to, $duplicate, false, true);
     };
     (end $name:ty, $from:ty => $to:ty, $duplicate:ty, $is_join:expr, $is_unique:expr) => {
         $crate::paste! {
             pub struct $name {
                 next_send_field_index: $crate::Mutex<usize>,
                 inner_chainlinks: std::vec::Vec<std::sync::Arc<$duplicate>>,
-                is_running_inner_chainlinks: std::vec::Vec<std::sync::Arc<$crate::Mutex<bool>>>
+                is_running_inner_chainlinks: std::vec::Vec<std::sync::Arc<$crate::Mutex<bool>>>,
+                scheduler: std::sync::Mutex<std::sync::Arc<dyn $crate::schedule::Scheduler>>
             }
 
             pub struct [<$name Initializer>] {
                 count: u32,
-                inner_initializer: std::sync::Arc<$crate::RwLock<[<$duplicate Initializer>]>>
+                inner_initializer: std::sync::Arc<$crate::RwLock<<$duplicate as $crate::framework::Initializable>::TInitializer>>
             }
 
             impl [<$name Initializer>] {
-                pub fn new(count: u32, initializer: [<$duplicate Initializer>]) -> Self {
+                pub fn new(count: u32, initializer: <$duplicate as $crate::framework::Initializable>::TInitializer) -> Self {
                     [<$name Initializer>] {
                         count,
                         inner_initializer: std::sync::Arc::new($crate::RwLock::new(initializer))
@@ -1010,18 +1464,31 @@ macro_rules! duplicate {
                     let mut inner_chainlinks = vec![];
                     let mut is_running_inner_chainlinks = vec![];
                     for _ in 0..(initializer.read().await.count) {
-                        inner_chainlinks.push(std::sync::Arc::new($duplicate::new(initializer.read().await.inner_initializer.clone()).await));
+                        inner_chainlinks.push(std::sync::Arc::new(<$duplicate as $crate::framework::Initializable>::initialize(initializer.read().await.inner_initializer.clone()).await));
                         is_running_inner_chainlinks.push(std::sync::Arc::new($crate::Mutex::new(false)));
                     }
                     Self {
                         next_send_field_index: $crate::Mutex::new(0),
                         inner_chainlinks,
-                        is_running_inner_chainlinks
+                        is_running_inner_chainlinks,
+                        scheduler: std::sync::Mutex::new(std::sync::Arc::new($crate::schedule::ThreadScheduler))
                     }
                 }
                 pub async fn new_raw(initializer: [<$name Initializer>]) -> Self {
                     $name::new(std::sync::Arc::new($crate::RwLock::new(initializer))).await
                 }
+                fn scheduler(&self) -> std::sync::Arc<dyn $crate::schedule::Scheduler> {
+                    self.scheduler.lock().unwrap().clone()
+                }
+            }
+
+            #[$crate::async_trait]
+            impl $crate::framework::Initializable for $name {
+                type TInitializer = [<$name Initializer>];
+
+                async fn initialize(initializer: std::sync::Arc<$crate::RwLock<[<$name Initializer>]>>) -> Self {
+                    $name::new(initializer).await
+                }
             }
 
             #[$crate::async_trait]
@@ -1038,6 +1505,12 @@ macro_rules! duplicate {
                 async fn push_raw(&self, input: $from) -> () {
                     self.push(std::sync::Arc::new($crate::RwLock::new(input))).await
                 }
+                async fn push_item_with_headers(&self, input: $crate::item::Item<$from>, headers: $crate::envelope::HeaderMap) -> () {
+                    let input = input.into_shared();
+                    for chainlink in self.inner_chainlinks.iter() {
+                        $crate::framework::ChainLink::push_item_with_headers(chainlink.as_ref(), $crate::item::Item::Shared(input.clone()), headers.clone()).await;
+                    }
+                }
                 async fn push_if_empty(&self, input: std::sync::Arc<$crate::RwLock<$from>>) -> () {
                     for chainlink in self.inner_chainlinks.iter() {
                         $crate::framework::ChainLink::push_if_empty(chainlink.as_ref(), input.clone()).await;
@@ -1046,7 +1519,21 @@ macro_rules! duplicate {
                 async fn push_raw_if_empty(&self, input: $from) -> () {
                     self.push_if_empty(std::sync::Arc::new($crate::RwLock::new(input))).await
                 }
+                async fn push_replace(&self, input: std::sync::Arc<$crate::RwLock<$from>>) -> () {
+                    for chainlink in self.inner_chainlinks.iter() {
+                        $crate::framework::ChainLink::push_replace(chainlink.as_ref(), input.clone()).await;
+                    }
+                }
+                async fn push_raw_replace(&self, input: $from) -> () {
+                    self.push_replace(std::sync::Arc::new($crate::RwLock::new(input))).await
+                }
                 async fn try_pop(&self) -> Option<std::sync::Arc<$crate::RwLock<$to>>> {
+                    self.try_pop_item().await.map($crate::item::Item::into_shared)
+                }
+                async fn try_pop_item(&self) -> Option<$crate::item::Item<$to>> {
+                    self.try_pop_item_with_headers().await.map(|(output, _)| output)
+                }
+                async fn try_pop_item_with_headers(&self) -> Option<($crate::item::Item<$to>, $crate::envelope::HeaderMap)> {
 
                     // loop until we have found `Some` or looped around all internal ChainLink in
                     let mut next_send_field_index_lock = self.next_send_field_index.lock().await;
@@ -1064,7 +1551,7 @@ macro_rules! duplicate {
                         }
 
                         // get the output for the current field index
-                        let output = self.inner_chainlinks[next_send_field_index].try_pop().await;
+                        let output = $crate::framework::ChainLink::try_pop_item_with_headers(self.inner_chainlinks[next_send_field_index].as_ref()).await;
 
                         // return the output if `Some`, else try to loop again
                         if output.is_some() {
@@ -1077,63 +1564,194 @@ macro_rules! duplicate {
                     // if we've exhausted all internal `ChainLink` instances, return None
                     return None;
                 }
-                async fn process(&self) -> bool {
+                fn set_scheduler(&self, scheduler: std::sync::Arc<dyn $crate::schedule::Scheduler>) {
+                    for chainlink in self.inner_chainlinks.iter() {
+                        $crate::framework::ChainLink::set_scheduler(chainlink.as_ref(), scheduler.clone());
+                    }
+                    *self.scheduler.lock().unwrap() = scheduler;
+                }
+                fn set_clock(&self, clock: $crate::clock::Clock) {
+                    for chainlink in self.inner_chainlinks.iter() {
+                        $crate::framework::ChainLink::set_clock(chainlink.as_ref(), clock.clone());
+                    }
+                }
+                fn set_dead_letter_queue(&self, dead_letter_queue: $crate::dead_letter::DeadLetterQueue) {
+                    let dead_letter_queue = dead_letter_queue.within(stringify!($name));
+                    for chainlink in self.inner_chainlinks.iter() {
+                        $crate::framework::ChainLink::set_dead_letter_queue(chainlink.as_ref(), dead_letter_queue.clone());
+                    }
+                }
+                fn set_watchdog(&self, watchdog: $crate::watchdog::Watchdog) {
+                    let watchdog = watchdog.within(stringify!($name));
+                    for chainlink in self.inner_chainlinks.iter() {
+                        $crate::framework::ChainLink::set_watchdog(chainlink.as_ref(), watchdog.clone());
+                    }
+                }
+                // every duplicate is at the same path, so the dead letter goes to the first
+                async fn redrive(&self, dead_letter: $crate::dead_letter::DeadLetter) -> Result<(), $crate::dead_letter::DeadLetter> {
+                    let mut dead_letter = dead_letter;
+                    for chainlink in self.inner_chainlinks.iter() {
+                        dead_letter = match $crate::framework::ChainLink::redrive(chainlink.as_ref(), dead_letter).await {
+                            Ok(()) => return Ok(()),
+                            Err(dead_letter) => dead_letter
+                        };
+                    }
+                    return Err(dead_letter);
+                }
+                async fn process(&self) -> $crate::framework::ProcessOutcome {
                     if $is_join {
                         let mut future_collection = vec![];
                         for chainlink in self.inner_chainlinks.iter() {
                             future_collection.push(chainlink.process());
                         }
-                        let outcome = $crate::join_all(future_collection).await;
-                        for index in 0..self.inner_chainlinks.len() {
-                            let indexed_bool_tuple: bool = *outcome.get(index).expect(&format!("The tuple index {} should exist within the $crate::join_all of the chainlink processes of length {}.", index, self.inner_chainlinks.len()));
-                            if indexed_bool_tuple {
-                                return true;
-                            }
+                        let inner_outcomes = $crate::join_all(future_collection).await;
+                        let mut outcome = $crate::framework::ProcessOutcome::idle();
+                        for (index, inner_outcome) in inner_outcomes.into_iter().enumerate() {
+                            outcome.merge_branch(index, inner_outcome);
                         }
-                        return false;
+                        return outcome;
                     }
                     else if $is_unique {
+                        let mut outcome = $crate::framework::ProcessOutcome::idle();
                         for (index, chainlink) in self.inner_chainlinks.iter().enumerate() {
                             let mut locked_is_running_inner_chainlink = self.is_running_inner_chainlinks[index].lock().await;
                             if !*locked_is_running_inner_chainlink {
                                 *locked_is_running_inner_chainlink = true;
                                 let inner_chainlink = chainlink.clone();
                                 let is_running_inner_chainlink = self.is_running_inner_chainlinks[index].clone();
-                                std::thread::spawn(move || {
-                                    let tokio_runtime = $crate::Builder::new_current_thread()
-                                        .enable_time()
-                                        .build()
-                                        .unwrap();
-
-                                    tokio_runtime.block_on(async {
-                                        inner_chainlink.process().await;
-                                        *is_running_inner_chainlink.lock().await = false;
-                                    });
-                                });
+                                self.scheduler().spawn(Box::pin(async move {
+                                    inner_chainlink.process().await;
+                                    *is_running_inner_chainlink.lock().await = false;
+                                }));
+                                outcome.is_deferred = true;
+                            }
+                            else {
+                                outcome.is_busy = true;
                             }
                         }
-                        return false;
+                        return outcome;
                     }
                     else {
                         self.inner_chainlinks
                             .iter()
                             .for_each(|c| {
                                 let inner_chainlink = c.clone();
-                                std::thread::spawn(move || {
-                                    let tokio_runtime = $crate::Builder::new_current_thread()
-                                        .enable_time()
-                                        .build()
-                                        .unwrap();
-
-                                    tokio_runtime.block_on(async {
-                                        inner_chainlink.process().await;
-                                    });
-                                });
+                                self.scheduler().spawn(Box::pin(async move {
+                                    inner_chainlink.process().await;
+                                }));
                             });
-                        return false;
+                        return $crate::framework::ProcessOutcome::deferred();
                     }
                 }
             }
         }
     };
-}
\ No newline at end of file
+}
+// implements ChainLink for a framework ChainLink along with the methods that framework ChainLinks have in common
+// `queued` pushes into `input_queue` and pops from `output_queue`
+// `headed` pushes and pops the headers of each item along with it, for ChainLinks that pass the headers of each input on to its outputs
+// `wrapping chain_link [...]` hands each listed setting and redrive to the wrapped ChainLink in the `chain_link` field, keeping the settings in `settings`
+macro_rules! impl_chain_link {
+    (impl [$($generics:tt)*] $type:ty where [$($bounds:tt)*] ($input:ty => $output:ty) $($rest:tt)*) => {
+        impl_chain_link!(@methods ($input) ($output) ([$($generics)*] $type where [$($bounds)*]) () $($rest)*);
+    };
+    (@methods ($input:ty) ($output:ty) $header:tt ($($methods:tt)*) queued $($rest:tt)*) => {
+        impl_chain_link!(@methods ($input) ($output) $header ($($methods)*
+            async fn push(&self, input: std::sync::Arc<tokio::sync::RwLock<$input>>) {
+                self.input_queue.push($crate::item::Item::Shared(input)).await;
+            }
+            async fn push_raw(&self, input: $input) {
+                self.input_queue.push($crate::item::Item::Owned(input)).await;
+            }
+            async fn push_if_empty(&self, input: std::sync::Arc<tokio::sync::RwLock<$input>>) {
+                self.input_queue.push_if_empty($crate::item::Item::Shared(input)).await;
+            }
+            async fn push_raw_if_empty(&self, input: $input) {
+                self.input_queue.push_if_empty($crate::item::Item::Owned(input)).await;
+            }
+            async fn push_replace(&self, input: std::sync::Arc<tokio::sync::RwLock<$input>>) {
+                self.input_queue.push_replace($crate::item::Item::Shared(input)).await;
+            }
+            async fn push_raw_replace(&self, input: $input) {
+                self.input_queue.push_replace($crate::item::Item::Owned(input)).await;
+            }
+            async fn try_pop(&self) -> Option<std::sync::Arc<tokio::sync::RwLock<$output>>> {
+                self.output_queue.try_pop().await.map($crate::item::Item::into_shared)
+            }
+            async fn push_item(&self, input: $crate::item::Item<$input>) {
+                self.input_queue.push(input).await;
+            }
+            async fn try_pop_item(&self) -> Option<$crate::item::Item<$output>> {
+                self.output_queue.try_pop().await
+            }
+        ) $($rest)*);
+    };
+    (@methods ($input:ty) ($output:ty) $header:tt ($($methods:tt)*) headed $($rest:tt)*) => {
+        impl_chain_link!(@methods ($input) ($output) $header ($($methods)*
+            async fn push_item_with_headers(&self, input: $crate::item::Item<$input>, headers: $crate::envelope::HeaderMap) {
+                self.input_queue.push_with_headers(input, headers).await;
+            }
+            async fn try_pop_item_with_headers(&self) -> Option<($crate::item::Item<$output>, $crate::envelope::HeaderMap)> {
+                self.output_queue.try_pop_with_headers().await
+            }
+        ) $($rest)*);
+    };
+    (@methods $input:tt $output:tt $header:tt $methods:tt wrapping $field:ident [$($forwarded:ident),*] $($rest:tt)*) => {
+        impl_chain_link!(@forward $input $output $header $methods $field [$($forwarded),*] $($rest)*);
+    };
+    (@forward $input:tt $output:tt $header:tt $methods:tt $field:ident [] $($rest:tt)*) => {
+        impl_chain_link!(@methods $input $output $header $methods $($rest)*);
+    };
+    (@forward $input:tt $output:tt $header:tt ($($methods:tt)*) $field:ident [set_scheduler $(, $more:ident)*] $($rest:tt)*) => {
+        impl_chain_link!(@forward $input $output $header ($($methods)*
+            fn set_scheduler(&self, scheduler: std::sync::Arc<dyn $crate::schedule::Scheduler>) {
+                $crate::framework::ChainLink::set_scheduler(&self.$field, scheduler.clone());
+                self.settings.set_scheduler(scheduler);
+            }
+        ) $field [$($more),*] $($rest)*);
+    };
+    (@forward $input:tt $output:tt $header:tt ($($methods:tt)*) $field:ident [set_clock $(, $more:ident)*] $($rest:tt)*) => {
+        impl_chain_link!(@forward $input $output $header ($($methods)*
+            fn set_clock(&self, clock: $crate::clock::Clock) {
+                $crate::framework::ChainLink::set_clock(&self.$field, clock.clone());
+                self.settings.set_clock(clock);
+            }
+        ) $field [$($more),*] $($rest)*);
+    };
+    (@forward $input:tt $output:tt $header:tt ($($methods:tt)*) $field:ident [set_dead_letter_queue $(, $more:ident)*] $($rest:tt)*) => {
+        impl_chain_link!(@forward $input $output $header ($($methods)*
+            fn set_dead_letter_queue(&self, dead_letter_queue: $crate::dead_letter::DeadLetterQueue) {
+                $crate::framework::ChainLink::set_dead_letter_queue(&self.$field, dead_letter_queue.clone());
+                self.settings.set_dead_letter_queue(dead_letter_queue);
+            }
+        ) $field [$($more),*] $($rest)*);
+    };
+    (@forward $input:tt $output:tt $header:tt ($($methods:tt)*) $field:ident [set_watchdog $(, $more:ident)*] $($rest:tt)*) => {
+        impl_chain_link!(@forward $input $output $header ($($methods)*
+            fn set_watchdog(&self, watchdog: $crate::watchdog::Watchdog) {
+                $crate::framework::ChainLink::set_watchdog(&self.$field, watchdog.clone());
+                self.settings.set_watchdog(watchdog);
+            }
+        ) $field [$($more),*] $($rest)*);
+    };
+    (@forward $input:tt $output:tt $header:tt ($($methods:tt)*) $field:ident [redrive $(, $more:ident)*] $($rest:tt)*) => {
+        impl_chain_link!(@forward $input $output $header ($($methods)*
+            async fn redrive(&self, dead_letter: $crate::dead_letter::DeadLetter) -> Result<(), $crate::dead_letter::DeadLetter> {
+                $crate::framework::ChainLink::redrive(&self.$field, dead_letter).await
+            }
+        ) $field [$($more),*] $($rest)*);
+    };
+    (@methods ($input:ty) ($output:ty) ([$($generics:tt)*] $type:ty where [$($bounds:tt)*]) ($($methods:tt)*) { $($body:tt)* }) => {
+        #[async_trait::async_trait]
+        impl<$($generics)*> $crate::framework::ChainLink for $type
+        where
+            $($bounds)*
+        {
+            type TInput = $input;
+            type TOutput = $output;
+
+            $($methods)*
+            $($body)*
+        }
+    };
+}
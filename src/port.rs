@@ -0,0 +1,69 @@
+use std::{marker::PhantomData, sync::Arc};
+use tokio::sync::RwLock;
+use crate::{framework::{ChainLink, Initializable, ProcessOutcome}, item::Item, queue::Queue};
+
+// a named input of a ChainLink, declared within chain_link! as `input: [camera: Direction, controller: KeyPress] => ...`
+// each port is a type named after the ChainLink and the port, such as `SensorProcessorCamera`
+pub trait Port {
+    type TChainLink: ChainLink;
+    type TValue;
+
+    fn wrap(value: Self::TValue) -> <Self::TChainLink as ChainLink>::TInput;
+}
+
+pub struct ToPortInitializer;
+
+// feeds the outputs of an upstream ChainLink into one port of a downstream ChainLink
+// within a chain!, place it between the two such as `CameraSensor => ToPort<SensorProcessorCamera>`, so that parallel ChainLinks can each feed a different port of the same ChainLink
+pub struct ToPort<TPort: Port> {
+    input_queue: Queue<Item<TPort::TValue>>,
+    output_queue: Queue<Item<<TPort::TChainLink as ChainLink>::TInput>>,
+    port: PhantomData<TPort>
+}
+
+impl<TPort: Port> ToPort<TPort> {
+    pub fn new() -> Self {
+        ToPort {
+            input_queue: Queue::default(),
+            output_queue: Queue::default(),
+            port: PhantomData
+        }
+    }
+}
+
+impl<TPort: Port> Default for ToPort<TPort> {
+    fn default() -> Self {
+        ToPort::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl<TPort: Port> Initializable for ToPort<TPort> {
+    type TInitializer = ToPortInitializer;
+
+    async fn initialize(_initializer: Arc<RwLock<ToPortInitializer>>) -> Self {
+        ToPort::new()
+    }
+}
+
+impl_chain_link! {
+    impl[TPort] ToPort<TPort>
+    where [
+        TPort: Port + Send + Sync,
+        TPort::TValue: Clone + Send + Sync,
+        <TPort::TChainLink as ChainLink>::TInput: Send + Sync
+    ]
+    (TPort::TValue => <TPort::TChainLink as ChainLink>::TInput)
+    queued
+    headed
+    {
+        async fn process(&self) -> ProcessOutcome {
+            let Some((input, headers)) = self.input_queue.try_pop_with_headers().await else {
+                return ProcessOutcome::idle();
+            };
+            let output = TPort::wrap(input.into_owned().await);
+            self.output_queue.push_with_headers(Item::Owned(output), headers).await;
+            ProcessOutcome::consumed(1, 1)
+        }
+    }
+}
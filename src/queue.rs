@@ -1,8 +1,11 @@
+use std::time::Instant;
 use tokio::sync::Mutex;
+use crate::{clock::Clock, envelope::HeaderMap, item::Item};
 
 
 pub struct Queue<T> {
-    items: Mutex<Vec<T>>
+    // each item along with the headers of the envelope it came from, which are empty for an item pushed without them
+    items: Mutex<Vec<(T, HeaderMap)>>
 }
 
 impl<T> Default for Queue<T> {
@@ -15,17 +18,28 @@ impl<T> Default for Queue<T> {
 
 impl<T> Queue<T> {
     pub async fn push(&self, item: T) {
+        self.push_with_headers(item, HeaderMap::new()).await;
+    }
+    pub async fn push_with_headers(&self, item: T, headers: HeaderMap) {
         let mut locked_items = self.items.lock().await;
-        locked_items.push(item);
+        locked_items.push((item, headers));
     }
     pub async fn push_if_empty(&self, item: T) {
         let mut locked_items = self.items.lock().await;
         if locked_items.is_empty() {
-            locked_items.push(item);
+            locked_items.push((item, HeaderMap::new()));
         }
     }
+    pub async fn push_replace(&self, item: T) {
+        let mut locked_items = self.items.lock().await;
+        locked_items.clear();
+        locked_items.push((item, HeaderMap::new()));
+    }
     pub async fn try_pop(&self) -> Option<T> {
-        let popped_item: Option<T>;
+        self.try_pop_with_headers().await.map(|(item, _)| item)
+    }
+    pub async fn try_pop_with_headers(&self) -> Option<(T, HeaderMap)> {
+        let popped_item: Option<(T, HeaderMap)>;
         {
             let mut locked_items = self.items.lock().await;
             popped_item = if locked_items.len() == 0 {
@@ -37,4 +51,51 @@ impl<T> Queue<T> {
         }
         return popped_item;
     }
-}
\ No newline at end of file
+    pub async fn len(&self) -> usize {
+        self.items.lock().await.len()
+    }
+    pub async fn is_empty(&self) -> bool {
+        self.items.lock().await.is_empty()
+    }
+    pub async fn try_pop_up_to(&self, count: usize) -> Vec<T> {
+        self.try_pop_up_to_with_headers(count).await.into_iter().map(|(item, _)| item).collect()
+    }
+    pub async fn try_pop_up_to_with_headers(&self, count: usize) -> Vec<(T, HeaderMap)> {
+        let mut locked_items = self.items.lock().await;
+        let count = count.min(locked_items.len());
+        locked_items.drain(..count).collect()
+    }
+}
+
+// the inputs of a ChainLink that measures how long they wait, such as a Batcher, each noting when it was pushed using the clock of the ChainLink
+pub(crate) struct ArrivalQueue<T> {
+    clock: std::sync::Mutex<Clock>,
+    queue: Queue<(Instant, Item<T>)>
+}
+
+impl<T> ArrivalQueue<T> {
+    pub(crate) fn new() -> Self {
+        ArrivalQueue {
+            clock: std::sync::Mutex::new(Clock::default()),
+            queue: Queue::default()
+        }
+    }
+    pub(crate) fn clock(&self) -> Clock {
+        self.clock.lock().unwrap().clone()
+    }
+    pub(crate) fn set_clock(&self, clock: Clock) {
+        *self.clock.lock().unwrap() = clock;
+    }
+    pub(crate) async fn push(&self, item: Item<T>) {
+        self.queue.push((self.clock().now(), item)).await;
+    }
+    pub(crate) async fn push_if_empty(&self, item: Item<T>) {
+        self.queue.push_if_empty((self.clock().now(), item)).await;
+    }
+    pub(crate) async fn push_replace(&self, item: Item<T>) {
+        self.queue.push_replace((self.clock().now(), item)).await;
+    }
+    pub(crate) async fn try_pop_up_to(&self, count: usize) -> Vec<(Instant, Item<T>)> {
+        self.queue.try_pop_up_to(count).await
+    }
+}
@@ -0,0 +1,210 @@
+use std::{sync::Arc, time::{Duration, Instant}};
+use tokio::sync::RwLock;
+use crate::{clock::Clock, framework::{Initializable, ProcessOutcome}, item::Item, queue::Queue};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bucket {
+    // up to `capacity` inputs at once, after which one more is allowed every `refill_interval`
+    Token {
+        capacity: usize,
+        refill_interval: Duration
+    },
+    // one input every `interval`, never in bursts, while the rest wait in the input queue
+    Leaky {
+        interval: Duration
+    }
+}
+
+struct RateState {
+    token_count: usize,
+    // when the tokens were last refilled or the bucket last leaked
+    last: Option<Instant>
+}
+
+// limits how many inputs are processed over time, where clones share the same limit
+pub struct RateLimiter {
+    bucket: Bucket,
+    state: Arc<std::sync::Mutex<RateState>>
+}
+
+impl Clone for RateLimiter {
+    fn clone(&self) -> Self {
+        RateLimiter {
+            bucket: self.bucket,
+            state: self.state.clone()
+        }
+    }
+}
+
+impl RateLimiter {
+    pub fn new(bucket: Bucket) -> Self {
+        if let Bucket::Token { capacity, .. } = bucket {
+            assert!(capacity != 0, "The token bucket should hold at least one token.");
+        }
+        let token_count = match bucket {
+            Bucket::Token { capacity, .. } => capacity,
+            Bucket::Leaky { .. } => 0
+        };
+        RateLimiter {
+            bucket,
+            state: Arc::new(std::sync::Mutex::new(RateState {
+                token_count,
+                last: None
+            }))
+        }
+    }
+    pub fn token_bucket(capacity: usize, refill_interval: Duration) -> Self {
+        RateLimiter::new(Bucket::Token {
+            capacity,
+            refill_interval
+        })
+    }
+    pub fn leaky_bucket(interval: Duration) -> Self {
+        RateLimiter::new(Bucket::Leaky {
+            interval
+        })
+    }
+    pub fn bucket(&self) -> Bucket {
+        self.bucket
+    }
+    // a limiter with the same limit that does not share it with this one
+    pub fn unshared(&self) -> Self {
+        RateLimiter::new(self.bucket)
+    }
+    // how long after `now` until another input is allowed, which is zero when one already is
+    pub fn due_in(&self, now: Instant) -> Duration {
+        let locked_state = self.state.lock().unwrap();
+        let interval = match self.bucket {
+            Bucket::Token { .. } if locked_state.token_count != 0 => return Duration::ZERO,
+            Bucket::Token { refill_interval, .. } => refill_interval,
+            Bucket::Leaky { interval } => interval
+        };
+        locked_state.last.map_or(Duration::ZERO, |last| (last + interval).saturating_duration_since(now))
+    }
+    // takes permission for up to `count` inputs at `now`, returning how many are allowed
+    pub fn acquire_up_to(&self, count: usize, now: Instant) -> usize {
+        if count == 0 {
+            return 0;
+        }
+        let mut locked_state = self.state.lock().unwrap();
+        match self.bucket {
+            Bucket::Token { capacity, refill_interval } => {
+                let last = *locked_state.last.get_or_insert(now);
+                if !refill_interval.is_zero() {
+                    let elapsed = now.saturating_duration_since(last);
+                    let refill_count = usize::try_from(elapsed.as_nanos() / refill_interval.as_nanos()).unwrap_or(usize::MAX);
+                    if locked_state.token_count.saturating_add(refill_count) >= capacity {
+                        locked_state.token_count = capacity;
+                        locked_state.last = Some(now);
+                    }
+                    else {
+                        locked_state.token_count += refill_count;
+                        // the time toward the next token is kept
+                        locked_state.last = Some(last + refill_interval * u32::try_from(refill_count).unwrap_or(u32::MAX));
+                    }
+                }
+                else {
+                    locked_state.token_count = capacity;
+                }
+                let acquired_count = count.min(locked_state.token_count);
+                locked_state.token_count -= acquired_count;
+                acquired_count
+            },
+            Bucket::Leaky { interval } => {
+                let is_leaking = locked_state.last.is_none_or(|last| now.saturating_duration_since(last) >= interval);
+                if !is_leaking {
+                    return 0;
+                }
+                locked_state.last = Some(now);
+                1
+            }
+        }
+    }
+}
+
+pub struct RateLimitInitializer {
+    pub limiter: RateLimiter,
+    // whether every instance made from this initializer, such as each within a duplicate!, shares the limit or has its own
+    pub is_global: bool
+}
+
+impl RateLimitInitializer {
+    pub fn new(limiter: RateLimiter, is_global: bool) -> Self {
+        RateLimitInitializer {
+            limiter,
+            is_global
+        }
+    }
+}
+
+// passes inputs through no faster than its limiter allows, measured using the clock of the ChainLink
+// while inputs are waiting, `process` reports that it is busy along with when the next is allowed, so that drivers keep processing until they are passed through
+pub struct RateLimit<T> {
+    limiter: RateLimiter,
+    input_queue: Queue<Item<T>>,
+    output_queue: Queue<Item<T>>,
+    clock: std::sync::Mutex<Clock>
+}
+
+impl<T> RateLimit<T> {
+    pub fn new(limiter: RateLimiter) -> Self {
+        RateLimit {
+            limiter,
+            input_queue: Queue::default(),
+            output_queue: Queue::default(),
+            clock: std::sync::Mutex::new(Clock::default())
+        }
+    }
+    pub fn limiter(&self) -> &RateLimiter {
+        &self.limiter
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> Initializable for RateLimit<T> {
+    type TInitializer = RateLimitInitializer;
+
+    async fn initialize(initializer: Arc<RwLock<RateLimitInitializer>>) -> Self {
+        let locked_initializer = initializer.read().await;
+        if locked_initializer.is_global {
+            RateLimit::new(locked_initializer.limiter.clone())
+        }
+        else {
+            RateLimit::new(locked_initializer.limiter.unshared())
+        }
+    }
+}
+
+impl_chain_link! {
+    impl[T] RateLimit<T>
+    where [
+        T: Send + Sync
+    ]
+    (T => T)
+    queued
+    headed
+    {
+        fn set_clock(&self, clock: Clock) {
+            *self.clock.lock().unwrap() = clock;
+        }
+        async fn process(&self) -> ProcessOutcome {
+            let waiting_count = self.input_queue.len().await;
+            if waiting_count == 0 {
+                return ProcessOutcome::idle();
+            }
+            let now = self.clock.lock().unwrap().now();
+            let acquired_count = self.limiter.acquire_up_to(waiting_count, now);
+            let received_items = self.input_queue.try_pop_up_to_with_headers(acquired_count).await;
+            let passed_count = received_items.len();
+            for (received, headers) in received_items {
+                self.output_queue.push_with_headers(received, headers).await;
+            }
+            let mut outcome = ProcessOutcome::consumed(passed_count, passed_count);
+            outcome.is_busy = passed_count < waiting_count;
+            if outcome.is_busy {
+                outcome.due_in = Some(self.limiter.due_in(now));
+            }
+            outcome
+        }
+    }
+}
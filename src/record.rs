@@ -0,0 +1,204 @@
+use std::{fs::File, io::{BufRead, BufReader}, path::Path, sync::Arc, time::{Duration, SystemTime, UNIX_EPOCH}};
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+use tokio::{io::AsyncWriteExt, sync::{Mutex, RwLock}};
+use crate::{envelope::HeaderMap, framework::{ChainLink, ProcessOutcome, WrapperSettings}, item::Item, testing::pop_next};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RecordedEvent<TInput, TOutput> {
+    Push(TInput),
+    // pushed only when nothing was queued
+    PushIfEmpty(TInput),
+    // pushed in place of everything that was queued
+    PushReplace(TInput),
+    Pop(TOutput)
+}
+
+// one line of a recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedEntry<TInput, TOutput> {
+    pub timestamp_micros: u64,
+    pub event: RecordedEvent<TInput, TOutput>
+}
+
+// wraps any ChainLink, writing every pushed input and popped output to a file as JSON lines
+// a failed write does not interrupt the wrapped ChainLink, and is instead kept for `take_error`
+pub struct Recorder<TChainLink: ChainLink> {
+    inner: TChainLink,
+    file: Mutex<tokio::fs::File>,
+    error: std::sync::Mutex<Option<std::io::Error>>,
+    settings: WrapperSettings
+}
+
+impl<TChainLink: ChainLink> Recorder<TChainLink>
+where
+    TChainLink::TInput: Serialize,
+    TChainLink::TOutput: Serialize
+{
+    pub fn new(inner: TChainLink, path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Ok(Recorder {
+            inner,
+            file: Mutex::new(tokio::fs::File::from_std(File::create(path)?)),
+            error: std::sync::Mutex::new(None),
+            settings: WrapperSettings::default()
+        })
+    }
+    pub fn inner(&self) -> &TChainLink {
+        &self.inner
+    }
+    // the first error met while writing the recording since the last call, after which the recording may be missing events
+    pub fn take_error(&self) -> Option<std::io::Error> {
+        self.error.lock().unwrap().take()
+    }
+    async fn record(&self, event: RecordedEvent<&TChainLink::TInput, &TChainLink::TOutput>) {
+        if let Err(error) = self.write(event).await {
+            self.error.lock().unwrap().get_or_insert(error);
+        }
+    }
+    async fn write(&self, event: RecordedEvent<&TChainLink::TInput, &TChainLink::TOutput>) -> std::io::Result<()> {
+        let timestamp_micros = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO)
+            .as_micros() as u64;
+        let mut line = serde_json::to_string(&RecordedEntry {
+            timestamp_micros,
+            event
+        })?;
+        line.push('\n');
+        let mut locked_file = self.file.lock().await;
+        locked_file.write_all(line.as_bytes()).await?;
+        // the line is on disk before the event it records goes on, so that the recording can be read at any time
+        locked_file.flush().await
+    }
+}
+
+impl_chain_link! {
+    impl[TChainLink] Recorder<TChainLink>
+    where [
+        TChainLink: ChainLink + Send + Sync,
+        TChainLink::TInput: Serialize + Send + Sync,
+        TChainLink::TOutput: Serialize + Send + Sync
+    ]
+    (TChainLink::TInput => TChainLink::TOutput)
+    wrapping inner [set_scheduler, set_clock, set_dead_letter_queue, redrive, set_watchdog]
+    {
+        async fn push(&self, input: Arc<RwLock<Self::TInput>>) {
+            self.record(RecordedEvent::Push(&*input.read().await)).await;
+            self.inner.push(input).await;
+        }
+        async fn push_raw(&self, input: Self::TInput) {
+            self.push(Arc::new(RwLock::new(input))).await
+        }
+        async fn push_if_empty(&self, input: Arc<RwLock<Self::TInput>>) {
+            self.record(RecordedEvent::PushIfEmpty(&*input.read().await)).await;
+            self.inner.push_if_empty(input).await;
+        }
+        async fn push_raw_if_empty(&self, input: Self::TInput) {
+            self.push_if_empty(Arc::new(RwLock::new(input))).await
+        }
+        async fn push_replace(&self, input: Arc<RwLock<Self::TInput>>) {
+            self.record(RecordedEvent::PushReplace(&*input.read().await)).await;
+            self.inner.push_replace(input).await;
+        }
+        async fn push_raw_replace(&self, input: Self::TInput) {
+            self.push_replace(Arc::new(RwLock::new(input))).await
+        }
+        async fn try_pop(&self) -> Option<Arc<RwLock<Self::TOutput>>> {
+            let output = self.inner.try_pop().await;
+            if let Some(output) = output.as_ref() {
+                self.record(RecordedEvent::Pop(&*output.read().await)).await;
+            }
+            output
+        }
+        async fn push_item(&self, input: Item<Self::TInput>) {
+            self.record(RecordedEvent::Push(&*input.read().await)).await;
+            self.inner.push_item(input).await;
+        }
+        async fn try_pop_item(&self) -> Option<Item<Self::TOutput>> {
+            let output = self.inner.try_pop_item().await;
+            if let Some(output) = output.as_ref() {
+                self.record(RecordedEvent::Pop(&*output.read().await)).await;
+            }
+            output
+        }
+        async fn push_item_with_headers(&self, input: Item<Self::TInput>, headers: HeaderMap) {
+            self.record(RecordedEvent::Push(&*input.read().await)).await;
+            self.inner.push_item_with_headers(input, headers).await;
+        }
+        async fn try_pop_item_with_headers(&self) -> Option<(Item<Self::TOutput>, HeaderMap)> {
+            let output = self.inner.try_pop_item_with_headers().await;
+            if let Some((output, _)) = output.as_ref() {
+                self.record(RecordedEvent::Pop(&*output.read().await)).await;
+            }
+            output
+        }
+        async fn process(&self) -> ProcessOutcome {
+            self.inner.process().await
+        }
+    }
+}
+
+// a recorded output that the replay did not reproduce
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayDifference<TOutput> {
+    pub pop_index: usize,
+    pub expected: Option<TOutput>,
+    pub actual: Option<TOutput>
+}
+
+pub fn read_recording<TInput: DeserializeOwned, TOutput: DeserializeOwned>(path: impl AsRef<Path>) -> std::io::Result<Vec<RecordedEntry<TInput, TOutput>>> {
+    let mut entries = vec![];
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            entries.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(entries)
+}
+
+// feeds a recording into a fresh ChainLink, returning every output that differs from the recording
+pub async fn replay<TChainLink>(chain_link: &TChainLink, path: impl AsRef<Path>) -> std::io::Result<Vec<ReplayDifference<TChainLink::TOutput>>>
+where
+    TChainLink: ChainLink + Sync,
+    TChainLink::TInput: DeserializeOwned + Send + Sync,
+    TChainLink::TOutput: DeserializeOwned + Clone + PartialEq + Send + Sync
+{
+    let entries = read_recording::<TChainLink::TInput, TChainLink::TOutput>(path)?;
+    let mut differences = vec![];
+    let mut pop_index = 0;
+    for entry in entries {
+        match entry.event {
+            RecordedEvent::Push(input) => {
+                chain_link.push_raw(input).await;
+            },
+            RecordedEvent::PushIfEmpty(input) => {
+                chain_link.push_raw_if_empty(input).await;
+            },
+            RecordedEvent::PushReplace(input) => {
+                chain_link.push_raw_replace(input).await;
+            },
+            RecordedEvent::Pop(expected) => {
+                let actual = pop_next(chain_link).await;
+                if actual.as_ref() != Some(&expected) {
+                    differences.push(ReplayDifference {
+                        pop_index,
+                        expected: Some(expected),
+                        actual
+                    });
+                }
+                pop_index += 1;
+            }
+        }
+    }
+
+    // any remaining output was not part of the recording
+    while let Some(actual) = pop_next(chain_link).await {
+        differences.push(ReplayDifference {
+            pop_index,
+            expected: None,
+            actual: Some(actual)
+        });
+        pop_index += 1;
+    }
+    Ok(differences)
+}
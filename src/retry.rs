@@ -0,0 +1,277 @@
+use std::{panic::AssertUnwindSafe, sync::Arc, time::{Duration, Instant}};
+use futures::FutureExt;
+use tokio::sync::{Mutex, RwLock};
+use crate::{clock::Clock, dead_letter::{DeadLetter, DeadLetterQueue, Failure}, envelope::HeaderMap, framework::{ChainLink, Initializable, ProcessOutcome, WrapperSettings, short_type_name}, item::Item, queue::Queue, schedule::Scheduler};
+
+// how often and how patiently a failing input is attempted again
+#[derive(Clone)]
+pub struct RetryPolicy {
+    // including the first attempt
+    pub max_attempts: usize,
+    // the wait after the first failure, multiplied by `multiplier` after each further failure
+    pub initial_backoff: Duration,
+    pub multiplier: u32,
+    pub max_backoff: Duration,
+    // each wait is shortened by a random fraction of up to this much, from 0.0 to 1.0, so that failures spread out
+    pub jitter: f64,
+    // decides from the failure whether another attempt could succeed, such as by the type of the error returned by a fallible map block
+    pub is_retryable: fn(&Failure) -> bool,
+    // an attempt that takes longer fails with a timeout, measured using the clock of the ChainLink
+    pub attempt_timeout: Option<Duration>
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            multiplier: 2,
+            max_backoff: Duration::from_secs(10),
+            jitter: 0.0,
+            is_retryable: |_| true,
+            attempt_timeout: None
+        }
+    }
+}
+
+impl RetryPolicy {
+    // the wait before the attempt following `failed_attempt_count` failures
+    pub fn backoff(&self, failed_attempt_count: usize, scheduler: &dyn Scheduler) -> Duration {
+        let exponent = u32::try_from(failed_attempt_count.saturating_sub(1)).unwrap_or(u32::MAX);
+        let backoff = self.multiplier
+            .checked_pow(exponent)
+            .and_then(|factor| self.initial_backoff.checked_mul(factor))
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff));
+        if self.jitter <= 0.0 {
+            return backoff;
+        }
+        let random_fraction = scheduler.choose_index(1000) as f64 / 1000.0;
+        backoff.mul_f64(1.0 - self.jitter.min(1.0) * random_fraction)
+    }
+}
+
+pub struct RetryInitializer<TChainLinkInitializer> {
+    pub policy: RetryPolicy,
+    pub chain_link: Arc<RwLock<TChainLinkInitializer>>
+}
+
+impl<TChainLinkInitializer> RetryInitializer<TChainLinkInitializer> {
+    pub fn new(policy: RetryPolicy, chain_link: TChainLinkInitializer) -> Self {
+        RetryInitializer {
+            policy,
+            chain_link: Arc::new(RwLock::new(chain_link))
+        }
+    }
+}
+
+struct PendingRetry<T> {
+    input: Arc<RwLock<T>>,
+    headers: HeaderMap,
+    failed_attempt_count: usize,
+    retry_at: Instant,
+    // an attempt that timed out may leave the input queued within the wrapped ChainLink, which then attempts it again by being processed rather than by being pushed it twice
+    is_held: bool
+}
+
+// attempts each input on the wrapped ChainLink until it succeeds, a panic or timeout being a failure
+// a failed input is held until its backoff has passed, during which `process` reports that it is busy
+// an input that runs out of attempts or fails with an error that is not retryable goes to the dead-letter queue if one is set, or else to `try_pop_exhausted`
+// while the wrapped ChainLink still holds an input whose attempt timed out, no other input is pushed to it, though one that runs out of attempts meanwhile may yet be mapped by it
+// within a chain!, name the type such as `Retry<GetParentById>`, which stands in for the ChainLink within the path of a dead letter
+pub struct Retry<TChainLink: ChainLink> {
+    policy: RetryPolicy,
+    chain_link: TChainLink,
+    input_queue: Queue<Item<TChainLink::TInput>>,
+    pending: Mutex<Vec<PendingRetry<TChainLink::TInput>>>,
+    output_queue: Queue<Item<TChainLink::TOutput>>,
+    exhausted_queue: Queue<DeadLetter>,
+    // set on the wrapped ChainLink, so that the failures it catches reach the retry policy
+    failures: DeadLetterQueue,
+    settings: WrapperSettings
+}
+
+impl<TChainLink: ChainLink> Retry<TChainLink> {
+    pub fn new(policy: RetryPolicy, chain_link: TChainLink) -> Self {
+        assert!(policy.max_attempts != 0, "The retry policy should allow at least one attempt.");
+        let failures = DeadLetterQueue::new();
+        chain_link.set_dead_letter_queue(failures.clone());
+        Retry {
+            policy,
+            chain_link,
+            input_queue: Queue::default(),
+            pending: Mutex::new(vec![]),
+            output_queue: Queue::default(),
+            exhausted_queue: Queue::default(),
+            failures,
+            settings: WrapperSettings::default()
+        }
+    }
+    pub fn chain_link(&self) -> &TChainLink {
+        &self.chain_link
+    }
+    // an input that could not be mapped within the retry policy, when no dead-letter queue is set
+    pub async fn try_pop_exhausted(&self) -> Option<DeadLetter> {
+        self.exhausted_queue.try_pop().await
+    }
+}
+
+#[async_trait::async_trait]
+impl<TChainLink> Initializable for Retry<TChainLink>
+where
+    TChainLink: ChainLink + Initializable + Send,
+    TChainLink::TInitializer: Send + Sync
+{
+    type TInitializer = RetryInitializer<TChainLink::TInitializer>;
+
+    async fn initialize(initializer: Arc<RwLock<RetryInitializer<TChainLink::TInitializer>>>) -> Self {
+        let locked_initializer = initializer.read().await;
+        Retry::new(locked_initializer.policy.clone(), TChainLink::initialize(locked_initializer.chain_link.clone()).await)
+    }
+}
+
+impl<TChainLink> Retry<TChainLink>
+where
+    TChainLink: ChainLink + Send + Sync + 'static,
+    TChainLink::TInput: Send + Sync + 'static,
+    TChainLink::TOutput: Send + Sync
+{
+    // makes one attempt, returning the failure when it fails
+    // an input still held by the wrapped ChainLink is not pushed again
+    async fn attempt(&self, input: Arc<RwLock<TChainLink::TInput>>, headers: HeaderMap, is_held: bool, clock: &Clock) -> Result<ProcessOutcome, Failure> {
+        if !is_held {
+            self.chain_link.push_item_with_headers(Item::Shared(input), headers).await;
+        }
+        let processed = AssertUnwindSafe(self.chain_link.process()).catch_unwind();
+        let outcome = match self.policy.attempt_timeout {
+            Some(attempt_timeout) => {
+                tokio::select! {
+                    outcome = processed => outcome,
+                    _ = clock.sleep(attempt_timeout) => return Err(Failure::Timeout)
+                }
+            },
+            None => processed.await
+        };
+        let outcome = outcome.map_err(|panic| Failure::from_panic(panic.as_ref()))?;
+        let mut failures = self.failures.take_all().into_iter();
+        let Some(dead_letter) = failures.next() else {
+            return Ok(outcome);
+        };
+        // any further failure, such as of an input produced within a wrapped chain, is not retried
+        for other_dead_letter in failures {
+            self.exhaust_dead_letter(other_dead_letter).await;
+        }
+        Err(dead_letter.failure)
+    }
+    async fn exhaust(&self, input: Arc<RwLock<TChainLink::TInput>>, failure: Failure, attempt_count: usize) {
+        let dead_letter_queue = self.settings.dead_letter_queue();
+        let name = short_type_name::<TChainLink>();
+        match dead_letter_queue {
+            Some(dead_letter_queue) => dead_letter_queue.send(input, name, failure, attempt_count),
+            None => self.exhausted_queue.push(DeadLetter::new(input, String::from(name), failure, attempt_count)).await
+        }
+    }
+    // passes on a dead letter of the wrapped ChainLink, whose path is within it
+    async fn exhaust_dead_letter(&self, mut dead_letter: DeadLetter) {
+        match self.settings.dead_letter_queue() {
+            Some(dead_letter_queue) => {
+                dead_letter.path = dead_letter_queue.path_to(&dead_letter.path);
+                dead_letter_queue.push(dead_letter);
+            },
+            None => self.exhausted_queue.push(dead_letter).await
+        }
+    }
+}
+
+impl_chain_link! {
+    impl[TChainLink] Retry<TChainLink>
+    where [
+        TChainLink: ChainLink + Send + Sync + 'static,
+        TChainLink::TInput: Send + Sync + 'static,
+        TChainLink::TOutput: Send + Sync
+    ]
+    (TChainLink::TInput => TChainLink::TOutput)
+    queued
+    headed
+    wrapping chain_link [set_scheduler, set_clock, set_watchdog]
+    {
+        // the dead-letter queue is kept rather than handed to the wrapped ChainLink, so that its failures reach the retry policy first
+        fn set_dead_letter_queue(&self, dead_letter_queue: DeadLetterQueue) {
+            self.settings.set_dead_letter_queue(dead_letter_queue);
+        }
+        async fn redrive(&self, dead_letter: DeadLetter) -> Result<(), DeadLetter> {
+            let dead_letter_queue = self.settings.dead_letter_queue();
+            let is_matching_path = dead_letter_queue.is_some_and(|dead_letter_queue| dead_letter_queue.path_to(short_type_name::<TChainLink>()) == dead_letter.path);
+            match (is_matching_path, dead_letter.input::<TChainLink::TInput>()) {
+                (true, Some(input)) => {
+                    self.input_queue.push(Item::Shared(input)).await;
+                    Ok(())
+                },
+                _ => Err(dead_letter)
+            }
+        }
+        async fn process(&self) -> ProcessOutcome {
+            let clock = self.settings.clock();
+            let scheduler = self.settings.scheduler();
+            let mut locked_pending = self.pending.lock().await;
+
+            // retries that are due are attempted before new inputs, unless the wrapped ChainLink still holds an input, which is then the only one attempted
+            let now = clock.now();
+            let is_holding = locked_pending.iter().any(|pending| pending.is_held);
+            let (due, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut *locked_pending).into_iter().partition(|pending| pending.retry_at <= now && pending.is_held == is_holding);
+            *locked_pending = waiting;
+            let received_items = match is_holding {
+                true => vec![],
+                false => self.input_queue.try_pop_up_to_with_headers(usize::MAX).await
+            };
+            let consumed_count = received_items.len();
+            let attempts = due.into_iter()
+                .map(|pending| (pending.input, pending.headers, pending.failed_attempt_count, pending.is_held))
+                .chain(received_items.into_iter().map(|(received, headers)| (received.into_shared(), headers, 0, false)));
+
+            let mut outcome = ProcessOutcome::consumed(consumed_count, 0);
+            for (input, headers, failed_attempt_count, is_held) in attempts {
+                if locked_pending.iter().any(|pending| pending.is_held) {
+                    // an earlier attempt within this invocation timed out while the wrapped ChainLink still holds its input
+                    locked_pending.push(PendingRetry {
+                        input,
+                        headers,
+                        failed_attempt_count,
+                        retry_at: now,
+                        is_held
+                    });
+                    continue;
+                }
+                match self.attempt(input.clone(), headers.clone(), is_held, &clock).await {
+                    Ok(attempt_outcome) => {
+                        outcome.merge_state(&attempt_outcome);
+                        while let Some((output, headers)) = self.chain_link.try_pop_item_with_headers().await {
+                            self.output_queue.push_with_headers(output, headers).await;
+                            outcome.produced_count += 1;
+                        }
+                    },
+                    Err(failure) => {
+                        let failed_attempt_count = failed_attempt_count + 1;
+                        // the wrapped ChainLink holds on to the input when the attempt timed out before it was taken from its queue
+                        let is_held = Arc::strong_count(&input) > 1;
+                        if failed_attempt_count < self.policy.max_attempts && (self.policy.is_retryable)(&failure) {
+                            locked_pending.push(PendingRetry {
+                                input,
+                                headers,
+                                failed_attempt_count,
+                                retry_at: clock.now() + self.policy.backoff(failed_attempt_count, scheduler.as_ref()),
+                                is_held
+                            });
+                        }
+                        else {
+                            self.exhaust(input, failure, failed_attempt_count).await;
+                            outcome.failed_count += 1;
+                        }
+                    }
+                }
+            }
+
+            outcome.is_busy |= !locked_pending.is_empty();
+            outcome
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::{future::{Future, poll_fn}, pin::Pin, sync::Mutex, task::Poll};
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom, thread_rng};
+
+pub type ScheduledFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+// decides where background processing runs and how random choices are made for chains and duplicates
+pub trait Scheduler: Send + Sync {
+    fn spawn(&self, future: ScheduledFuture);
+    // a random index in the range 0..count
+    fn choose_index(&self, count: usize) -> usize;
+    // every index in the range 0..count in a random order
+    fn shuffled_indexes(&self, count: usize) -> Vec<usize>;
+}
+
+// runs each spawned future on its own thread, choosing randomly using the thread's random number generator
+pub struct ThreadScheduler;
+
+impl Scheduler for ThreadScheduler {
+    fn spawn(&self, future: ScheduledFuture) {
+        std::thread::spawn(move || {
+            let tokio_runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_time()
+                .build()
+                .unwrap();
+
+            tokio_runtime.block_on(future);
+        });
+    }
+    fn choose_index(&self, count: usize) -> usize {
+        thread_rng().gen_range(0..count)
+    }
+    fn shuffled_indexes(&self, count: usize) -> Vec<usize> {
+        let mut indexes: Vec<usize> = (0..count).collect();
+        indexes.shuffle(&mut thread_rng());
+        indexes
+    }
+}
+
+// holds spawned futures until `run_until_idle` is awaited, interleaving them in an order determined by the seed
+pub struct DeterministicScheduler {
+    rng: Mutex<StdRng>,
+    pending: Mutex<Vec<ScheduledFuture>>
+}
+
+impl DeterministicScheduler {
+    pub fn new(seed: u64) -> Self {
+        DeterministicScheduler {
+            rng: Mutex::new(StdRng::seed_from_u64(seed)),
+            pending: Mutex::new(vec![])
+        }
+    }
+    pub fn pending_count(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+    // polls the spawned futures one step at a time, choosing the next one by the seed, until all have completed
+    pub async fn run_until_idle(&self) {
+        let mut running: Vec<ScheduledFuture> = vec![];
+        loop {
+            running.append(&mut self.pending.lock().unwrap());
+            if running.is_empty() {
+                return;
+            }
+            let index = self.choose_index(running.len());
+            let is_ready = poll_fn(|context| {
+                Poll::Ready(running[index].as_mut().poll(context).is_ready())
+            }).await;
+            if is_ready {
+                drop(running.remove(index));
+            }
+            else {
+                // let the runtime drive whatever the future is waiting on
+                tokio::task::yield_now().await;
+            }
+        }
+    }
+}
+
+impl Scheduler for DeterministicScheduler {
+    fn spawn(&self, future: ScheduledFuture) {
+        self.pending.lock().unwrap().push(future);
+    }
+    fn choose_index(&self, count: usize) -> usize {
+        self.rng.lock().unwrap().gen_range(0..count)
+    }
+    fn shuffled_indexes(&self, count: usize) -> Vec<usize> {
+        let mut indexes: Vec<usize> = (0..count).collect();
+        indexes.shuffle(&mut *self.rng.lock().unwrap());
+        indexes
+    }
+}
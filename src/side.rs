@@ -0,0 +1,188 @@
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use crate::{clock::Clock, dead_letter::{DeadLetter, DeadLetterQueue}, envelope::HeaderMap, framework::{ChainLink, Initializable, ProcessOutcome}, item::Item, schedule::Scheduler, watchdog::Watchdog};
+
+// collects the values a map block sends to a side output, which are queued once the map block returns
+pub struct SideSender<T> {
+    values: Arc<std::sync::Mutex<Vec<T>>>
+}
+
+impl<T> Clone for SideSender<T> {
+    fn clone(&self) -> Self {
+        SideSender {
+            values: self.values.clone()
+        }
+    }
+}
+
+impl<T> Default for SideSender<T> {
+    fn default() -> Self {
+        SideSender {
+            values: Arc::new(std::sync::Mutex::new(Vec::new()))
+        }
+    }
+}
+
+impl<T> SideSender<T> {
+    pub fn send(&self, value: T) {
+        self.values.lock().unwrap().push(value);
+    }
+    pub fn take(&self) -> Vec<T> {
+        std::mem::take(&mut *self.values.lock().unwrap())
+    }
+}
+
+// a named output of a ChainLink besides its `TOutput`, declared within chain_link! as `side: [rejected: String]`
+// each side output is a type named after the ChainLink and the side output, such as `ValidateRejected`
+#[async_trait::async_trait]
+pub trait SideOutput {
+    type TChainLink: ChainLink;
+    type TValue;
+
+    async fn try_pop_item(chain_link: &Self::TChainLink) -> Option<Item<Self::TValue>>;
+}
+
+pub struct SideChainInitializer<TChainLinkInitializer, TSideInitializer> {
+    pub chain_link: Arc<RwLock<TChainLinkInitializer>>,
+    pub side: Arc<RwLock<TSideInitializer>>
+}
+
+impl<TChainLinkInitializer, TSideInitializer> SideChainInitializer<TChainLinkInitializer, TSideInitializer> {
+    pub fn new(chain_link: TChainLinkInitializer, side: TSideInitializer) -> Self {
+        SideChainInitializer {
+            chain_link: Arc::new(RwLock::new(chain_link)),
+            side: Arc::new(RwLock::new(side))
+        }
+    }
+}
+
+// connects one side output of a ChainLink to another ChainLink, behaving otherwise as the ChainLink itself
+// the outputs of the side ChainLink are dropped, so it should store or report what it receives
+// within a chain!, name the type such as `SideChain<ValidateRejected, StoreRejected>`
+pub struct SideChain<TSide: SideOutput, TSideChainLink> {
+    chain_link: TSide::TChainLink,
+    side: TSideChainLink
+}
+
+impl<TSide: SideOutput, TSideChainLink> SideChain<TSide, TSideChainLink> {
+    pub fn new(chain_link: TSide::TChainLink, side: TSideChainLink) -> Self {
+        SideChain {
+            chain_link,
+            side
+        }
+    }
+    pub fn chain_link(&self) -> &TSide::TChainLink {
+        &self.chain_link
+    }
+    pub fn side(&self) -> &TSideChainLink {
+        &self.side
+    }
+}
+
+#[async_trait::async_trait]
+impl<TSide, TSideChainLink> Initializable for SideChain<TSide, TSideChainLink>
+where
+    TSide: SideOutput,
+    TSide::TChainLink: Initializable + Send,
+    TSideChainLink: Initializable + Send,
+    <TSide::TChainLink as Initializable>::TInitializer: Send + Sync,
+    TSideChainLink::TInitializer: Send + Sync
+{
+    type TInitializer = SideChainInitializer<<TSide::TChainLink as Initializable>::TInitializer, TSideChainLink::TInitializer>;
+
+    async fn initialize(initializer: Arc<RwLock<Self::TInitializer>>) -> Self {
+        let locked_initializer = initializer.read().await;
+        SideChain::new(
+            <TSide::TChainLink as Initializable>::initialize(locked_initializer.chain_link.clone()).await,
+            TSideChainLink::initialize(locked_initializer.side.clone()).await
+        )
+    }
+}
+
+#[async_trait::async_trait]
+impl<TSide, TSideChainLink> ChainLink for SideChain<TSide, TSideChainLink>
+where
+    TSide: SideOutput + Send + Sync,
+    TSide::TChainLink: Send + Sync,
+    TSide::TValue: Send + Sync,
+    <TSide::TChainLink as ChainLink>::TInput: Send + Sync,
+    <TSide::TChainLink as ChainLink>::TOutput: Send + Sync,
+    TSideChainLink: ChainLink<TInput = TSide::TValue> + Send + Sync,
+    TSideChainLink::TOutput: Send + Sync
+{
+    type TInput = <TSide::TChainLink as ChainLink>::TInput;
+    type TOutput = <TSide::TChainLink as ChainLink>::TOutput;
+
+    async fn push(&self, input: Arc<RwLock<Self::TInput>>) {
+        self.chain_link.push(input).await;
+    }
+    async fn push_raw(&self, input: Self::TInput) {
+        self.chain_link.push_raw(input).await;
+    }
+    async fn push_if_empty(&self, input: Arc<RwLock<Self::TInput>>) {
+        self.chain_link.push_if_empty(input).await;
+    }
+    async fn push_raw_if_empty(&self, input: Self::TInput) {
+        self.chain_link.push_raw_if_empty(input).await;
+    }
+    async fn push_replace(&self, input: Arc<RwLock<Self::TInput>>) {
+        self.chain_link.push_replace(input).await;
+    }
+    async fn push_raw_replace(&self, input: Self::TInput) {
+        self.chain_link.push_raw_replace(input).await;
+    }
+    async fn try_pop(&self) -> Option<Arc<RwLock<Self::TOutput>>> {
+        self.chain_link.try_pop().await
+    }
+    async fn push_item(&self, input: Item<Self::TInput>) {
+        self.chain_link.push_item(input).await;
+    }
+    async fn try_pop_item(&self) -> Option<Item<Self::TOutput>> {
+        self.chain_link.try_pop_item().await
+    }
+    async fn push_item_with_headers(&self, input: Item<Self::TInput>, headers: HeaderMap) {
+        self.chain_link.push_item_with_headers(input, headers).await;
+    }
+    async fn try_pop_item_with_headers(&self) -> Option<(Item<Self::TOutput>, HeaderMap)> {
+        self.chain_link.try_pop_item_with_headers().await
+    }
+    fn set_scheduler(&self, scheduler: Arc<dyn Scheduler>) {
+        self.chain_link.set_scheduler(scheduler.clone());
+        self.side.set_scheduler(scheduler);
+    }
+    fn set_clock(&self, clock: Clock) {
+        self.chain_link.set_clock(clock.clone());
+        self.side.set_clock(clock);
+    }
+    fn set_dead_letter_queue(&self, dead_letter_queue: DeadLetterQueue) {
+        self.chain_link.set_dead_letter_queue(dead_letter_queue.clone());
+        self.side.set_dead_letter_queue(dead_letter_queue);
+    }
+    fn set_watchdog(&self, watchdog: Watchdog) {
+        self.chain_link.set_watchdog(watchdog.clone());
+        self.side.set_watchdog(watchdog);
+    }
+    async fn redrive(&self, dead_letter: DeadLetter) -> Result<(), DeadLetter> {
+        match self.chain_link.redrive(dead_letter).await {
+            Ok(()) => Ok(()),
+            Err(dead_letter) => self.side.redrive(dead_letter).await
+        }
+    }
+    async fn process(&self) -> ProcessOutcome {
+        let mut outcome = self.chain_link.process().await;
+        while let Some(item) = TSide::try_pop_item(&self.chain_link).await {
+            self.side.push_item(item).await;
+        }
+
+        // the side ChainLink works through everything it was given so that nothing is left waiting unseen
+        loop {
+            let side_outcome = self.side.process().await;
+            while self.side.try_pop_item().await.is_some() { }
+            outcome.merge_state(&side_outcome);
+            if !side_outcome.is_consumed() && !side_outcome.is_produced() {
+                break;
+            }
+        }
+        outcome
+    }
+}
@@ -2,9 +2,30 @@
 mod test {
     use std::{sync::Arc, time::Duration};
     use tokio::sync::RwLock;
-    use crate::framework::ChainLink;
+    use crate::framework::{ChainLink, Initializable};
+    use crate::debugger::Debugger;
+    use crate::record::{Recorder, RecordedEvent, read_recording, replay};
+    use crate::schedule::DeterministicScheduler;
+    use crate::clock::{Clock, VirtualClock};
+    use crate::function::{AsyncFnLink, FnLink};
+    use crate::item::Item;
+    use crate::batch::{Batcher, BatcherInitializer, Unbatch, UnbatchInitializer};
+    use crate::window::{Window, WindowKind};
+    use crate::join::{Fairness, JoinInput, JoinKind, KeyedJoin, KeyedJoinInitializer};
+    use crate::port::{ToPort, ToPortInitializer};
+    use crate::side::{SideChain, SideChainInitializer};
+    use crate::dead_letter::DeadLetterQueue;
+    use crate::retry::{Retry, RetryInitializer, RetryPolicy};
+    use crate::watchdog::{Deadline, DeadlineInitializer, Watchdog};
+    use crate::envelope::{Envelope, Enveloped, HeaderMap};
+    use crate::expire::{Expire, Expired, Expiry};
+    use crate::conflate::{Conflate, ConflateInitializer, Debounce, ThrottleLatest};
+    use crate::rate::{RateLimit, RateLimitInitializer, RateLimiter};
+    use crate::circuit::{CircuitBreaker, CircuitBreakerFallback, CircuitBreakerPolicy, CircuitState, OpenBehavior};
+    use futures::future::BoxFuture;
+    use crate::testing::{assert_golden, assert_idempotent, assert_outputs, collect_until_idle};
 
-    #[derive(Debug, PartialEq)]
+    #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
     pub enum SomeInput {
         First,
         Second
@@ -77,6 +98,8 @@ mod test {
         ]: (all join)
     );
 
+    duplicate!(DuplicateTest, SomeInput => String, TestChainLink, join);
+
     chain_link!(StringToInt, input: String => i32, {
         match input.received {
             Some(received) => {
@@ -140,6 +163,31 @@ mod test {
         }
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn process_outcome_counts_consumed_and_produced() {
+        let test = TestChainLink::new_raw(
+            TestChainLinkInitializer { }
+        ).await;
+        assert!(test.process().await.is_idle());
+        test.push_raw(SomeInput::First).await;
+        let outcome = test.process().await;
+        assert_eq!(1, outcome.consumed_count);
+        assert_eq!(1, outcome.produced_count);
+
+        let split = SplitMergeTwoChainLinks::new_raw(
+            SplitMergeTwoChainLinksInitializer::new(
+                StringToIntInitializer { },
+                StringPrintInitializer { }
+            )
+        ).await;
+        split.push_raw(String::from("test")).await;
+        let outcome = split.process().await;
+        assert_eq!(2, outcome.consumed_count);
+        assert_eq!(2, outcome.produced_count);
+        assert_eq!(vec![0, 1], outcome.branch_indexes);
+        assert!(!outcome.is_deferred);
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn chain_link_using_initializer() {
         let test = HardCoded::new_raw(
@@ -205,6 +253,1750 @@ mod test {
         }
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn debugger_steps_between_stages() {
+        let triple_test = Arc::new(TripleTest::new_raw(
+            TripleTestInitializer::new(
+                TestChainLinkInitializer { },
+                StringToSomeInputInitializer { },
+                TestChainLinkInitializer { }
+            )
+        ).await);
+        let debugger = Arc::new(Debugger::new());
+        triple_test.attach_debugger(debugger.clone());
+        triple_test.push_raw(SomeInput::Second).await;
+
+        let processing_triple_test = triple_test.clone();
+        let processing = tokio::spawn(async move {
+            processing_triple_test.process().await
+        });
+
+        let stage = debugger.wait_for_pause().await;
+        assert_eq!(0, stage.stage_index);
+        assert_eq!("TestChainLink", stage.stage_name);
+        assert_eq!("second", stage.value::<String>().unwrap().read().await.as_str());
+        assert!(stage.value::<SomeInput>().is_none());
+        debugger.step();
+
+        let stage = debugger.wait_for_pause().await;
+        assert_eq!(1, stage.stage_index);
+        assert_eq!(SomeInput::Second, *stage.value::<SomeInput>().unwrap().read().await);
+        debugger.resume();
+
+        assert!(processing.await.unwrap().is_produced());
+        assert_eq!("second", triple_test.try_pop().await.unwrap().read().await.as_str());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn debugger_breaks_on_predicate() {
+        let triple_test = Arc::new(TripleTest::new_raw(
+            TripleTestInitializer::new(
+                TestChainLinkInitializer { },
+                StringToSomeInputInitializer { },
+                TestChainLinkInitializer { }
+            )
+        ).await);
+        let debugger = Arc::new(Debugger::new());
+        debugger.break_when(|stage| stage.stage_index == 1);
+        debugger.resume();
+        triple_test.attach_debugger(debugger.clone());
+        triple_test.push_raw(SomeInput::First).await;
+
+        let processing_triple_test = triple_test.clone();
+        let processing = tokio::spawn(async move {
+            processing_triple_test.process().await
+        });
+
+        let stage = debugger.wait_for_pause().await;
+        assert_eq!(1, stage.stage_index);
+        assert_eq!("StringToSomeInput", stage.stage_name);
+        debugger.resume();
+
+        assert!(processing.await.unwrap().is_produced());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn record_and_replay_chain() {
+        let recording = tempfile::NamedTempFile::new().unwrap();
+        let recorder = Recorder::new(
+            TripleTest::new_raw(
+                TripleTestInitializer::new(
+                    TestChainLinkInitializer { },
+                    StringToSomeInputInitializer { },
+                    TestChainLinkInitializer { }
+                )
+            ).await,
+            recording.path()
+        ).unwrap();
+        recorder.push_raw(SomeInput::First).await;
+        recorder.push_raw(SomeInput::Second).await;
+        recorder.process().await;
+        recorder.process().await;
+        assert!(recorder.try_pop().await.is_some());
+        assert!(recorder.try_pop().await.is_some());
+
+        let entries = read_recording::<SomeInput, String>(recording.path()).unwrap();
+        assert_eq!(4, entries.len());
+        assert_eq!(RecordedEvent::Push(SomeInput::First), entries[0].event);
+        assert_eq!(RecordedEvent::Pop(String::from("second")), entries[3].event);
+
+        let fresh = TripleTest::new_raw(
+            TripleTestInitializer::new(
+                TestChainLinkInitializer { },
+                StringToSomeInputInitializer { },
+                TestChainLinkInitializer { }
+            )
+        ).await;
+        let differences = replay(&fresh, recording.path()).await.unwrap();
+        assert!(differences.is_empty());
+
+        // a duplicate sends each input to every inner ChainLink, so the replay finds the extra outputs
+        let duplicate = DuplicateTest::new_raw(
+            DuplicateTestInitializer::new(2, TestChainLinkInitializer { })
+        ).await;
+        let differences = replay(&duplicate, recording.path()).await.unwrap();
+        assert_eq!(3, differences.len());
+        assert_eq!(1, differences[0].pop_index);
+        assert_eq!(Some(String::from("first")), differences[0].actual);
+        assert_eq!(None, differences[2].expected);
+
+        // if-empty and latest-wins pushes are replayed as they were made
+        let recording = tempfile::NamedTempFile::new().unwrap();
+        let recorder = Recorder::new(
+            TestChainLink::new_raw(TestChainLinkInitializer { }).await,
+            recording.path()
+        ).unwrap();
+        recorder.push_raw(SomeInput::First).await;
+        recorder.push_raw_replace(SomeInput::Second).await;
+        recorder.push_raw_if_empty(SomeInput::First).await;
+        recorder.process().await;
+        assert_eq!("second", *recorder.try_pop().await.unwrap().read().await);
+        assert!(recorder.take_error().is_none());
+
+        let entries = read_recording::<SomeInput, String>(recording.path()).unwrap();
+        assert_eq!(RecordedEvent::PushReplace(SomeInput::Second), entries[1].event);
+        assert_eq!(RecordedEvent::PushIfEmpty(SomeInput::First), entries[2].event);
+        let fresh = TestChainLink::new_raw(TestChainLinkInitializer { }).await;
+        assert!(replay(&fresh, recording.path()).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn deterministic_scheduler_runs_free_processing_on_demand() {
+        chain!(SplitFreeTest, String => i32, [StringToInt, StringPrint]: (all free));
+
+        let split = SplitFreeTest::new_raw(
+            SplitFreeTestInitializer::new(
+                StringToIntInitializer { },
+                StringPrintInitializer { }
+            )
+        ).await;
+        let scheduler = Arc::new(DeterministicScheduler::new(7));
+        split.set_scheduler(scheduler.clone());
+        split.push_raw(String::from("test")).await;
+
+        assert!(split.process().await.is_deferred);
+        assert_eq!(2, scheduler.pending_count());
+        assert!(split.try_pop().await.is_none());
+
+        scheduler.run_until_idle().await;
+        assert_eq!(0, scheduler.pending_count());
+        assert!(split.try_pop().await.is_some());
+        assert!(split.try_pop().await.is_some());
+        assert!(split.try_pop().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn deterministic_scheduler_repeats_random_choices_for_seed() {
+        chain!(SplitRandomTest, String => i32, [StringToInt, StringPrint, StringToInt]: (random join));
+
+        async fn get_branch_indexes(seed: u64) -> Vec<usize> {
+            let split = SplitRandomTest::new_raw(
+                SplitRandomTestInitializer::new(
+                    StringToIntInitializer { },
+                    StringPrintInitializer { },
+                    StringToIntInitializer { }
+                )
+            ).await;
+            split.set_scheduler(Arc::new(DeterministicScheduler::new(seed)));
+            let mut branch_indexes = vec![];
+            for _ in 0..10 {
+                split.push_raw(String::from("test")).await;
+                branch_indexes.extend(split.process().await.branch_indexes);
+            }
+            branch_indexes
+        }
+
+        let branch_indexes = get_branch_indexes(42).await;
+        assert_eq!(10, branch_indexes.len());
+        assert_eq!(branch_indexes, get_branch_indexes(42).await);
+    }
+
+    #[tokio::test]
+    async fn deterministic_scheduler_locks_unique_duplicates_until_processed() {
+        chain_link!(IsUppercase, input: String => bool, {
+            match input.received {
+                Some(text) => {
+                    tokio::task::yield_now().await;
+                    let text = text.read().await;
+                    Some(!text.is_empty() && !text.chars().any(|c| c.is_lowercase()))
+                },
+                None => None
+            }
+        });
+
+        duplicate!(UniqueIsUppercase, String => bool, IsUppercase, unique);
+
+        let dup = UniqueIsUppercase::new_raw(UniqueIsUppercaseInitializer::new(2, IsUppercaseInitializer { })).await;
+        let scheduler = Arc::new(DeterministicScheduler::new(7));
+        dup.set_scheduler(scheduler.clone());
+        dup.push_raw(String::from("test")).await;
+        dup.push_raw(String::from("TEST")).await;
+
+        // each inner ChainLink stays locked until its background processing has run
+        assert!(dup.process().await.is_deferred);
+        let outcome = dup.process().await;
+        assert!(outcome.is_busy);
+        assert!(!outcome.is_deferred);
+        assert_eq!(2, scheduler.pending_count());
+        scheduler.run_until_idle().await;
+        assert!(dup.try_pop().await.is_some());
+        assert!(dup.try_pop().await.is_some());
+
+        // once unlocked, each inner ChainLink processes its second input
+        assert!(!dup.process().await.is_busy);
+        scheduler.run_until_idle().await;
+        assert!(dup.try_pop().await.is_some());
+        assert!(dup.try_pop().await.is_some());
+        assert!(dup.try_pop().await.is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn testing_helpers_feed_and_assert() {
+        let triple_test = TripleTest::new_raw(
+            TripleTestInitializer::new(
+                TestChainLinkInitializer { },
+                StringToSomeInputInitializer { },
+                TestChainLinkInitializer { }
+            )
+        ).await;
+        assert_outputs(&triple_test, vec![SomeInput::First, SomeInput::Second, SomeInput::First], vec![
+            String::from("first"),
+            String::from("second"),
+            String::from("first")
+        ]).await;
+        assert!(collect_until_idle(&triple_test).await.is_empty());
+
+        let outputs = assert_idempotent::<StringToInt>(Arc::new(RwLock::new(StringToIntInitializer { })), vec![String::from("test"), String::from("other")]).await;
+        assert_eq!(vec![1, 2], outputs);
+
+        // a ChainLink that keeps state in its initializer produces something else the second time
+        chain_link!(CountCalls => (calls: std::sync::atomic::AtomicUsize), input: String => usize, {
+            match input.received {
+                Some(_) => Some(input.initializer.read().await.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst)),
+                None => None
+            }
+        });
+        let is_repeat_caught = futures::FutureExt::catch_unwind(std::panic::AssertUnwindSafe(assert_idempotent::<CountCalls>(Arc::new(RwLock::new(CountCallsInitializer {
+            calls: std::sync::atomic::AtomicUsize::new(0)
+        })), vec![String::from("test")]))).await.is_err();
+        assert!(is_repeat_caught);
+
+        let golden_directory = tempfile::tempdir().unwrap();
+        let golden_path = golden_directory.path().join("string_to_int.golden");
+        assert_golden(&outputs, &golden_path);
+        assert_golden(&outputs, &golden_path);
+        let is_mismatch_caught = std::panic::catch_unwind(|| {
+            assert_golden(&[3], &golden_path);
+        }).is_err();
+        assert!(is_mismatch_caught);
+    }
+
+    #[cfg(feature = "proptest")]
+    #[test]
+    fn chain_links_satisfy_laws() {
+        use crate::laws::{Composed, check_deterministic, check_equivalent, check_never_panics, check_output_count_equals_input_count};
+
+        chain_link!(Reverse, input: String => String, {
+            match input.received {
+                Some(text) => Some(text.read().await.chars().rev().collect()),
+                None => None
+            }
+        });
+
+        chain!(ReverseToInt, String => i32, [Reverse => StringToInt]: (all join));
+
+        check_never_panics(|| StringToInt::new_raw(StringToIntInitializer { }));
+        check_output_count_equals_input_count(|| StringToInt::new_raw(StringToIntInitializer { }));
+        check_deterministic(|| ReverseToInt::new_raw(ReverseToIntInitializer::new(ReverseInitializer { }, StringToIntInitializer { })));
+        check_equivalent(
+            || ReverseToInt::new_raw(ReverseToIntInitializer::new(ReverseInitializer { }, StringToIntInitializer { })),
+            || async {
+                Composed::new(
+                    Reverse::new_raw(ReverseInitializer { }).await,
+                    StringToInt::new_raw(StringToIntInitializer { }).await
+                )
+            }
+        );
+
+        // every output of a flat ChainLink is handed along at once, as within a chain!
+        chain_link!(SplitChars, input: String => String, flat, {
+            match input.received {
+                Some(received) => received.read().await.chars().map(String::from).collect::<Vec<String>>(),
+                None => vec![]
+            }
+        });
+        chain_link!(Shout, input: String => String, concurrency = 4, {
+            match input.received {
+                Some(received) => Some(received.read().await.to_uppercase()),
+                None => None
+            }
+        });
+
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let produced_count = runtime.block_on(async {
+            let composed = Composed::new(SplitChars::new_raw(SplitCharsInitializer { }).await, Shout::new_raw(ShoutInitializer { }).await);
+            composed.push_raw(String::from("abc")).await;
+            composed.process().await.produced_count
+        });
+        assert_eq!(3, produced_count);
+
+        // any string other than "first" or "second" panics, so the failure shrinks to a single empty string
+        let failure = std::panic::catch_unwind(|| {
+            check_never_panics(|| StringToSomeInput::new_raw(StringToSomeInputInitializer { }));
+        }).expect_err("The law should fail for unexpected strings.");
+        let message = failure.downcast_ref::<String>().expect("The failure should be a message.");
+        let minimal_failing_input: String = message
+            .split("minimal failing input:")
+            .nth(1)
+            .expect("The failure should include the minimal failing input.")
+            .split_whitespace()
+            .collect();
+        assert_eq!("[\"\",]", minimal_failing_input, "{}", message);
+    }
+
+    #[tokio::test]
+    async fn virtual_clock_drives_background_sleeps() {
+        chain_link!(SlowEcho, input: String => String, {
+            match input.received {
+                Some(text) => {
+                    input.clock.sleep(Duration::from_secs(60)).await;
+                    Some(text.read().await.clone())
+                },
+                None => None
+            }
+        });
+
+        chain!(SlowEchoFree, String => String, [SlowEcho]: (all free));
+
+        let slow_echo = SlowEchoFree::new_raw(SlowEchoFreeInitializer::new(SlowEchoInitializer { })).await;
+        let scheduler = Arc::new(DeterministicScheduler::new(0));
+        let virtual_clock = VirtualClock::new();
+        slow_echo.set_scheduler(scheduler.clone());
+        slow_echo.set_clock(Clock::from_virtual(virtual_clock.clone()));
+        slow_echo.push_raw(String::from("echo")).await;
+        assert!(slow_echo.process().await.is_deferred);
+
+        let started = std::time::Instant::now();
+        futures::join!(scheduler.run_until_idle(), async {
+            virtual_clock.advance(Duration::from_secs(60));
+        });
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert_eq!(Duration::from_secs(60), virtual_clock.elapsed());
+        assert_eq!("echo", slow_echo.try_pop().await.unwrap().read().await.as_str());
+
+        let clock = Clock::from_virtual(virtual_clock.clone());
+        let now = clock.now();
+        virtual_clock.advance(Duration::from_millis(1500));
+        assert_eq!(Duration::from_millis(1500), clock.elapsed_since(now));
+    }
+
+    #[tokio::test]
+    async fn owned_items_are_only_shared_for_parallel_chain_links() {
+        chain_link!(Length, input: String => usize, owned, {
+            match input.received {
+                Some(text) => Some(text.read().await.len()),
+                None => None
+            }
+        });
+
+        chain_link!(Doubled, input: usize => usize, owned, {
+            match input.received {
+                Some(length) => match length.try_into_owned() {
+                    Ok(length) => Some(length * 2),
+                    Err(_) => panic!("The length should only have one consumer.")
+                },
+                None => None
+            }
+        });
+
+        chain_link!(IsShared, input: String => bool, owned, {
+            input.received.map(|text| text.is_shared())
+        });
+
+        chain!(LengthDoubled, String => usize, [Length => Doubled]: (all join));
+        chain!(SingleIsShared, String => bool, [IsShared]: (all join));
+        chain!(ParallelIsShared, String => bool, [IsShared, IsShared]: (all join));
+
+        let length_doubled = LengthDoubled::new_raw(LengthDoubledInitializer::new(LengthInitializer { }, DoubledInitializer { })).await;
+        length_doubled.push_raw(String::from("four")).await;
+        length_doubled.process().await;
+        let output = length_doubled.try_pop_item().await.unwrap();
+        assert!(!output.is_shared());
+        assert_eq!(8, output.into_owned().await);
+
+        let single_is_shared = SingleIsShared::new_raw(SingleIsSharedInitializer::new(IsSharedInitializer { })).await;
+        assert_outputs(&single_is_shared, vec![String::from("test")], vec![false]).await;
+
+        let parallel_is_shared = ParallelIsShared::new_raw(ParallelIsSharedInitializer::new(IsSharedInitializer { }, IsSharedInitializer { })).await;
+        assert_outputs(&parallel_is_shared, vec![String::from("test")], vec![true, true]).await;
+    }
+
+    #[tokio::test]
+    async fn function_links_stand_alone_and_compose() {
+        type Parse = FnLink<String, i32, fn(&String) -> Option<i32>>;
+        type Increment = AsyncFnLink<i32, i32, fn(Item<i32>) -> BoxFuture<'static, Option<i32>>>;
+
+        chain!(ParseIncrement, String => i32, [Parse => Increment]: (all join));
+        duplicate!(ParseTwice, String => i32, Parse, join);
+
+        let parse = FnLink::new(|text: &String| text.parse::<i32>().ok());
+        assert_outputs(&parse, vec![String::from("1"), String::from("x"), String::from("3")], vec![1, 3]).await;
+
+        let increment = AsyncFnLink::new(|value: Item<i32>| async move {
+            Some(value.into_owned().await + 1)
+        });
+        assert_outputs(&increment, vec![1, 2], vec![2, 3]).await;
+
+        let parse_increment = ParseIncrement::new_raw(ParseIncrementInitializer::new(
+            |text| text.parse().ok(),
+            |value| Box::pin(async move {
+                Some(value.into_owned().await + 1)
+            })
+        )).await;
+        assert_outputs(&parse_increment, vec![String::from("41")], vec![42]).await;
+
+        let parse_twice = ParseTwice::new_raw(ParseTwiceInitializer::new(2, |text| text.parse().ok())).await;
+        assert_outputs(&parse_twice, vec![String::from("7")], vec![7, 7]).await;
+    }
+
+    #[tokio::test]
+    async fn blocking_map_blocks_receive_read_input() {
+        chain_link!(RepeatText => (times: usize), input: String => String, blocking, {
+            input.received.map(|text| text.repeat(input.initializer.times))
+        });
+
+        chain!(RepeatTextTwice, String => String, [RepeatText => RepeatText]: (all join));
+
+        let repeat_text = RepeatText::new_raw(RepeatTextInitializer { times: 2 }).await;
+        assert_outputs(&repeat_text, vec![String::from("ab"), String::from("c")], vec![String::from("abab"), String::from("cc")]).await;
+
+        let repeat_text_twice = RepeatTextTwice::new_raw(RepeatTextTwiceInitializer::new(RepeatTextInitializer { times: 2 }, RepeatTextInitializer { times: 3 })).await;
+        assert_outputs(&repeat_text_twice, vec![String::from("x")], vec![String::from("xxxxxx")]).await;
+
+        // without an input the map block is not run, so the initializer is not read
+        let initializer = Arc::new(RwLock::new(RepeatTextInitializer { times: 2 }));
+        let repeat_text = RepeatText::new(initializer.clone()).await;
+        let _write_guard = initializer.write().await;
+        let outcome = tokio::time::timeout(Duration::from_secs(1), repeat_text.process()).await.expect("an idle process should not wait for the initializer");
+        assert!(outcome.is_idle());
+    }
+
+    #[tokio::test]
+    async fn concurrent_map_blocks_share_one_process() {
+        chain_link!(SleepUnordered, input: u64 => u64, concurrency = 3, {
+            match input.received {
+                Some(seconds) => {
+                    let seconds = *seconds.read().await;
+                    input.clock.sleep(Duration::from_secs(seconds)).await;
+                    Some(seconds)
+                },
+                None => None
+            }
+        });
+
+        chain_link!(SleepOrdered, input: u64 => u64, owned, concurrency = 3, ordered, {
+            match input.received {
+                Some(seconds) => {
+                    let seconds = *seconds.read().await;
+                    input.clock.sleep(Duration::from_secs(seconds)).await;
+                    Some(seconds)
+                },
+                None => None
+            }
+        });
+
+        // the virtual clock only reaches the longest sleep, so every input must have been sleeping at the same time
+        async fn process_while_advancing(chain_link: &impl ChainLink, virtual_clock: &VirtualClock) -> crate::framework::ProcessOutcome {
+            let (outcome, _) = futures::join!(chain_link.process(), async {
+                for _ in 0..3 {
+                    tokio::task::yield_now().await;
+                    virtual_clock.advance(Duration::from_secs(1));
+                }
+            });
+            outcome
+        }
+
+        let virtual_clock = VirtualClock::new();
+        let sleep_unordered = SleepUnordered::new_raw(SleepUnorderedInitializer { }).await;
+        sleep_unordered.set_clock(Clock::from_virtual(virtual_clock.clone()));
+        for seconds in [3, 1, 2, 0] {
+            sleep_unordered.push_raw(seconds).await;
+        }
+        let outcome = tokio::time::timeout(Duration::from_secs(5), process_while_advancing(&sleep_unordered, &virtual_clock)).await.unwrap();
+        assert_eq!(crate::framework::ProcessOutcome::consumed(3, 3), outcome);
+        assert_outputs(&sleep_unordered, vec![], vec![1, 2, 3, 0]).await;
+
+        let virtual_clock = VirtualClock::new();
+        let sleep_ordered = SleepOrdered::new_raw(SleepOrderedInitializer { }).await;
+        sleep_ordered.set_clock(Clock::from_virtual(virtual_clock.clone()));
+        for seconds in [3, 1, 2] {
+            sleep_ordered.push_raw(seconds).await;
+        }
+        let outcome = tokio::time::timeout(Duration::from_secs(5), process_while_advancing(&sleep_ordered, &virtual_clock)).await.unwrap();
+        assert_eq!(crate::framework::ProcessOutcome::consumed(3, 3), outcome);
+        assert_outputs(&sleep_ordered, vec![], vec![3, 1, 2]).await;
+    }
+
+    #[tokio::test]
+    async fn batcher_produces_full_and_timed_out_batches() {
+        type BatchNumbers = Batcher<i32>;
+        type UnbatchNumbers = Unbatch<i32>;
+
+        chain!(BatchUnbatch, i32 => i32, [BatchNumbers => UnbatchNumbers]: (all join));
+
+        let virtual_clock = VirtualClock::new();
+        let batcher = Batcher::new(2, Duration::from_secs(1));
+        batcher.set_clock(Clock::from_virtual(virtual_clock.clone()));
+        for number in 1..=3 {
+            batcher.push_raw(number).await;
+        }
+        let outcome = batcher.process().await;
+        assert_eq!((3, 1, true), (outcome.consumed_count, outcome.produced_count, outcome.is_busy));
+        assert_eq!(vec![1, 2], *batcher.try_pop().await.unwrap().read().await);
+
+        // the partial batch waits for the timeout
+        assert!(batcher.process().await.is_busy);
+        assert!(batcher.try_pop().await.is_none());
+        virtual_clock.advance(Duration::from_secs(1));
+        let outcome = batcher.process().await;
+        assert_eq!((0, 1, false), (outcome.consumed_count, outcome.produced_count, outcome.is_busy));
+        assert_eq!(vec![3], *batcher.try_pop().await.unwrap().read().await);
+        assert!(batcher.process().await.is_idle());
+
+        // an item left over after a full batch keeps waiting from when it was pushed
+        for number in 4..=6 {
+            batcher.push_raw(number).await;
+        }
+        virtual_clock.advance(Duration::from_millis(600));
+        let outcome = batcher.process().await;
+        assert_eq!((3, 1, true), (outcome.consumed_count, outcome.produced_count, outcome.is_busy));
+        assert_eq!(vec![4, 5], *batcher.try_pop().await.unwrap().read().await);
+        virtual_clock.advance(Duration::from_millis(400));
+        let outcome = batcher.process().await;
+        assert_eq!((0, 1, false), (outcome.consumed_count, outcome.produced_count, outcome.is_busy));
+        assert_eq!(vec![6], *batcher.try_pop().await.unwrap().read().await);
+
+        let batch_unbatch = BatchUnbatch::new_raw(BatchUnbatchInitializer::new(
+            BatcherInitializer {
+                size: 2,
+                timeout: Duration::ZERO
+            },
+            UnbatchInitializer
+        )).await;
+        assert_outputs(&batch_unbatch, vec![1, 2, 3], vec![1, 2, 3]).await;
+    }
+
+    #[tokio::test]
+    async fn windows_reduce_by_count_time_and_session() {
+        type Sum = Window<i32, i32, fn(&[i32]) -> i32>;
+
+        fn sum(numbers: &[i32]) -> i32 {
+            numbers.iter().sum()
+        }
+
+        async fn process_and_pop(window: &Sum) -> Vec<i32> {
+            window.process().await;
+            let mut outputs = vec![];
+            while let Some(output) = window.try_pop().await {
+                outputs.push(*output.read().await);
+            }
+            outputs
+        }
+
+        let tumbling_count = Sum::new(WindowKind::TumblingCount(2), sum);
+        assert_outputs(&tumbling_count, vec![1, 2, 3, 4, 5], vec![3, 7]).await;
+
+        let sliding_count = Sum::new(WindowKind::SlidingCount { size: 3, step: 1 }, sum);
+        assert_outputs(&sliding_count, vec![1, 2, 3, 4], vec![6, 9]).await;
+
+        let delimited = Sum::new(WindowKind::Delimited(|number| *number == 0), sum);
+        assert_outputs(&delimited, vec![1, 2, 0, 3, 0], vec![3, 3]).await;
+
+        let virtual_clock = VirtualClock::new();
+        let tumbling_time = Sum::new(WindowKind::TumblingTime(Duration::from_secs(2)), sum);
+        tumbling_time.set_clock(Clock::from_virtual(virtual_clock.clone()));
+        tumbling_time.push_raw(1).await;
+        assert!(tumbling_time.process().await.is_busy);
+        virtual_clock.advance(Duration::from_secs(1));
+        tumbling_time.push_raw(2).await;
+        assert_eq!(Vec::<i32>::new(), process_and_pop(&tumbling_time).await);
+        virtual_clock.advance(Duration::from_secs(1));
+        assert_eq!(vec![3], process_and_pop(&tumbling_time).await);
+        assert!(tumbling_time.process().await.is_idle());
+
+        let virtual_clock = VirtualClock::new();
+        let sliding_time = Sum::new(WindowKind::SlidingTime { size: Duration::from_secs(2), step: Duration::from_secs(1) }, sum);
+        sliding_time.set_clock(Clock::from_virtual(virtual_clock.clone()));
+        sliding_time.push_raw(1).await;
+        assert_eq!(Vec::<i32>::new(), process_and_pop(&sliding_time).await);
+        virtual_clock.advance(Duration::from_secs(1));
+        sliding_time.push_raw(2).await;
+        assert_eq!(vec![3], process_and_pop(&sliding_time).await);
+        virtual_clock.advance(Duration::from_secs(1));
+        assert_eq!(vec![2], process_and_pop(&sliding_time).await);
+        assert!(sliding_time.process().await.is_idle());
+
+        let virtual_clock = VirtualClock::new();
+        let session = Sum::new(WindowKind::Session { gap: Duration::from_secs(2) }, sum);
+        session.set_clock(Clock::from_virtual(virtual_clock.clone()));
+        session.push_raw(1).await;
+        assert_eq!(Vec::<i32>::new(), process_and_pop(&session).await);
+        virtual_clock.advance(Duration::from_secs(1));
+        session.push_raw(2).await;
+        assert_eq!(Vec::<i32>::new(), process_and_pop(&session).await);
+        virtual_clock.advance(Duration::from_secs(1));
+        assert_eq!(Vec::<i32>::new(), process_and_pop(&session).await);
+        virtual_clock.advance(Duration::from_secs(1));
+        assert_eq!(vec![3], process_and_pop(&session).await);
+        assert!(session.process().await.is_idle());
+
+        // time is measured from when an input is pushed, and a waiting window reports when it closes
+        let virtual_clock = VirtualClock::new();
+        let tumbling_time = Sum::new(WindowKind::TumblingTime(Duration::from_secs(2)), sum);
+        tumbling_time.set_clock(Clock::from_virtual(virtual_clock.clone()));
+        tumbling_time.push_raw(1).await;
+        virtual_clock.advance(Duration::from_secs(1));
+        assert_eq!(Some(Duration::from_secs(1)), tumbling_time.process().await.due_in);
+        virtual_clock.advance(Duration::from_secs(1));
+        assert_eq!(vec![1], process_and_pop(&tumbling_time).await);
+
+        let virtual_clock = VirtualClock::new();
+        let session = Sum::new(WindowKind::Session { gap: Duration::from_secs(2) }, sum);
+        session.set_clock(Clock::from_virtual(virtual_clock.clone()));
+        session.push_raw(1).await;
+        virtual_clock.advance(Duration::from_secs(2));
+        assert_eq!(vec![1], process_and_pop(&session).await);
+
+        let virtual_clock = VirtualClock::new();
+        let sliding_time = Sum::new(WindowKind::SlidingTime { size: Duration::from_secs(2), step: Duration::from_secs(1) }, sum);
+        sliding_time.set_clock(Clock::from_virtual(virtual_clock.clone()));
+        sliding_time.push_raw(1).await;
+        assert_eq!(Some(Duration::from_secs(1)), sliding_time.process().await.due_in);
+    }
+
+    #[tokio::test]
+    async fn keyed_join_pairs_matching_keys() {
+        type PairByParity = KeyedJoin<i32, String, bool>;
+
+        fn new_initializer(kind: JoinKind, fairness: Fairness, ttl: Option<Duration>) -> KeyedJoinInitializer<i32, String, bool> {
+            KeyedJoinInitializer {
+                left_key: |number| number % 2 == 0,
+                right_key: |text| text.len() % 2 == 0,
+                kind,
+                fairness,
+                ttl
+            }
+        }
+
+        let oldest = PairByParity::new(new_initializer(JoinKind::Inner, Fairness::Oldest, None));
+        assert_outputs(&oldest, vec![
+            JoinInput::Left(1),
+            JoinInput::Left(2),
+            JoinInput::Left(3),
+            JoinInput::Right(String::from("a")),
+            JoinInput::Right(String::from("bb")),
+            JoinInput::Right(String::from("cc"))
+        ], vec![
+            (1, Some(String::from("a"))),
+            (2, Some(String::from("bb")))
+        ]).await;
+
+        let newest = PairByParity::new(new_initializer(JoinKind::Inner, Fairness::Newest, None));
+        assert_outputs(&newest, vec![
+            JoinInput::Left(1),
+            JoinInput::Left(3),
+            JoinInput::Right(String::from("a"))
+        ], vec![
+            (3, Some(String::from("a")))
+        ]).await;
+
+        let virtual_clock = VirtualClock::new();
+        let left = PairByParity::new(new_initializer(JoinKind::Left, Fairness::Oldest, Some(Duration::from_secs(5))));
+        left.set_clock(Clock::from_virtual(virtual_clock.clone()));
+        left.push_raw(JoinInput::Left(1)).await;
+        left.push_raw(JoinInput::Right(String::from("bb"))).await;
+        assert!(left.process().await.is_busy);
+        virtual_clock.advance(Duration::from_secs(5));
+        let outcome = left.process().await;
+        assert_eq!((1, false), (outcome.produced_count, outcome.is_busy));
+        assert_eq!((1, None), *left.try_pop().await.unwrap().read().await);
+
+        // the expired right input is no longer available to match
+        left.push_raw(JoinInput::Left(2)).await;
+        let outcome = left.process().await;
+        assert_eq!((0, true, Some(Duration::from_secs(5))), (outcome.produced_count, outcome.is_busy, outcome.due_in));
+        virtual_clock.advance(Duration::from_secs(2));
+        assert_eq!(Some(Duration::from_secs(3)), left.process().await.due_in);
+
+        // the ttl is measured from when an input is pushed rather than from when it is processed
+        left.push_raw(JoinInput::Left(4)).await;
+        virtual_clock.advance(Duration::from_secs(3));
+        let outcome = left.process().await;
+        assert_eq!((1, 1, Some(Duration::from_secs(2))), (outcome.consumed_count, outcome.produced_count, outcome.due_in));
+        assert_eq!((2, None), *left.try_pop().await.unwrap().read().await);
+
+        // drivers wait until an unmatched left input expires, however long the ttl
+        let real_time = PairByParity::new(new_initializer(JoinKind::Left, Fairness::Oldest, Some(Duration::from_secs(2))));
+        assert_outputs(&real_time, vec![JoinInput::Left(1)], vec![(1, None)]).await;
+
+        let without_ttl = std::panic::catch_unwind(|| PairByParity::new(new_initializer(JoinKind::Left, Fairness::Oldest, None)));
+        assert!(without_ttl.is_err());
+    }
+
+    #[tokio::test]
+    async fn input_ports_are_fed_by_different_chain_links() {
+        chain_link!(Describe, input: [count: i32, name: String] => String, {
+            match input.received {
+                Some(received) => {
+                    Some(match &*received.read().await {
+                        DescribePortInput::Count(count) => format!("count {}", count),
+                        DescribePortInput::Name(name) => format!("name {}", name)
+                    })
+                },
+                None => None
+            }
+        });
+        chain_link!(Shout, input: String => String, {
+            match input.received {
+                Some(received) => Some(received.read().await.to_uppercase()),
+                None => None
+            }
+        });
+
+        type CountToDescribe = ToPort<DescribeCount>;
+        type NameToDescribe = ToPort<DescribeName>;
+
+        chain!(ToDescribePorts, String => DescribePortInput, [StringToInt => CountToDescribe, Shout => NameToDescribe]: (all join));
+        chain!(CountAndName, String => String, [ToDescribePorts => Describe]: (all join));
+
+        let describe = Describe::new_raw(DescribeInitializer { }).await;
+        describe.push_to::<DescribeName>(String::from("first")).await;
+        describe.push_to::<DescribeCount>(2).await;
+        assert_eq!(vec![String::from("name first"), String::from("count 2")], collect_until_idle(&describe).await);
+
+        let count_and_name = CountAndName::new_raw(CountAndNameInitializer::new(
+            ToDescribePortsInitializer::new(
+                StringToIntInitializer { },
+                ToPortInitializer,
+                ShoutInitializer { },
+                ToPortInitializer
+            ),
+            DescribeInitializer { }
+        )).await;
+        count_and_name.push_raw(String::from("test")).await;
+        let mut outputs = collect_until_idle(&count_and_name).await;
+        outputs.sort();
+        assert_eq!(vec![String::from("count 1"), String::from("name TEST")], outputs);
+    }
+
+    #[tokio::test]
+    async fn side_outputs_are_queued_and_chained() {
+        chain_link!(Validate, input: String => i32, side: [rejected: String], {
+            match input.received {
+                Some(received) => {
+                    let text = received.read().await;
+                    match text.parse::<i32>() {
+                        Ok(number) => Some(number),
+                        Err(_) => {
+                            input.side.rejected.send(text.clone());
+                            None
+                        }
+                    }
+                },
+                None => None
+            }
+        });
+        chain_link!(StoreRejected => (stored: Arc<std::sync::Mutex<Vec<String>>>), input: String => (), owned, {
+            match input.received {
+                Some(received) => {
+                    let text = received.read().await.clone();
+                    input.initializer.read().await.stored.lock().unwrap().push(text);
+                    Some(())
+                },
+                None => None
+            }
+        });
+        chain_link!(Double, input: i32 => i32, {
+            match input.received {
+                Some(received) => Some(*received.read().await * 2),
+                None => None
+            }
+        });
+
+        type ValidateWithRejects = SideChain<ValidateRejected, StoreRejected>;
+
+        chain!(ValidateAndDouble, String => i32, [ValidateWithRejects => Double]: (all join));
+
+        let validate = Validate::new_raw(ValidateInitializer { }).await;
+        validate.push_raw(String::from("1")).await;
+        validate.push_raw(String::from("one")).await;
+        assert_eq!(vec![1], collect_until_idle(&validate).await);
+        assert_eq!("one", *validate.try_pop_rejected().await.unwrap().read().await);
+        assert!(validate.try_pop_rejected().await.is_none());
+
+        let stored = Arc::new(std::sync::Mutex::new(vec![]));
+        let validate_and_double = ValidateAndDouble::new_raw(ValidateAndDoubleInitializer::new(
+            SideChainInitializer::new(ValidateInitializer { }, StoreRejectedInitializer {
+                stored: stored.clone()
+            }),
+            DoubleInitializer { }
+        )).await;
+        assert_outputs(&validate_and_double, vec![
+            String::from("2"),
+            String::from("two"),
+            String::from("3"),
+            String::from("three")
+        ], vec![4, 6]).await;
+        assert_eq!(vec![String::from("two"), String::from("three")], *stored.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn flat_and_stream_map_blocks_produce_every_output() {
+        chain_link!(SplitWords, input: String => String, flat, {
+            match input.received {
+                Some(received) => received.read().await.split_whitespace().map(String::from).collect::<Vec<String>>(),
+                None => vec![]
+            }
+        });
+        chain_link!(CountUpTo, input: usize => usize, stream, {
+            match input.received {
+                Some(received) => futures::stream::iter(0..*received.read().await),
+                None => futures::stream::iter(0..0)
+            }
+        });
+
+        chain!(SplitWordsToInt, String => i32, [SplitWords => StringToInt]: (all join));
+
+        let split_words = SplitWords::new_raw(SplitWordsInitializer { }).await;
+        split_words.push_raw(String::from("one two three")).await;
+        let outcome = split_words.process().await;
+        assert_eq!((1, 3), (outcome.consumed_count, outcome.produced_count));
+        assert_eq!(vec![String::from("one"), String::from("two"), String::from("three")], collect_until_idle(&split_words).await);
+
+        let count_up_to = CountUpTo::new_raw(CountUpToInitializer { }).await;
+        assert_outputs(&count_up_to, vec![3, 0, 2], vec![0, 1, 2, 0, 1]).await;
+
+        // every word is passed along the sequence even though they are all produced at once
+        let split_words_to_int = SplitWordsToInt::new_raw(SplitWordsToIntInitializer::new(SplitWordsInitializer { }, StringToIntInitializer { })).await;
+        split_words_to_int.push_raw(String::from("test other test")).await;
+        assert_eq!(vec![1, 2, 1], collect_until_idle(&split_words_to_int).await);
+    }
+
+    #[tokio::test]
+    async fn panicking_inputs_are_dead_lettered_and_redriven() {
+        chain_link!(ParseNumber => (is_fixed: Arc<std::sync::atomic::AtomicBool>), input: String => i32, {
+            match input.received {
+                Some(received) => {
+                    let text = received.read().await;
+                    if input.initializer.read().await.is_fixed.load(std::sync::atomic::Ordering::SeqCst) {
+                        Some(text.len() as i32)
+                    }
+                    else {
+                        Some(text.parse::<i32>().expect("The text should be a number."))
+                    }
+                },
+                None => None
+            }
+        });
+
+        chain!(ParseAndMore, String => i32, [ParseNumber]: (all join));
+
+        let is_fixed = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let parse_and_more = ParseAndMore::new_raw(ParseAndMoreInitializer::new(ParseNumberInitializer {
+            is_fixed: is_fixed.clone()
+        })).await;
+        let dead_letter_queue = DeadLetterQueue::new();
+        parse_and_more.set_dead_letter_queue(dead_letter_queue.clone());
+        assert_outputs(&parse_and_more, vec![String::from("2"), String::from("two"), String::from("3")], vec![2, 3]).await;
+        assert_eq!(1, dead_letter_queue.len());
+
+        // an input that fails again after being redriven counts both attempts
+        assert_eq!(1, dead_letter_queue.redrive(&parse_and_more).await);
+        assert!(collect_until_idle(&parse_and_more).await.is_empty());
+        let dead_letter = dead_letter_queue.take_all().pop().unwrap();
+        assert_eq!("ParseAndMore/ParseNumber", dead_letter.path);
+        assert!(dead_letter.failure.is_panic());
+        assert!(dead_letter.failure.to_string().contains("The text should be a number."));
+        assert_eq!(2, dead_letter.attempt_count);
+        assert_eq!("two", *dead_letter.input::<String>().unwrap().read().await);
+        assert!(dead_letter.input::<i32>().is_none());
+
+        // once fixed, the redriven input is mapped
+        dead_letter_queue.push(dead_letter);
+        is_fixed.store(true, std::sync::atomic::Ordering::SeqCst);
+        let elsewhere = ParseNumber::new_raw(ParseNumberInitializer {
+            is_fixed: is_fixed.clone()
+        }).await;
+        assert_eq!(0, dead_letter_queue.redrive(&elsewhere).await);
+        assert_eq!(1, dead_letter_queue.redrive(&parse_and_more).await);
+        assert_eq!(vec![3], collect_until_idle(&parse_and_more).await);
+        assert!(dead_letter_queue.is_empty());
+    }
+
+    #[tokio::test]
+    async fn fallible_map_blocks_dead_letter_errors_and_failures_are_counted() {
+        chain_link!(TryParseNumber, input: String => i32, fallible = std::num::ParseIntError, {
+            match input.received {
+                Some(received) => Ok(Some(received.read().await.parse::<i32>()?)),
+                None => Ok(None)
+            }
+        });
+
+        chain_link!(PanickingParseNumber, input: String => i32, {
+            match input.received {
+                Some(received) => Some(received.read().await.parse::<i32>().expect("The text should be a number.")),
+                None => None
+            }
+        });
+
+        let try_parse_number = TryParseNumber::new_raw(TryParseNumberInitializer { }).await;
+        let dead_letter_queue = DeadLetterQueue::new();
+        try_parse_number.set_dead_letter_queue(dead_letter_queue.clone());
+        for text in ["1", "one", "2"] {
+            try_parse_number.push_raw(String::from(text)).await;
+        }
+        let outcome = try_parse_number.process().await;
+        assert_eq!((1, 1, 0), (outcome.consumed_count, outcome.produced_count, outcome.failed_count));
+        let outcome = try_parse_number.process().await;
+        assert_eq!((1, 0, 1), (outcome.consumed_count, outcome.produced_count, outcome.failed_count));
+        assert_eq!(vec![1, 2], collect_until_idle(&try_parse_number).await);
+        let dead_letter = dead_letter_queue.take_all().pop().unwrap();
+        assert_eq!("TryParseNumber", dead_letter.path);
+        assert_eq!("one".parse::<i32>().unwrap_err().to_string(), dead_letter.failure.to_string());
+        assert_eq!(Some(&"one".parse::<i32>().unwrap_err()), dead_letter.failure.error::<std::num::ParseIntError>());
+
+        // without a dead-letter queue, a panicking input is dropped rather than stopping the process
+        let panicking_parse_number = PanickingParseNumber::new_raw(PanickingParseNumberInitializer { }).await;
+        panicking_parse_number.push_raw(String::from("one")).await;
+        let outcome = panicking_parse_number.process().await;
+        assert_eq!((1, 0, 1), (outcome.consumed_count, outcome.produced_count, outcome.failed_count));
+        assert_outputs(&panicking_parse_number, vec![String::from("3")], vec![3]).await;
+
+        // a map block that panics without an input is counted as failed rather than stopping the process
+        chain_link!(PanickingWithoutInput, input: String => usize, {
+            Some(input.received.expect("There should be an input.").read().await.len())
+        });
+
+        let panicking_without_input = PanickingWithoutInput::new_raw(PanickingWithoutInputInitializer { }).await;
+        let outcome = panicking_without_input.process().await;
+        assert_eq!((0, 0, 1), (outcome.consumed_count, outcome.produced_count, outcome.failed_count));
+        panicking_without_input.push_raw(String::from("four")).await;
+        let outcome = panicking_without_input.process().await;
+        assert_eq!((1, 1, 1), (outcome.consumed_count, outcome.produced_count, outcome.failed_count));
+        assert_eq!(vec![4], collect_until_idle(&panicking_without_input).await);
+    }
+
+    #[tokio::test]
+    async fn retry_backs_off_and_exhausts_failing_inputs() {
+        #[derive(Debug, PartialEq)]
+        enum FlakyError {
+            Invalid,
+            Unavailable
+        }
+
+        impl std::fmt::Display for FlakyError {
+            fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    FlakyError::Invalid => formatter.write_str("The input is invalid."),
+                    FlakyError::Unavailable => formatter.write_str("The dependency is unavailable.")
+                }
+            }
+        }
+
+        chain_link!(Flaky => (failures_left: Arc<std::sync::atomic::AtomicUsize>), input: i32 => i32, fallible = FlakyError, {
+            match input.received {
+                Some(received) => {
+                    let number = *received.read().await;
+                    if number < 0 {
+                        return Err(FlakyError::Invalid);
+                    }
+                    if input.initializer.read().await.failures_left.fetch_update(std::sync::atomic::Ordering::SeqCst, std::sync::atomic::Ordering::SeqCst, |failures_left| failures_left.checked_sub(1)).is_ok() {
+                        return Err(FlakyError::Unavailable);
+                    }
+                    Ok(Some(number * 10))
+                },
+                None => Ok(None)
+            }
+        });
+        chain_link!(Slow, input: u64 => u64, {
+            match input.received {
+                Some(received) => {
+                    let seconds = *received.read().await;
+                    input.clock.sleep(Duration::from_secs(seconds)).await;
+                    Some(seconds)
+                },
+                None => None
+            }
+        });
+
+        type RetryFlaky = Retry<Flaky>;
+
+        chain!(RetryChain, i32 => i32, [RetryFlaky]: (all join));
+
+        async fn new_flaky(failures_left: usize) -> Flaky {
+            Flaky::new_raw(FlakyInitializer {
+                failures_left: Arc::new(std::sync::atomic::AtomicUsize::new(failures_left))
+            }).await
+        }
+
+        // the backoff doubles after each failure
+        let virtual_clock = VirtualClock::new();
+        let retry = Retry::new(RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(1),
+            is_retryable: |failure| failure.error::<FlakyError>() != Some(&FlakyError::Invalid),
+            ..RetryPolicy::default()
+        }, new_flaky(2).await);
+        retry.set_clock(Clock::from_virtual(virtual_clock.clone()));
+        retry.push_raw(1).await;
+        let outcome = retry.process().await;
+        assert_eq!((1, 0, true), (outcome.consumed_count, outcome.produced_count, outcome.is_busy));
+        virtual_clock.advance(Duration::from_secs(1));
+        let outcome = retry.process().await;
+        assert_eq!((0, true), (outcome.produced_count, outcome.is_busy));
+        virtual_clock.advance(Duration::from_secs(1));
+        assert_eq!(0, retry.process().await.produced_count);
+        virtual_clock.advance(Duration::from_secs(1));
+        let outcome = retry.process().await;
+        assert_eq!((1, false), (outcome.produced_count, outcome.is_busy));
+        assert_eq!(10, *retry.try_pop().await.unwrap().read().await);
+
+        // an error that is not retryable is not attempted again
+        retry.push_raw(-1).await;
+        assert!(!retry.process().await.is_busy);
+        let exhausted = retry.try_pop_exhausted().await.unwrap();
+        assert_eq!(("Flaky", 1), (exhausted.path.as_str(), exhausted.attempt_count));
+        assert_eq!(Some(&FlakyError::Invalid), exhausted.failure.error::<FlakyError>());
+
+        let slow = Retry::new(RetryPolicy {
+            max_attempts: 1,
+            attempt_timeout: Some(Duration::from_millis(10)),
+            ..RetryPolicy::default()
+        }, Slow::new_raw(SlowInitializer { }).await);
+        slow.push_raw(10).await;
+        slow.process().await;
+        assert!(slow.try_pop_exhausted().await.unwrap().failure.is_timeout());
+
+        // an input whose attempt timed out before the wrapped ChainLink took it from its queue is not pushed again
+        chain_link!(SlowToStart => (is_started: std::sync::atomic::AtomicBool, mapped_count: std::sync::atomic::AtomicUsize), input: i32 => i32, {
+            match input.received {
+                Some(received) => {
+                    input.initializer.read().await.mapped_count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    Some(*received.read().await)
+                },
+                None => {
+                    if !input.initializer.read().await.is_started.swap(true, std::sync::atomic::Ordering::SeqCst) {
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                    }
+                    None
+                }
+            }
+        });
+
+        let slow_to_start = Retry::new(RetryPolicy {
+            initial_backoff: Duration::ZERO,
+            attempt_timeout: Some(Duration::from_millis(10)),
+            ..RetryPolicy::default()
+        }, SlowToStart::new_raw(SlowToStartInitializer {
+            is_started: std::sync::atomic::AtomicBool::new(false),
+            mapped_count: std::sync::atomic::AtomicUsize::new(0)
+        }).await);
+        slow_to_start.push_raw(1).await;
+        slow_to_start.push_raw(2).await;
+        let outcome = slow_to_start.process().await;
+        assert_eq!((2, 0, true), (outcome.consumed_count, outcome.produced_count, outcome.is_busy));
+        assert_eq!(vec![1, 2], collect_until_idle(&slow_to_start).await);
+        assert_eq!(2, slow_to_start.chain_link().initializer.read().await.mapped_count.load(std::sync::atomic::Ordering::SeqCst));
+
+        // every failure of a single attempt is kept, the first being the one retried
+        chain_link!(SplitDigits, input: String => u32, flat, {
+            match input.received {
+                Some(received) => received.read().await.chars().filter_map(|digit| digit.to_digit(10)).collect::<Vec<u32>>(),
+                None => vec![]
+            }
+        });
+        chain_link!(RejectDigit, input: u32 => u32, fallible = FlakyError, {
+            match input.received {
+                Some(_) => Err(FlakyError::Invalid),
+                None => Ok(None)
+            }
+        });
+
+        chain!(RejectDigits, String => u32, [SplitDigits => RejectDigit]: (all join));
+
+        let reject_digits = Retry::new(RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        }, RejectDigits::new_raw(RejectDigitsInitializer::new(SplitDigitsInitializer { }, RejectDigitInitializer { })).await);
+        reject_digits.push_raw(String::from("12")).await;
+        assert!(collect_until_idle(&reject_digits).await.is_empty());
+        let mut exhausted_paths = vec![];
+        while let Some(exhausted) = reject_digits.try_pop_exhausted().await {
+            exhausted_paths.push(exhausted.path);
+        }
+        assert_eq!(vec![String::from("RejectDigits/RejectDigit"), String::from("RejectDigits")], exhausted_paths);
+
+        // within a chain, exhausted inputs go to the dead-letter queue with every attempt counted
+        let retry_chain = RetryChain::new_raw(RetryChainInitializer::new(RetryInitializer::new(RetryPolicy {
+            max_attempts: 2,
+            initial_backoff: Duration::ZERO,
+            ..RetryPolicy::default()
+        }, FlakyInitializer {
+            failures_left: Arc::new(std::sync::atomic::AtomicUsize::new(3))
+        }))).await;
+        let dead_letter_queue = DeadLetterQueue::new();
+        retry_chain.set_dead_letter_queue(dead_letter_queue.clone());
+        retry_chain.push_raw(2).await;
+        assert!(collect_until_idle(&retry_chain).await.is_empty());
+        let dead_letter = dead_letter_queue.take_all().pop().unwrap();
+        assert_eq!(("RetryChain/Flaky", 2), (dead_letter.path.as_str(), dead_letter.attempt_count));
+        dead_letter_queue.push(dead_letter);
+        assert_eq!(1, dead_letter_queue.redrive(&retry_chain).await);
+        assert_eq!(vec![20], collect_until_idle(&retry_chain).await);
+
+        // jitter shortens the backoff by a seeded random fraction
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_secs(1),
+            jitter: 0.5,
+            ..RetryPolicy::default()
+        };
+        let backoff = policy.backoff(2, &DeterministicScheduler::new(7));
+        assert!(backoff >= Duration::from_secs(1) && backoff <= Duration::from_secs(2));
+        assert_eq!(backoff, policy.backoff(2, &DeterministicScheduler::new(7)));
+    }
+
+    #[tokio::test]
+    async fn deadlines_are_watched_and_cancelled() {
+        chain_link!(Hang, input: u64 => u64, {
+            match input.received {
+                Some(received) => {
+                    let seconds = *received.read().await;
+                    input.clock.sleep(Duration::from_secs(seconds)).await;
+                    Some(seconds)
+                },
+                None => None
+            }
+        });
+
+        type DeadlineHang = Deadline<Hang>;
+
+        chain!(HangUnique, u64 => u64, [DeadlineHang]: (all unique));
+
+        // a watchdog that does not cancel shows the link as stuck until it returns
+        let virtual_clock = VirtualClock::new();
+        let deadline = Deadline::new(Duration::from_secs(1), Hang::new_raw(HangInitializer { }).await);
+        let watchdog = Watchdog::new();
+        deadline.set_clock(Clock::from_virtual(virtual_clock.clone()));
+        deadline.set_watchdog(watchdog.clone());
+        deadline.push_raw(5).await;
+        let (outcome, ()) = futures::join!(deadline.process(), async {
+            virtual_clock.advance(Duration::from_secs(1));
+            while watchdog.stuck().is_empty() {
+                tokio::task::yield_now().await;
+            }
+            let stuck_link = watchdog.stuck().pop().unwrap();
+            assert_eq!("Hang", stuck_link.path);
+            assert_eq!(Duration::from_secs(1), stuck_link.stuck_since - stuck_link.started);
+            virtual_clock.advance(Duration::from_secs(4));
+        });
+        assert_eq!(1, outcome.produced_count);
+        assert!(watchdog.stuck().is_empty());
+        let exceeded = watchdog.take_exceeded();
+        assert_eq!(1, exceeded.len());
+        assert!(!exceeded[0].is_cancelled);
+
+        // a cancelling watchdog frees a unique chain to process again
+        let hang_unique = HangUnique::new_raw(HangUniqueInitializer::new(DeadlineInitializer::new(Duration::from_secs(1), HangInitializer { }))).await;
+        let scheduler = Arc::new(DeterministicScheduler::new(0));
+        let virtual_clock = VirtualClock::new();
+        let watchdog = Watchdog::cancelling();
+        hang_unique.set_scheduler(scheduler.clone());
+        hang_unique.set_clock(Clock::from_virtual(virtual_clock.clone()));
+        hang_unique.set_watchdog(watchdog.clone());
+        hang_unique.push_raw(60).await;
+        assert!(hang_unique.process().await.is_deferred);
+        futures::join!(scheduler.run_until_idle(), async {
+            virtual_clock.advance(Duration::from_secs(1));
+        });
+        let exceeded = watchdog.take_exceeded();
+        assert_eq!(1, exceeded.len());
+        assert_eq!(("HangUnique/Hang", true), (exceeded[0].path.as_str(), exceeded[0].is_cancelled));
+
+        hang_unique.push_raw(0).await;
+        let outcome = hang_unique.process().await;
+        assert!(outcome.is_deferred && !outcome.is_busy);
+        scheduler.run_until_idle().await;
+        assert_eq!(0, *hang_unique.try_pop().await.unwrap().read().await);
+
+        // a cancelled input is consumed and routed, to the dead-letter queue when one is set
+        let virtual_clock = VirtualClock::new();
+        let deadline = Deadline::new(Duration::from_secs(1), Hang::new_raw(HangInitializer { }).await);
+        deadline.set_clock(Clock::from_virtual(virtual_clock.clone()));
+        deadline.set_watchdog(Watchdog::cancelling());
+        deadline.push_raw(60).await;
+        deadline.push_raw(0).await;
+        let (outcome, ()) = futures::join!(deadline.process(), async {
+            virtual_clock.advance(Duration::from_secs(1));
+        });
+        assert_eq!((1, 0, 1), (outcome.consumed_count, outcome.produced_count, outcome.failed_count));
+        assert_eq!(60, *deadline.try_pop_exceeded().await.unwrap().read().await);
+        // the cancelled input is no longer queued within the wrapped ChainLink
+        assert!(deadline.chain_link().input_queue.is_empty().await);
+        assert_outputs(&deadline, vec![], vec![0]).await;
+
+        // an input the wrapped ChainLink had yet to take when its invocation was cancelled is processed again rather than cancelled
+        chain_link!(SlowToStart => (is_started: std::sync::atomic::AtomicBool, mapped_count: std::sync::atomic::AtomicUsize), input: u64 => u64, {
+            match input.received {
+                Some(received) => {
+                    input.initializer.read().await.mapped_count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    Some(*received.read().await)
+                },
+                None => {
+                    if !input.initializer.read().await.is_started.swap(true, std::sync::atomic::Ordering::SeqCst) {
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                    }
+                    None
+                }
+            }
+        });
+
+        let slow_to_start = Deadline::new(Duration::from_millis(10), SlowToStart::new_raw(SlowToStartInitializer {
+            is_started: std::sync::atomic::AtomicBool::new(false),
+            mapped_count: std::sync::atomic::AtomicUsize::new(0)
+        }).await);
+        slow_to_start.set_watchdog(Watchdog::cancelling());
+        slow_to_start.push_raw(1).await;
+        slow_to_start.push_raw(2).await;
+        let outcome = slow_to_start.process().await;
+        assert_eq!((1, 0, 0, true), (outcome.consumed_count, outcome.produced_count, outcome.failed_count, outcome.is_busy));
+        assert!(slow_to_start.try_pop_exceeded().await.is_none());
+        assert_eq!(vec![1, 2], collect_until_idle(&slow_to_start).await);
+        assert_eq!(2, slow_to_start.chain_link().initializer.read().await.mapped_count.load(std::sync::atomic::Ordering::SeqCst));
+
+        let dead_letter_queue = DeadLetterQueue::new();
+        deadline.set_dead_letter_queue(dead_letter_queue.clone());
+        deadline.push_raw(60).await;
+        futures::join!(deadline.process(), async {
+            virtual_clock.advance(Duration::from_secs(1));
+        });
+        assert!(deadline.try_pop_exceeded().await.is_none());
+        let dead_letter = dead_letter_queue.take_all().pop().unwrap();
+        assert_eq!("Hang", dead_letter.path);
+        assert!(dead_letter.failure.is_timeout());
+        dead_letter_queue.push(dead_letter);
+        assert_eq!(1, dead_letter_queue.redrive(&deadline).await);
+    }
+
+    #[tokio::test]
+    async fn circuit_breakers_open_after_failures_and_probe_after_cooldown() {
+        chain_link!(Database => (is_down: Arc<std::sync::atomic::AtomicBool>, call_count: Arc<std::sync::atomic::AtomicUsize>), input: i32 => i32, {
+            match input.received {
+                Some(received) => {
+                    let locked_initializer = input.initializer.read().await;
+                    locked_initializer.call_count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    assert!(!locked_initializer.is_down.load(std::sync::atomic::Ordering::SeqCst), "The database is unavailable.");
+                    Some(*received.read().await)
+                },
+                None => None
+            }
+        });
+
+        chain_link!(Later, input: i32 => i32, {
+            match input.received {
+                Some(received) => Some(*received.read().await),
+                None => None
+            }
+        });
+
+        type StoreForLater = SideChain<CircuitBreakerFallback<Database>, Later>;
+
+        let is_down = Arc::new(std::sync::atomic::AtomicBool::new(true));
+        let call_count = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let new_database = || Database::new_raw(DatabaseInitializer {
+            is_down: is_down.clone(),
+            call_count: call_count.clone()
+        });
+        let policy = CircuitBreakerPolicy {
+            failure_threshold: 2,
+            cooldown: Duration::from_secs(10),
+            open_behavior: OpenBehavior::Fallback
+        };
+
+        // failures open the circuit, after which the database is no longer called
+        let virtual_clock = VirtualClock::new();
+        let circuit_breaker = CircuitBreaker::new(policy.clone(), new_database().await);
+        let dead_letter_queue = DeadLetterQueue::new();
+        circuit_breaker.set_clock(Clock::from_virtual(virtual_clock.clone()));
+        circuit_breaker.set_dead_letter_queue(dead_letter_queue.clone());
+        let mut transitions = circuit_breaker.subscribe();
+        for number in 1..=3 {
+            circuit_breaker.push_raw(number).await;
+        }
+        let outcome = circuit_breaker.process().await;
+        assert_eq!((1, 0, 1), (outcome.consumed_count, outcome.produced_count, outcome.failed_count));
+        assert!(collect_until_idle(&circuit_breaker).await.is_empty());
+        assert_eq!(CircuitState::Open, circuit_breaker.state());
+        assert_eq!(2, call_count.load(std::sync::atomic::Ordering::SeqCst));
+        let mut fallback = vec![];
+        while let Some(input) = circuit_breaker.try_pop_fallback().await {
+            fallback.push(*input.read().await);
+        }
+        assert_eq!(vec![3], fallback);
+        // the failed inputs are handled by the database itself
+        let dead_letters = dead_letter_queue.take_all();
+        assert_eq!(vec!["Database", "Database"], dead_letters.iter().map(|dead_letter| dead_letter.path.as_str()).collect::<Vec<_>>());
+
+        // once the cooldown has passed, a successful probe closes the circuit
+        is_down.store(false, std::sync::atomic::Ordering::SeqCst);
+        virtual_clock.advance(Duration::from_secs(10));
+        circuit_breaker.push_raw(4).await;
+        assert_eq!(1, circuit_breaker.process().await.produced_count);
+        assert_eq!(4, *circuit_breaker.try_pop().await.unwrap().read().await);
+        let mut states = vec![];
+        while let Ok(transition) = transitions.try_recv() {
+            states.push((transition.from, transition.to));
+        }
+        assert_eq!(vec![
+            (CircuitState::Closed, CircuitState::Open),
+            (CircuitState::Open, CircuitState::HalfOpen),
+            (CircuitState::HalfOpen, CircuitState::Closed)
+        ], states);
+
+        // inputs wait while the circuit is open, and a failed probe opens it again
+        is_down.store(true, std::sync::atomic::Ordering::SeqCst);
+        let holding = CircuitBreaker::new(CircuitBreakerPolicy {
+            failure_threshold: 1,
+            open_behavior: OpenBehavior::Hold,
+            ..policy.clone()
+        }, new_database().await);
+        holding.set_clock(Clock::from_virtual(virtual_clock.clone()));
+        holding.push_raw(5).await;
+        assert!(holding.process().await.is_failed());
+        holding.push_raw(6).await;
+        let outcome = holding.process().await;
+        assert_eq!((0, true, Some(Duration::from_secs(10))), (outcome.consumed_count, outcome.is_busy, outcome.due_in));
+        virtual_clock.advance(Duration::from_secs(10));
+        assert!(holding.process().await.is_failed());
+        assert_eq!(CircuitState::Open, holding.state());
+        holding.push_raw(7).await;
+        assert!(holding.process().await.is_busy);
+        is_down.store(false, std::sync::atomic::Ordering::SeqCst);
+        virtual_clock.advance(Duration::from_secs(10));
+        let outcome = holding.process().await;
+        assert_eq!((1, false), (outcome.produced_count, outcome.is_busy));
+        assert_eq!(CircuitState::Closed, holding.state());
+
+        // a probe passed to a chain processing in the background stays pending until its output is popped
+        chain!(DatabaseInBackground, i32 => i32, [Database]: (all free));
+        let scheduler = Arc::new(DeterministicScheduler::new(0));
+        let in_background = CircuitBreaker::new(CircuitBreakerPolicy {
+            failure_threshold: 1,
+            ..policy.clone()
+        }, DatabaseInBackground::new_raw(DatabaseInBackgroundInitializer::new(DatabaseInitializer {
+            is_down: is_down.clone(),
+            call_count: call_count.clone()
+        })).await);
+        in_background.set_scheduler(scheduler.clone());
+        in_background.push_raw(8).await;
+        in_background.push_raw(9).await;
+        let outcome = in_background.process().await;
+        assert_eq!((1, 0, true), (outcome.consumed_count, outcome.produced_count, outcome.is_deferred));
+        // the second input waits for the first to settle
+        assert_eq!(0, in_background.process().await.consumed_count);
+        scheduler.run_until_idle().await;
+        let outcome = in_background.process().await;
+        assert_eq!((0, 1), (outcome.consumed_count, outcome.produced_count));
+        assert_eq!(1, in_background.process().await.consumed_count);
+
+        // an input the chain filters out in the background succeeds once the chain is done with it, and one sent to the dead-letter queue fails
+        chain_link!(DropOdd, input: i32 => i32, {
+            match input.received {
+                Some(received) => {
+                    let number = *received.read().await;
+                    (number % 2 == 0).then_some(number)
+                },
+                None => None
+            }
+        });
+
+        chain!(DropOddInBackground, i32 => i32, [DropOdd]: (all free));
+
+        let filtering = CircuitBreaker::new(CircuitBreakerPolicy {
+            failure_threshold: 1,
+            ..policy.clone()
+        }, DropOddInBackground::new_raw(DropOddInBackgroundInitializer::new(DropOddInitializer { })).await);
+        filtering.set_scheduler(scheduler.clone());
+        filtering.push_raw(1).await;
+        filtering.push_raw(2).await;
+        assert_eq!(1, filtering.process().await.consumed_count);
+        scheduler.run_until_idle().await;
+        assert_eq!(0, filtering.process().await.consumed_count);
+        assert_eq!(CircuitState::Closed, filtering.state());
+        assert_eq!(1, filtering.process().await.consumed_count);
+        scheduler.run_until_idle().await;
+        assert_eq!(1, filtering.process().await.produced_count);
+
+        is_down.store(true, std::sync::atomic::Ordering::SeqCst);
+        let failing_in_background = CircuitBreaker::new(CircuitBreakerPolicy {
+            failure_threshold: 1,
+            ..policy.clone()
+        }, DatabaseInBackground::new_raw(DatabaseInBackgroundInitializer::new(DatabaseInitializer {
+            is_down: is_down.clone(),
+            call_count: call_count.clone()
+        })).await);
+        failing_in_background.set_scheduler(scheduler.clone());
+        failing_in_background.set_dead_letter_queue(DeadLetterQueue::new());
+        failing_in_background.push_raw(12).await;
+        failing_in_background.process().await;
+        scheduler.run_until_idle().await;
+        failing_in_background.process().await;
+        assert_eq!(CircuitState::Open, failing_in_background.state());
+
+        // the fallback can be routed to another ChainLink
+        is_down.store(true, std::sync::atomic::Ordering::SeqCst);
+        let store_for_later: StoreForLater = SideChain::new(CircuitBreaker::new(CircuitBreakerPolicy {
+            failure_threshold: 1,
+            ..policy
+        }, new_database().await), Later::new_raw(LaterInitializer { }).await);
+        store_for_later.push_raw(10).await;
+        store_for_later.push_raw(11).await;
+        collect_until_idle(&store_for_later).await;
+        assert!(store_for_later.chain_link().try_pop_fallback().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn rate_limits_defer_inputs_over_the_rate() {
+        chain_link!(CallApi => (limiter: RateLimiter), input: i32 => i32, rate_limit = limiter, concurrency = 4, {
+            match input.received {
+                Some(received) => Some(*received.read().await),
+                None => None
+            }
+        });
+
+        duplicate!(CallApiTwice, i32 => i32, CallApi, join);
+
+        async fn drain<TChainLink: ChainLink<TOutput = i32> + Sync>(chain_link: &TChainLink) -> usize {
+            let mut count = 0;
+            while chain_link.try_pop().await.is_some() {
+                count += 1;
+            }
+            count
+        }
+
+        // the token bucket within the initializer is shared by every duplicate
+        let virtual_clock = VirtualClock::new();
+        let call_api_twice = CallApiTwice::new_raw(CallApiTwiceInitializer::new(2, CallApiInitializer {
+            limiter: RateLimiter::token_bucket(3, Duration::from_secs(1))
+        })).await;
+        call_api_twice.set_clock(Clock::from_virtual(virtual_clock.clone()));
+        for number in 0..6 {
+            call_api_twice.push_raw(number).await;
+        }
+        call_api_twice.process().await;
+        // a limited ChainLink reports when the next token is refilled
+        let outcome = call_api_twice.process().await;
+        assert_eq!((true, Some(Duration::from_secs(1))), (outcome.is_busy, outcome.due_in));
+        assert_eq!(3, drain(&call_api_twice).await);
+        virtual_clock.advance(Duration::from_secs(2));
+        call_api_twice.process().await;
+        assert_eq!(2, drain(&call_api_twice).await);
+
+        // an unshared rate limit gives every duplicate its own token bucket
+        chain_link!(CallApiAlone => (limiter: RateLimiter), input: i32 => i32, rate_limit = limiter, unshared, concurrency = 4, {
+            match input.received {
+                Some(received) => Some(*received.read().await),
+                None => None
+            }
+        });
+
+        duplicate!(CallApiAloneTwice, i32 => i32, CallApiAlone, join);
+
+        let call_api_alone_twice = CallApiAloneTwice::new_raw(CallApiAloneTwiceInitializer::new(2, CallApiAloneInitializer {
+            limiter: RateLimiter::token_bucket(3, Duration::from_secs(1))
+        })).await;
+        call_api_alone_twice.set_clock(Clock::from_virtual(virtual_clock.clone()));
+        for number in 0..8 {
+            call_api_alone_twice.push_raw(number).await;
+        }
+        call_api_alone_twice.process().await;
+        assert!(call_api_alone_twice.process().await.is_busy);
+        assert_eq!(6, drain(&call_api_alone_twice).await);
+
+        // the leaky bucket passes one input every interval
+        let leaky = RateLimit::new(RateLimiter::leaky_bucket(Duration::from_secs(1)));
+        leaky.set_clock(Clock::from_virtual(virtual_clock.clone()));
+        for number in 0..3 {
+            leaky.push_raw(number).await;
+        }
+        let outcome = leaky.process().await;
+        assert_eq!((1, true, Some(Duration::from_secs(1))), (outcome.produced_count, outcome.is_busy, outcome.due_in));
+        virtual_clock.advance(Duration::from_millis(400));
+        let outcome = leaky.process().await;
+        assert_eq!((0, Some(Duration::from_millis(600))), (outcome.produced_count, outcome.due_in));
+        virtual_clock.advance(Duration::from_millis(600));
+        assert_eq!(1, leaky.process().await.produced_count);
+        assert_eq!(vec![0, 1], vec![*leaky.try_pop().await.unwrap().read().await, *leaky.try_pop().await.unwrap().read().await]);
+
+        // instances share the limit only when it is global
+        for is_global in [true, false] {
+            let initializer = Arc::new(RwLock::new(RateLimitInitializer::new(RateLimiter::token_bucket(1, Duration::from_secs(60)), is_global)));
+            let first: RateLimit<i32> = RateLimit::initialize(initializer.clone()).await;
+            let second: RateLimit<i32> = RateLimit::initialize(initializer).await;
+            first.push_raw(1).await;
+            second.push_raw(2).await;
+            assert_eq!(1, first.process().await.produced_count);
+            assert_eq!(!is_global, second.process().await.is_produced());
+        }
+    }
+
+    #[tokio::test]
+    async fn latest_inputs_replace_stale_ones() {
+        chain_link!(Steer, input: i32 => i32, {
+            match input.received {
+                Some(received) => Some(*received.read().await),
+                None => None
+            }
+        });
+
+        type LatestSteer = Conflate<i32>;
+
+        chain!(SteerLatest, i32 => i32, [LatestSteer => Steer]: (all join));
+
+        // a push that replaces drops whatever is still queued
+        let steer = Steer::new_raw(SteerInitializer { }).await;
+        steer.push_raw(1).await;
+        steer.push_raw(2).await;
+        steer.push_raw_replace(3).await;
+        assert_eq!(vec![3], collect_until_idle(&steer).await);
+
+        // within a chain, only the latest output of the previous ChainLink is kept
+        let steer_latest = SteerLatest::new_raw(SteerLatestInitializer::new(ConflateInitializer, SteerInitializer { })).await;
+        let conflate: Conflate<i32> = Conflate::new();
+        for number in 1..=3 {
+            conflate.push_raw(number).await;
+            steer_latest.push_raw(number).await;
+        }
+        assert_eq!(vec![3], collect_until_idle(&conflate).await);
+        assert_eq!(vec![3], collect_until_idle(&steer_latest).await);
+
+        // a debounce waits for the inputs to settle
+        let virtual_clock = VirtualClock::new();
+        let debounce = Debounce::new(Duration::from_secs(1));
+        debounce.set_clock(Clock::from_virtual(virtual_clock.clone()));
+        debounce.push_raw(1).await;
+        virtual_clock.advance(Duration::from_millis(600));
+        debounce.push_raw(2).await;
+        virtual_clock.advance(Duration::from_millis(600));
+        assert!(debounce.process().await.is_busy);
+        virtual_clock.advance(Duration::from_millis(400));
+        assert_eq!(1, debounce.process().await.produced_count);
+        assert_eq!(2, *debounce.try_pop().await.unwrap().read().await);
+        assert!(debounce.process().await.is_idle());
+
+        // a throttle lets the first input through at once and then the latest once per interval
+        let throttle = ThrottleLatest::new(Duration::from_secs(1));
+        throttle.set_clock(Clock::from_virtual(virtual_clock.clone()));
+        throttle.push_raw(1).await;
+        assert_eq!(1, throttle.process().await.produced_count);
+        throttle.push_raw(2).await;
+        throttle.push_raw(3).await;
+        assert!(throttle.process().await.is_busy);
+        virtual_clock.advance(Duration::from_secs(1));
+        assert_eq!(1, throttle.process().await.produced_count);
+        assert_eq!(vec![1, 3], vec![*throttle.try_pop().await.unwrap().read().await, *throttle.try_pop().await.unwrap().read().await]);
+    }
+
+    #[tokio::test]
+    async fn expired_inputs_are_not_processed_late() {
+        chain_link!(Move, input: i32 => i32, {
+            match input.received {
+                Some(received) => Some(*received.read().await),
+                None => None
+            }
+        });
+
+        type MoveOrLog = SideChain<Expired<Move>, Move>;
+
+        let virtual_clock = VirtualClock::new();
+        let clock = Clock::from_virtual(virtual_clock.clone());
+        let fresh_move = Expire::new(Duration::from_secs(2), Expiry::Route, Move::new_raw(MoveInitializer { }).await);
+        fresh_move.set_clock(clock.clone());
+
+        // an input that waited longer than the ttl is routed instead of processed
+        fresh_move.push_raw(1).await;
+        virtual_clock.advance(Duration::from_secs(3));
+        fresh_move.push_raw(2).await;
+        let outcome = fresh_move.process().await;
+        assert_eq!((2, 1), (outcome.consumed_count, outcome.produced_count));
+        assert_eq!(2, *fresh_move.try_pop().await.unwrap().read().await);
+        assert_eq!(1, *fresh_move.try_pop_expired().await.unwrap().read().await);
+
+        // an absolute deadline replaces the ttl, and live inputs are passed on one at a time
+        fresh_move.push_raw_until(3, clock.now() + Duration::from_secs(1)).await;
+        fresh_move.push_raw(4).await;
+        assert_eq!(1, fresh_move.process().await.produced_count);
+        virtual_clock.advance(Duration::from_millis(1500));
+        assert_eq!(1, fresh_move.process().await.produced_count);
+        assert_eq!(vec![3, 4], collect_until_idle(&fresh_move).await);
+        assert!(fresh_move.try_pop_expired().await.is_none());
+
+        // a chain processing in the background works on one input at a time, while the next waits within the Expire and can still expire
+        chain!(MoveTwiceInBackground, i32 => i32, [Move => Move]: (all free));
+        let scheduler = Arc::new(DeterministicScheduler::new(0));
+        let in_background = Expire::new(Duration::from_secs(2), Expiry::Route, MoveTwiceInBackground::new_raw(MoveTwiceInBackgroundInitializer::new(MoveInitializer { }, MoveInitializer { })).await);
+        in_background.set_scheduler(scheduler.clone());
+        in_background.set_clock(clock.clone());
+        in_background.push_raw(7).await;
+        in_background.push_raw(8).await;
+        let outcome = in_background.process().await;
+        assert_eq!((1, 0, true), (outcome.consumed_count, outcome.produced_count, outcome.is_deferred));
+        assert_eq!(0, in_background.process().await.consumed_count);
+        virtual_clock.advance(Duration::from_secs(3));
+        let mut outputs = vec![];
+        loop {
+            scheduler.run_until_idle().await;
+            let outcome = in_background.process().await;
+            while let Some(output) = in_background.try_pop().await {
+                outputs.push(*output.read().await);
+            }
+            if outcome.is_idle() {
+                break;
+            }
+        }
+        assert_eq!(vec![7], outputs);
+        assert_eq!(8, *in_background.try_pop_expired().await.unwrap().read().await);
+
+        // an input the chain filters out in the background lets the next one through
+        chain_link!(KeepEven, input: i32 => i32, {
+            match input.received {
+                Some(received) => {
+                    let number = *received.read().await;
+                    (number % 2 == 0).then_some(number)
+                },
+                None => None
+            }
+        });
+
+        chain!(KeepEvenInBackground, i32 => i32, [KeepEven]: (all free));
+
+        let filtering = Expire::new(Duration::from_secs(2), Expiry::Route, KeepEvenInBackground::new_raw(KeepEvenInBackgroundInitializer::new(KeepEvenInitializer { })).await);
+        filtering.set_scheduler(scheduler.clone());
+        filtering.set_clock(clock.clone());
+        filtering.push_raw(9).await;
+        filtering.push_raw(10).await;
+        let mut outputs = vec![];
+        for _ in 0..4 {
+            filtering.process().await;
+            scheduler.run_until_idle().await;
+            while let Some(output) = filtering.try_pop().await {
+                outputs.push(*output.read().await);
+            }
+        }
+        assert_eq!(vec![10], outputs);
+        assert!(filtering.try_pop_expired().await.is_none());
+
+        // dropped inputs go nowhere, and routed ones can be handed to another ChainLink
+        let dropping = Expire::new(Duration::ZERO, Expiry::Drop, Move::new_raw(MoveInitializer { }).await);
+        dropping.set_clock(clock.clone());
+        dropping.push_raw(5).await;
+        virtual_clock.advance(Duration::from_millis(1));
+        assert!(collect_until_idle(&dropping).await.is_empty());
+        assert!(dropping.try_pop_expired().await.is_none());
+        let move_or_log: MoveOrLog = SideChain::new(Expire::new(Duration::ZERO, Expiry::Route, Move::new_raw(MoveInitializer { }).await), Move::new_raw(MoveInitializer { }).await);
+        move_or_log.set_clock(clock);
+        move_or_log.push_raw(6).await;
+        virtual_clock.advance(Duration::from_millis(1));
+        assert_eq!(1, move_or_log.process().await.consumed_count);
+        assert!(move_or_log.chain_link().try_pop_expired().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn envelope_headers_travel_with_each_item() {
+        chain_link!(ParseAmount, input: String => i32, concurrency = 2, {
+            match input.received {
+                Some(received) => {
+                    input.headers.insert("parsed_by", "ParseAmount");
+                    received.read().await.parse::<i32>().ok()
+                },
+                None => None
+            }
+        });
+        chain_link!(DoubleAmount, input: i32 => i32, concurrency = 2, {
+            match input.received {
+                Some(received) => {
+                    let tenant = input.headers.get("tenant").unwrap_or(String::from("none"));
+                    input.headers.insert("tenant_seen", tenant);
+                    Some(*received.read().await * 2)
+                },
+                None => None
+            }
+        });
+
+        chain!(AmountPipeline, String => i32, [ParseAmount => DoubleAmount]: (all join));
+        chain!(AmountPipelineInBackground, String => i32, [ParseAmount => DoubleAmount]: (all free));
+
+        let pipeline = Enveloped::new(AmountPipeline::new_raw(AmountPipelineInitializer::new(ParseAmountInitializer { }, DoubleAmountInitializer { })).await);
+        pipeline.push_raw(Envelope::new(String::from("4")).with_header("correlation_id", "a").with_header("tenant", "acme")).await;
+        pipeline.push_raw(Envelope::new(String::from("5")).with_header("correlation_id", "b")).await;
+        let outputs = collect_until_idle(&pipeline).await;
+
+        // each output inherits the headers of its own input, along with those set along the way
+        assert_eq!(vec![8, 10], outputs.iter().map(|output| output.payload).collect::<Vec<_>>());
+        assert_eq!(vec![Some("a"), Some("b")], outputs.iter().map(|output| output.header("correlation_id")).collect::<Vec<_>>());
+        assert_eq!(vec![Some("acme"), Some("none")], outputs.iter().map(|output| output.header("tenant_seen")).collect::<Vec<_>>());
+        assert!(outputs.iter().all(|output| output.header("parsed_by") == Some("ParseAmount")));
+        assert_eq!(None, outputs[1].header("tenant"));
+
+        // the headers travel with each item, so inputs mapped together in the background keep their own
+        let scheduler = Arc::new(DeterministicScheduler::new(0));
+        let in_background = Enveloped::new(AmountPipelineInBackground::new_raw(AmountPipelineInBackgroundInitializer::new(ParseAmountInitializer { }, DoubleAmountInitializer { })).await);
+        in_background.set_scheduler(scheduler.clone());
+        for number in 1..=4 {
+            in_background.push_raw(Envelope::new(number.to_string()).with_header("correlation_id", number.to_string())).await;
+        }
+        let mut outputs = vec![];
+        for _ in 0..8 {
+            in_background.process().await;
+            scheduler.run_until_idle().await;
+            while let Some(output) = in_background.try_pop().await {
+                outputs.push(output.read().await.clone());
+            }
+        }
+        outputs.sort_by_key(|output| output.payload);
+        assert_eq!(vec![2, 4, 6, 8], outputs.iter().map(|output| output.payload).collect::<Vec<_>>());
+        assert!(outputs.iter().all(|output| output.header("correlation_id") == Some((output.payload / 2).to_string().as_str())));
+        assert!(outputs.iter().all(|output| output.header("tenant_seen") == Some("none")));
+
+        // a chain passes the headers of each item from one ChainLink to the next without an envelope
+        let headed = AmountPipeline::new_raw(AmountPipelineInitializer::new(ParseAmountInitializer { }, DoubleAmountInitializer { })).await;
+        headed.push_item_with_headers(Item::Owned(String::from("3")), HeaderMap::from([(String::from("tenant"), String::from("globex"))])).await;
+        headed.process().await;
+        let (output, headers) = headed.try_pop_item_with_headers().await.unwrap();
+        assert_eq!(6, *output.read().await);
+        assert_eq!(Some("globex"), headers.get("tenant_seen").map(String::as_str));
+        assert!(headed.try_pop_item_with_headers().await.is_none());
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn chain_to_chain() {
         let test = ChainToChain::new_raw(
@@ -377,7 +2169,7 @@ mod test {
             match input.received {
                 // we will pretend that it takes a little bit of time to check
                 Some(text) => {
-                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    tokio::task::yield_now().await;
                     let text = text.read().await;
                     if text.is_empty() {
                         Some(false)
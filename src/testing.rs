@@ -0,0 +1,97 @@
+use std::{fmt::Debug, path::Path, sync::Arc, time::Duration};
+use tokio::sync::RwLock;
+use crate::framework::{ChainLink, Initializable};
+
+// how many times a ChainLink is processed while waiting for it to become idle
+pub const PROCESS_ATTEMPTS: usize = 1000;
+// when set, golden files are rewritten instead of compared
+pub const UPDATE_GOLDEN_VARIABLE: &str = "RUSTY_CHAIN_UPDATE_GOLDEN";
+
+// processes until an output is available or the ChainLink has nothing left to do
+pub async fn pop_next<TChainLink>(chain_link: &TChainLink) -> Option<TChainLink::TOutput>
+where
+    TChainLink: ChainLink + Sync,
+    TChainLink::TOutput: Clone + Send + Sync
+{
+    for _ in 0..PROCESS_ATTEMPTS {
+        if let Some(output) = chain_link.try_pop_item().await {
+            return Some(output.into_owned().await);
+        }
+        let outcome = chain_link.process().await;
+        if outcome.is_idle() {
+            break;
+        }
+        if outcome.is_deferred || outcome.is_busy {
+            // background threads need time to finish, and held items need time to become due
+            tokio::time::sleep(outcome.due_in.unwrap_or_default().max(Duration::from_millis(1))).await;
+        }
+    }
+    if let Some(output) = chain_link.try_pop_item().await {
+        return Some(output.into_owned().await);
+    }
+    None
+}
+
+// processes until the ChainLink is idle, returning every output in order
+pub async fn collect_until_idle<TChainLink>(chain_link: &TChainLink) -> Vec<TChainLink::TOutput>
+where
+    TChainLink: ChainLink + Sync,
+    TChainLink::TOutput: Clone + Send + Sync
+{
+    let mut outputs = vec![];
+    while let Some(output) = pop_next(chain_link).await {
+        outputs.push(output);
+    }
+    outputs
+}
+
+// pushes every input and then collects every output until the ChainLink is idle
+pub async fn feed_and_collect<TChainLink>(chain_link: &TChainLink, inputs: Vec<TChainLink::TInput>) -> Vec<TChainLink::TOutput>
+where
+    TChainLink: ChainLink + Sync,
+    TChainLink::TInput: Send,
+    TChainLink::TOutput: Clone + Send + Sync
+{
+    for input in inputs {
+        chain_link.push_raw(input).await;
+    }
+    collect_until_idle(chain_link).await
+}
+
+pub async fn assert_outputs<TChainLink>(chain_link: &TChainLink, inputs: Vec<TChainLink::TInput>, expected: Vec<TChainLink::TOutput>)
+where
+    TChainLink: ChainLink + Sync,
+    TChainLink::TInput: Send,
+    TChainLink::TOutput: Clone + Debug + PartialEq + Send + Sync
+{
+    let actual = feed_and_collect(chain_link, inputs).await;
+    assert_eq!(expected, actual, "The ChainLink should produce the expected outputs.");
+}
+
+// feeds the same inputs twice, once into each of two ChainLinks sharing the initializer, asserting that the second run produces the same outputs as the first
+pub async fn assert_idempotent<TChainLink>(initializer: Arc<RwLock<TChainLink::TInitializer>>, inputs: Vec<TChainLink::TInput>) -> Vec<TChainLink::TOutput>
+where
+    TChainLink: ChainLink + Initializable + Sync,
+    TChainLink::TInput: Clone + Send,
+    TChainLink::TOutput: Clone + Debug + PartialEq + Send + Sync
+{
+    let first_outputs = feed_and_collect(&TChainLink::initialize(initializer.clone()).await, inputs.clone()).await;
+    let second_outputs = feed_and_collect(&TChainLink::initialize(initializer).await, inputs).await;
+    assert_eq!(first_outputs, second_outputs, "The ChainLink should produce the same outputs when the same inputs are run again.");
+    first_outputs
+}
+
+// compares the pretty debug output against the golden file, writing the file if it is missing or updates were requested
+pub fn assert_golden<T: Debug>(outputs: &[T], path: impl AsRef<Path>) {
+    let path = path.as_ref();
+    let actual = format!("{:#?}\n", outputs);
+    if std::env::var_os(UPDATE_GOLDEN_VARIABLE).is_some() || !path.exists() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).expect("The golden file directory should be created.");
+        }
+        std::fs::write(path, actual).expect("The golden file should be written.");
+        return;
+    }
+    let expected = std::fs::read_to_string(path).expect("The golden file should be readable.");
+    assert_eq!(expected, actual, "The outputs should match the golden file {}. Set {} to update it.", path.display(), UPDATE_GOLDEN_VARIABLE);
+}
@@ -0,0 +1,292 @@
+use std::{sync::Arc, time::{Duration, Instant}};
+use tokio::sync::{Mutex, RwLock};
+use crate::{dead_letter::{DeadLetter, Failure}, framework::{ChainLink, Initializable, ProcessOutcome, WrapperSettings, short_type_name}, item::Item, queue::Queue};
+
+// a ChainLink whose `process` has run past its deadline and has not yet returned
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StuckLink {
+    pub path: String,
+    // when the `process` invocation began
+    pub started: Instant,
+    // when the deadline passed
+    pub stuck_since: Instant
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadlineExceeded {
+    pub path: String,
+    pub started: Instant,
+    pub deadline: Duration,
+    // the `process` invocation was dropped rather than waited for
+    pub is_cancelled: bool
+}
+
+struct WatchdogState {
+    next_stuck_id: usize,
+    stuck: Vec<(usize, StuckLink)>,
+    exceeded: Vec<DeadlineExceeded>
+}
+
+// watches every Deadline it is set on, where clones share what they report
+// calling `set_watchdog` on a chain or duplicate hands it to every inner ChainLink
+pub struct Watchdog {
+    path: String,
+    is_cancelling: bool,
+    state: Arc<std::sync::Mutex<WatchdogState>>
+}
+
+impl Clone for Watchdog {
+    fn clone(&self) -> Self {
+        Watchdog {
+            path: self.path.clone(),
+            is_cancelling: self.is_cancelling,
+            state: self.state.clone()
+        }
+    }
+}
+
+impl Watchdog {
+    fn with_cancelling(is_cancelling: bool) -> Self {
+        Watchdog {
+            path: String::new(),
+            is_cancelling,
+            state: Arc::new(std::sync::Mutex::new(WatchdogState {
+                next_stuck_id: 0,
+                stuck: vec![],
+                exceeded: vec![]
+            }))
+        }
+    }
+    // reports ChainLinks that run past their deadline while still waiting for them
+    pub fn new() -> Self {
+        Watchdog::with_cancelling(false)
+    }
+    // reports ChainLinks that run past their deadline and drops their `process` invocation, losing any input it was mapping
+    pub fn cancelling() -> Self {
+        Watchdog::with_cancelling(true)
+    }
+    pub fn is_cancelling(&self) -> bool {
+        self.is_cancelling
+    }
+    // the same watchdog, reached from within the chain or ChainLink named `name`
+    pub fn within(&self, name: &str) -> Self {
+        Watchdog {
+            path: self.path_to(name),
+            is_cancelling: self.is_cancelling,
+            state: self.state.clone()
+        }
+    }
+    pub fn path_to(&self, name: &str) -> String {
+        if self.path.is_empty() {
+            name.to_string()
+        }
+        else {
+            format!("{}/{}", self.path, name)
+        }
+    }
+    // the ChainLinks that are currently past their deadline
+    pub fn stuck(&self) -> Vec<StuckLink> {
+        self.state.lock().unwrap().stuck.iter().map(|(_, stuck_link)| stuck_link.clone()).collect()
+    }
+    // every deadline exceeded since the last call
+    pub fn take_exceeded(&self) -> Vec<DeadlineExceeded> {
+        std::mem::take(&mut self.state.lock().unwrap().exceeded)
+    }
+    fn report(&self, exceeded: DeadlineExceeded) {
+        self.state.lock().unwrap().exceeded.push(exceeded);
+    }
+    fn mark_stuck(&self, stuck_link: StuckLink) -> usize {
+        let mut locked_state = self.state.lock().unwrap();
+        let stuck_id = locked_state.next_stuck_id;
+        locked_state.next_stuck_id += 1;
+        locked_state.stuck.push((stuck_id, stuck_link));
+        stuck_id
+    }
+    fn unmark_stuck(&self, stuck_id: usize) {
+        self.state.lock().unwrap().stuck.retain(|(id, _)| *id != stuck_id);
+    }
+}
+
+impl Default for Watchdog {
+    fn default() -> Self {
+        Watchdog::new()
+    }
+}
+
+pub struct DeadlineInitializer<TChainLinkInitializer> {
+    pub deadline: Duration,
+    pub chain_link: Arc<RwLock<TChainLinkInitializer>>
+}
+
+impl<TChainLinkInitializer> DeadlineInitializer<TChainLinkInitializer> {
+    pub fn new(deadline: Duration, chain_link: TChainLinkInitializer) -> Self {
+        DeadlineInitializer {
+            deadline,
+            chain_link: Arc::new(RwLock::new(chain_link))
+        }
+    }
+}
+
+// gives each input of the wrapped ChainLink a deadline for the `process` invocation that maps it, measured using the clock of the ChainLink
+// a deadline does nothing until a watchdog is set, which decides whether an invocation past its deadline is cancelled
+// a cancelled input counts as consumed and goes to the dead-letter queue when one is set, or else to `try_pop_exceeded`
+// an input still held by the wrapped ChainLink once its invocation is cancelled, such as one not yet taken from its queue, is not cancelled, being processed again before another input is pushed
+// inputs are passed to the wrapped ChainLink one at a time, so that a cancelled invocation only ever holds up a single input
+// within a chain!, name the type such as `Deadline<GetParentById>`, which stands in for the ChainLink within the path reported by the watchdog
+pub struct Deadline<TChainLink: ChainLink> {
+    deadline: Duration,
+    chain_link: TChainLink,
+    input_queue: Queue<Item<TChainLink::TInput>>,
+    // the input of a cancelled invocation that the wrapped ChainLink still holds
+    held: Mutex<Option<Arc<RwLock<TChainLink::TInput>>>>,
+    output_queue: Queue<Item<TChainLink::TOutput>>,
+    exceeded_queue: Queue<Item<TChainLink::TInput>>,
+    settings: WrapperSettings
+}
+
+impl<TChainLink: ChainLink> Deadline<TChainLink> {
+    pub fn new(deadline: Duration, chain_link: TChainLink) -> Self {
+        Deadline {
+            deadline,
+            chain_link,
+            input_queue: Queue::default(),
+            held: Mutex::new(None),
+            output_queue: Queue::default(),
+            exceeded_queue: Queue::default(),
+            settings: WrapperSettings::default()
+        }
+    }
+    pub fn chain_link(&self) -> &TChainLink {
+        &self.chain_link
+    }
+    // an input whose invocation was cancelled, when no dead-letter queue is set
+    pub async fn try_pop_exceeded(&self) -> Option<Arc<RwLock<TChainLink::TInput>>> {
+        self.exceeded_queue.try_pop().await.map(Item::into_shared)
+    }
+}
+
+#[async_trait::async_trait]
+impl<TChainLink> Initializable for Deadline<TChainLink>
+where
+    TChainLink: ChainLink + Initializable + Send,
+    TChainLink::TInitializer: Send + Sync
+{
+    type TInitializer = DeadlineInitializer<TChainLink::TInitializer>;
+
+    async fn initialize(initializer: Arc<RwLock<DeadlineInitializer<TChainLink::TInitializer>>>) -> Self {
+        let locked_initializer = initializer.read().await;
+        Deadline::new(locked_initializer.deadline, TChainLink::initialize(locked_initializer.chain_link.clone()).await)
+    }
+}
+
+impl<TChainLink> Deadline<TChainLink>
+where
+    TChainLink: ChainLink + Send + Sync,
+    TChainLink::TInput: Send + Sync + 'static,
+    TChainLink::TOutput: Send + Sync
+{
+    // processes the wrapped ChainLink, returning `None` when a cancelling watchdog dropped the invocation
+    async fn process_within_deadline(&self) -> Option<ProcessOutcome> {
+        let watchdog = self.settings.watchdog();
+        let Some(watchdog) = watchdog else {
+            return Some(self.chain_link.process().await);
+        };
+        let clock = self.settings.clock();
+        let started = clock.now();
+        let mut processing = self.chain_link.process();
+        tokio::select! {
+            outcome = &mut processing => return Some(outcome),
+            _ = clock.sleep(self.deadline) => { }
+        }
+
+        let path = watchdog.path_to(short_type_name::<TChainLink>());
+        watchdog.report(DeadlineExceeded {
+            path: path.clone(),
+            started,
+            deadline: self.deadline,
+            is_cancelled: watchdog.is_cancelling()
+        });
+        if watchdog.is_cancelling() {
+            return None;
+        }
+        let stuck_id = watchdog.mark_stuck(StuckLink {
+            path,
+            started,
+            stuck_since: clock.now()
+        });
+        let outcome = processing.await;
+        watchdog.unmark_stuck(stuck_id);
+        Some(outcome)
+    }
+}
+
+impl_chain_link! {
+    impl[TChainLink] Deadline<TChainLink>
+    where [
+        TChainLink: ChainLink + Send + Sync,
+        TChainLink::TInput: Send + Sync + 'static,
+        TChainLink::TOutput: Send + Sync
+    ]
+    (TChainLink::TInput => TChainLink::TOutput)
+    queued
+    headed
+    wrapping chain_link [set_scheduler, set_clock, set_dead_letter_queue, set_watchdog]
+    {
+        async fn redrive(&self, dead_letter: DeadLetter) -> Result<(), DeadLetter> {
+            let dead_letter_queue = self.settings.dead_letter_queue();
+            let is_matching_path = dead_letter_queue.is_some_and(|dead_letter_queue| dead_letter_queue.path_to(short_type_name::<TChainLink>()) == dead_letter.path);
+            match (is_matching_path, dead_letter.input::<TChainLink::TInput>()) {
+                (true, Some(input)) => {
+                    self.input_queue.push(Item::Shared(input)).await;
+                    Ok(())
+                },
+                _ => self.chain_link.redrive(dead_letter).await
+            }
+        }
+        async fn process(&self) -> ProcessOutcome {
+            let mut locked_held = self.held.lock().await;
+            // a held input was counted as consumed when it was popped
+            let (received, consumed_count) = match locked_held.take() {
+                Some(held) => (Some(held), 0),
+                None => {
+                    let received = self.input_queue.try_pop_with_headers().await.map(|(received, headers)| (received.into_shared(), headers));
+                    match received {
+                        Some((received, headers)) => {
+                            self.chain_link.push_item_with_headers(Item::Shared(received.clone()), headers).await;
+                            (Some(received), 1)
+                        },
+                        None => (None, 0)
+                    }
+                }
+            };
+            let mut outcome = match self.process_within_deadline().await {
+                Some(outcome) => outcome,
+                None => {
+                    let mut outcome = ProcessOutcome::idle();
+                    match received {
+                        Some(received) if Arc::strong_count(&received) > 1 => {
+                            *locked_held = Some(received);
+                            outcome.is_busy = true;
+                        },
+                        Some(received) => {
+                            match self.settings.dead_letter_queue() {
+                                Some(dead_letter_queue) => dead_letter_queue.send(received, short_type_name::<TChainLink>(), Failure::Timeout, 1),
+                                None => self.exceeded_queue.push(Item::Shared(received)).await
+                            }
+                            outcome.failed_count = 1;
+                        },
+                        None => { }
+                    }
+                    outcome
+                }
+            };
+            outcome.consumed_count = consumed_count;
+            outcome.produced_count = 0;
+            while let Some((output, headers)) = self.chain_link.try_pop_item_with_headers().await {
+                self.output_queue.push_with_headers(output, headers).await;
+                outcome.produced_count += 1;
+            }
+            outcome
+        }
+    }
+}
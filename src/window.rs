@@ -0,0 +1,227 @@
+use std::{sync::Arc, time::{Duration, Instant}};
+use tokio::sync::{Mutex, RwLock};
+use crate::{clock::Clock, framework::{Initializable, ProcessOutcome}, item::Item, queue::{ArrivalQueue, Queue}};
+
+// decides which inputs are reduced together
+// time is measured from when an input is pushed, using the clock of the ChainLink
+pub enum WindowKind<T> {
+    // every `size` inputs, without overlap
+    TumblingCount(usize),
+    // the latest `size` inputs, every `step` inputs
+    SlidingCount {
+        size: usize,
+        step: usize
+    },
+    // the inputs pushed within this duration of the first input of the window
+    TumblingTime(Duration),
+    // the inputs pushed within the last `size`, every `step` after the first input
+    SlidingTime {
+        size: Duration,
+        step: Duration
+    },
+    // the inputs pushed until no input has been pushed for `gap`
+    Session {
+        gap: Duration
+    },
+    // the inputs taken until one matches the delimiter, which closes the window without being part of it
+    Delimited(fn(&T) -> bool)
+}
+
+impl<T> Clone for WindowKind<T> {
+    fn clone(&self) -> Self {
+        match self {
+            WindowKind::TumblingCount(size) => WindowKind::TumblingCount(*size),
+            WindowKind::SlidingCount { size, step } => WindowKind::SlidingCount {
+                size: *size,
+                step: *step
+            },
+            WindowKind::TumblingTime(duration) => WindowKind::TumblingTime(*duration),
+            WindowKind::SlidingTime { size, step } => WindowKind::SlidingTime {
+                size: *size,
+                step: *step
+            },
+            WindowKind::Session { gap } => WindowKind::Session {
+                gap: *gap
+            },
+            WindowKind::Delimited(is_delimiter) => WindowKind::Delimited(*is_delimiter)
+        }
+    }
+}
+
+impl<T> WindowKind<T> {
+    fn is_timed(&self) -> bool {
+        matches!(self, WindowKind::TumblingTime(_) | WindowKind::SlidingTime { .. } | WindowKind::Session { .. })
+    }
+}
+
+pub struct WindowInitializer<T, TReducer> {
+    pub kind: WindowKind<T>,
+    pub reducer: TReducer
+}
+
+struct WindowState<T> {
+    items: Vec<T>,
+    // when each item was pushed
+    pushed: Vec<Instant>,
+    // when the current sliding time window closes
+    next_close: Option<Instant>
+}
+
+// reduces each window of inputs into one output
+// while a timed window is waiting, `process` reports that it is busy and when it closes so that drivers keep processing until then
+// within a chain!, name the type with a function pointer such as `Window<i32, i32, fn(&[i32]) -> i32>`
+pub struct Window<T, TOutput, TReducer> {
+    kind: WindowKind<T>,
+    reducer: TReducer,
+    input_queue: ArrivalQueue<T>,
+    state: Mutex<WindowState<T>>,
+    output_queue: Queue<Item<TOutput>>
+}
+
+impl<T, TOutput, TReducer> Window<T, TOutput, TReducer>
+where
+    TReducer: Fn(&[T]) -> TOutput
+{
+    pub fn new(kind: WindowKind<T>, reducer: TReducer) -> Self {
+        match &kind {
+            WindowKind::TumblingCount(size) => assert!(*size != 0, "The window size should be at least one."),
+            WindowKind::SlidingCount { size, step } => assert!(*size != 0 && *step != 0, "The window size and step should be at least one."),
+            WindowKind::SlidingTime { step, .. } => assert!(!step.is_zero(), "The window step should not be zero."),
+            _ => { }
+        }
+        Window {
+            kind,
+            reducer,
+            input_queue: ArrivalQueue::new(),
+            state: Mutex::new(WindowState {
+                items: vec![],
+                pushed: vec![],
+                next_close: None
+            }),
+            output_queue: Queue::default()
+        }
+    }
+    async fn close(&self, items: &[T]) {
+        self.output_queue.push(Item::Owned((self.reducer)(items))).await;
+    }
+}
+
+#[async_trait::async_trait]
+impl<T, TOutput, TReducer> Initializable for Window<T, TOutput, TReducer>
+where
+    TReducer: Fn(&[T]) -> TOutput + Clone + Send + Sync,
+    T: Send + Sync
+{
+    type TInitializer = WindowInitializer<T, TReducer>;
+
+    async fn initialize(initializer: Arc<RwLock<WindowInitializer<T, TReducer>>>) -> Self {
+        let locked_initializer = initializer.read().await;
+        Window::new(locked_initializer.kind.clone(), locked_initializer.reducer.clone())
+    }
+}
+
+impl_chain_link! {
+    impl[T, TOutput, TReducer] Window<T, TOutput, TReducer>
+    where [
+        T: Clone + Send + Sync,
+        TOutput: Send + Sync,
+        TReducer: Fn(&[T]) -> TOutput + Send + Sync
+    ]
+    (T => TOutput)
+    queued
+    {
+        fn set_clock(&self, clock: Clock) {
+            self.input_queue.set_clock(clock);
+        }
+        async fn process(&self) -> ProcessOutcome {
+            let now = self.input_queue.clock().now();
+            let mut locked_state = self.state.lock().await;
+            let received_items = self.input_queue.try_pop_up_to(usize::MAX).await;
+            let consumed_count = received_items.len();
+            let mut produced_count: usize = 0;
+
+            // count and delimited windows close as inputs are taken
+            for (pushed, received) in received_items {
+                let received = received.into_owned().await;
+                if let WindowKind::Delimited(is_delimiter) = &self.kind {
+                    if is_delimiter(&received) {
+                        let items = std::mem::take(&mut locked_state.items);
+                        locked_state.pushed.clear();
+                        self.close(&items).await;
+                        produced_count += 1;
+                        continue;
+                    }
+                }
+                locked_state.items.push(received);
+                locked_state.pushed.push(pushed);
+                match &self.kind {
+                    WindowKind::TumblingCount(size) if locked_state.items.len() == *size => {
+                        let items = std::mem::take(&mut locked_state.items);
+                        locked_state.pushed.clear();
+                        self.close(&items).await;
+                        produced_count += 1;
+                    },
+                    WindowKind::SlidingCount { size, step } if locked_state.items.len() == *size => {
+                        self.close(&locked_state.items).await;
+                        let step = (*step).min(*size);
+                        locked_state.items.drain(..step);
+                        locked_state.pushed.drain(..step);
+                        produced_count += 1;
+                    },
+                    _ => { }
+                }
+            }
+
+            // timed windows close once enough time has passed
+            let is_closing = match (&self.kind, locked_state.pushed.first(), locked_state.pushed.last()) {
+                (WindowKind::TumblingTime(duration), Some(first_pushed), _) => now.saturating_duration_since(*first_pushed) >= *duration,
+                (WindowKind::Session { gap }, _, Some(last_pushed)) => now.saturating_duration_since(*last_pushed) >= *gap,
+                _ => false
+            };
+            if is_closing {
+                let items = std::mem::take(&mut locked_state.items);
+                locked_state.pushed.clear();
+                self.close(&items).await;
+                produced_count += 1;
+            }
+            if let WindowKind::SlidingTime { size, step } = &self.kind {
+                if locked_state.next_close.is_none() {
+                    locked_state.next_close = locked_state.pushed.first().map(|first_pushed| *first_pushed + *step);
+                }
+                while let Some(next_close) = locked_state.next_close.filter(|next_close| *next_close <= now) {
+                    // the window holds the items pushed after `next_close - size` and no later than `next_close`
+                    let window_start = locked_state.pushed.partition_point(|pushed| next_close.saturating_duration_since(*pushed) >= *size && *pushed < next_close);
+                    let window_end = locked_state.pushed.partition_point(|pushed| *pushed <= next_close);
+                    if window_start < window_end {
+                        self.close(&locked_state.items[window_start..window_end]).await;
+                        produced_count += 1;
+                    }
+
+                    // the items that are too old for the next window are no longer needed
+                    let following_close = next_close + *step;
+                    let expired_count = locked_state.pushed.partition_point(|pushed| following_close.saturating_duration_since(*pushed) >= *size);
+                    locked_state.items.drain(..expired_count);
+                    locked_state.pushed.drain(..expired_count);
+                    locked_state.next_close = if locked_state.items.is_empty() {
+                        None
+                    }
+                    else {
+                        Some(following_close)
+                    };
+                }
+            }
+
+            let mut outcome = ProcessOutcome::consumed(consumed_count, produced_count);
+            outcome.is_busy = self.kind.is_timed() && !locked_state.items.is_empty();
+            // a waiting timed window closes once its first or last item is old enough, or at its next close when sliding
+            let closes_at = match (&self.kind, locked_state.pushed.first(), locked_state.pushed.last()) {
+                (WindowKind::TumblingTime(duration), Some(first_pushed), _) => Some(*first_pushed + *duration),
+                (WindowKind::Session { gap }, _, Some(last_pushed)) => Some(*last_pushed + *gap),
+                (WindowKind::SlidingTime { .. }, _, _) => locked_state.next_close,
+                _ => None
+            };
+            outcome.due_in = closes_at.map(|closes_at| closes_at.saturating_duration_since(now));
+            outcome
+        }
+    }
+}